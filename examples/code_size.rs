@@ -0,0 +1,47 @@
+//! Four routers of different types served on the same kind of connection, to
+//! compare the code size of the router core between versions of this crate.
+//!
+//! Build with `cargo build --release --example code_size` and compare the size of
+//! the `.text` section, e.g. with `size target/release/examples/code_size`.
+
+use std::net::Ipv4Addr;
+
+use embedded_io_adapters::tokio_1::FromTokio;
+use low_profile::{Router, Service};
+use tokio::net::TcpStream;
+
+async fn serve<S: Service>(service: &S, mut stream: TcpStream) {
+    let (reader, writer) = stream.split();
+    let _ = service
+        .serve(FromTokio::new(reader), FromTokio::new(writer))
+        .await;
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let socket = tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 8000)).await?;
+    let (stream, addr) = socket.accept().await?;
+
+    match addr.port() % 4 {
+        0 => serve(&Router::new().get("/", || async { "index" }), stream).await,
+        1 => {
+            let router = Router::new()
+                .get("/", || async { "index" })
+                .post("/", |body: heapless::String<8>| async move { body });
+            serve(&router, stream).await
+        }
+        2 => {
+            let router = Router::new().get("/", || async {
+                heapless::Vec::<u8, 4>::from_slice(b"data").unwrap()
+            });
+            serve(&router, stream).await
+        }
+        _ => {
+            let router = Router::new()
+                .post("/echo", |body: heapless::Vec<u8, 16>| async move { body })
+                .put("/", || async { "put" });
+            serve(&router, stream).await
+        }
+    }
+    Ok(())
+}
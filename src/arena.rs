@@ -0,0 +1,146 @@
+//! Request scoped bump allocation.
+
+use core::{cell::Cell, fmt, marker::PhantomData, mem, ptr::NonNull};
+
+/// A bump allocator over a user provided byte buffer.
+///
+/// Allocations are never freed individually, the whole arena is reset at once.
+/// When used with [`Router::serve_with_arena`](crate::Router::serve_with_arena)
+/// a fresh arena is created for every request and made available to extractors
+/// and routes through [`Parts::arena`](crate::Parts::arena).
+///
+/// Values allocated in the arena are never dropped.
+pub struct Arena<'a> {
+    start: NonNull<u8>,
+    capacity: usize,
+    used: Cell<usize>,
+    _buf: PhantomData<&'a mut [u8]>,
+}
+
+// Allocations never overlap, handing out mutable references from `&self` is sound.
+#[allow(clippy::mut_from_ref)]
+impl<'a> Arena<'a> {
+    /// Creates a new arena allocating from `buf`.
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self {
+            capacity: buf.len(),
+            start: NonNull::from(buf).cast(),
+            used: Cell::new(0),
+            _buf: PhantomData,
+        }
+    }
+
+    /// Total size of the arena in bytes.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Amount of bytes still available, not accounting for alignment.
+    pub fn remaining(&self) -> usize {
+        self.capacity - self.used.get()
+    }
+
+    /// Frees all allocations.
+    pub fn reset(&mut self) {
+        self.used.set(0);
+    }
+
+    /// Moves `value` into the arena.
+    pub fn alloc<T>(&self, value: T) -> Option<&mut T> {
+        let ptr = self.alloc_raw(mem::size_of::<T>(), mem::align_of::<T>())?;
+        let ptr = ptr.cast::<T>().as_ptr();
+        // SAFETY: `ptr` is valid, properly aligned and not aliased by any other allocation.
+        unsafe {
+            ptr.write(value);
+            Some(&mut *ptr)
+        }
+    }
+
+    /// Copies `src` into the arena.
+    pub fn alloc_slice_copy<T: Copy>(&self, src: &[T]) -> Option<&mut [T]> {
+        let ptr = self.alloc_raw(mem::size_of_val(src), mem::align_of::<T>())?;
+        let ptr = ptr.cast::<T>().as_ptr();
+        // SAFETY: `ptr` is valid for `src.len()` elements, properly aligned
+        // and not aliased by any other allocation.
+        unsafe {
+            ptr.copy_from_nonoverlapping(src.as_ptr(), src.len());
+            Some(core::slice::from_raw_parts_mut(ptr, src.len()))
+        }
+    }
+
+    /// Allocates a zeroed byte slice of length `len`.
+    pub fn alloc_bytes(&self, len: usize) -> Option<&mut [u8]> {
+        let ptr = self.alloc_raw(len, 1)?.as_ptr();
+        // SAFETY: `ptr` is valid for `len` bytes and not aliased by any other allocation.
+        unsafe {
+            ptr.write_bytes(0, len);
+            Some(core::slice::from_raw_parts_mut(ptr, len))
+        }
+    }
+
+    /// Copies `s` into the arena.
+    pub fn alloc_str(&self, s: &str) -> Option<&mut str> {
+        let bytes = self.alloc_slice_copy(s.as_bytes())?;
+        // SAFETY: the bytes were copied from a valid `str`.
+        Some(unsafe { core::str::from_utf8_unchecked_mut(bytes) })
+    }
+
+    /// Formats `args` into the arena, using all of the remaining space at most.
+    pub fn alloc_fmt(&self, args: fmt::Arguments<'_>) -> Option<&mut str> {
+        struct Writer<'b> {
+            buf: &'b mut [u8],
+            len: usize,
+        }
+
+        impl<'b> fmt::Write for Writer<'b> {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                let dst = self
+                    .buf
+                    .get_mut(self.len..self.len + s.len())
+                    .ok_or(fmt::Error)?;
+                dst.copy_from_slice(s.as_bytes());
+                self.len += s.len();
+                Ok(())
+            }
+        }
+
+        let used = self.used.get();
+        let buf = self.alloc_bytes(self.remaining())?;
+        let mut writer = Writer { buf, len: 0 };
+        if fmt::write(&mut writer, args).is_err() {
+            self.used.set(used);
+            return None;
+        }
+
+        // Give back the unused tail.
+        self.used.set(used + writer.len);
+        let Writer { buf, len } = writer;
+        // SAFETY: only complete `str`s were written into the buffer.
+        Some(unsafe { core::str::from_utf8_unchecked_mut(&mut buf[..len]) })
+    }
+
+    fn alloc_raw(&self, size: usize, align: usize) -> Option<NonNull<u8>> {
+        let start = self.start.as_ptr() as usize;
+        let used = self.used.get();
+
+        let aligned = (start + used).checked_add(align - 1)? & !(align - 1);
+        let offset = aligned - start;
+        let end = offset.checked_add(size)?;
+        if end > self.capacity {
+            return None;
+        }
+
+        self.used.set(end);
+        // SAFETY: `offset` is within the bounds of the buffer.
+        Some(unsafe { NonNull::new_unchecked(self.start.as_ptr().add(offset)) })
+    }
+}
+
+impl<'a> fmt::Debug for Arena<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Arena")
+            .field("capacity", &self.capacity)
+            .field("used", &self.used.get())
+            .finish()
+    }
+}
@@ -0,0 +1,74 @@
+//! Role/permission based authorization.
+//!
+//! Routes declare the permissions they require at registration time by
+//! wrapping their handler with [`require`]. The authenticated identity is
+//! extracted from the request like any other extractor and checked against
+//! the required permissions before the handler runs.
+
+use core::marker::PhantomData;
+
+use crate::{
+    either::Either, extract::utils::define_rejection, handler::HandlerFunction, FromRequestParts,
+    IntoResponse, Read, Request,
+};
+
+/// An authenticated identity which can be checked against permissions of type `P`.
+pub trait Identity<P> {
+    /// Returns `true` if the identity holds `permission`.
+    fn has_permission(&self, permission: &P) -> bool;
+}
+
+define_rejection! {
+    #[status = FORBIDDEN]
+    #[body = "Forbidden"]
+    /// The authenticated identity lacks a permission required by the route.
+    pub struct Forbidden;
+}
+
+/// Handler wrapper created by [`require`].
+pub struct RequirePermissions<I, P: 'static, H> {
+    permissions: &'static [P],
+    handler: H,
+    _identity: PhantomData<fn() -> I>,
+}
+
+/// Requires the identity `I` to hold all `permissions` before `handler` is invoked.
+///
+/// The identity is extracted using its [`FromRequestParts`] implementation,
+/// a failed extraction responds with the extractor's rejection,
+/// missing permissions respond with `403 Forbidden`.
+pub fn require<I, P, H>(permissions: &'static [P], handler: H) -> RequirePermissions<I, P, H> {
+    RequirePermissions {
+        permissions,
+        handler,
+        _identity: PhantomData,
+    }
+}
+
+#[doc(hidden)]
+pub struct Authorized<Params>(PhantomData<Params>);
+
+impl<S, I, E, P, H, Params> HandlerFunction<S, Authorized<Params>> for RequirePermissions<I, P, H>
+where
+    I: for<'a> FromRequestParts<'a, S, Rejection = E> + Identity<P>,
+    E: IntoResponse,
+    H: HandlerFunction<S, Params>,
+{
+    type Response = Either<H::Response, Either<E, Forbidden>>;
+
+    async fn call<Body: Read>(&self, req: Request<'_, Body>, state: &S) -> Self::Response {
+        let (mut parts, body) = req.into_parts();
+
+        let identity = match I::from_request_parts(&mut parts, state).await {
+            Ok(identity) => identity,
+            Err(err) => return Either::Right(Either::Left(err)),
+        };
+
+        if !self.permissions.iter().all(|p| identity.has_permission(p)) {
+            return Either::Right(Either::Right(Forbidden));
+        }
+
+        let req = Request::from_parts(parts, body);
+        Either::Left(self.handler.call(req, state).await)
+    }
+}
@@ -0,0 +1,145 @@
+//! Coalescing of concurrent identical requests.
+//!
+//! Wrapping a handler with [`coalesce`] deduplicates concurrent `GET` requests
+//! for the same path and query: while one request runs the handler, identical
+//! requests wait for it and respond with a clone of its response. This protects
+//! expensive endpoints, e.g. sensor reads, polled by multiple clients at once.
+//!
+//! Each wrapped handler tracks a single request in flight, concurrent requests
+//! for other paths or queries run the handler as usual. So do requests carrying
+//! credentials in an `Authorization` or `Cookie` header, their responses are
+//! never shared with other clients.
+
+use core::{cell::RefCell, fmt::Write as _, future::poll_fn, task::Poll};
+
+use heapless::String;
+
+use crate::{handler::HandlerFunction, utils::WakerSet, Method, Read, Request};
+
+/// Maximum amount of waiting requests woken individually, more waiting requests
+/// are still served but are woken spuriously.
+const WAITERS: usize = 4;
+
+/// Handler wrapper created by [`coalesce`].
+///
+/// `KEY` is the maximum length of the path and query coalesced on,
+/// longer requests are never coalesced.
+pub struct Coalesce<H, R, const KEY: usize = 64> {
+    handler: H,
+    flight: RefCell<Flight<R, KEY>>,
+}
+
+struct Flight<R, const KEY: usize> {
+    key: String<KEY>,
+    /// Incremented for every request running the handler on behalf of others.
+    generation: u64,
+    running: bool,
+    response: Option<R>,
+    wakers: WakerSet<WAITERS>,
+}
+
+/// Coalesces concurrent identical `GET` requests handled by `handler`.
+///
+/// The handler's response must be [`Clone`], it is kept until the next
+/// coalesced request starts.
+pub fn coalesce<H, R>(handler: H) -> Coalesce<H, R> {
+    Coalesce {
+        handler,
+        flight: RefCell::new(Flight {
+            key: String::new(),
+            generation: 0,
+            running: false,
+            response: None,
+            wakers: WakerSet::new(),
+        }),
+    }
+}
+
+/// Marks the flight as finished when dropped, also if the handler was cancelled.
+struct Leader<'f, R, const KEY: usize>(&'f RefCell<Flight<R, KEY>>);
+
+impl<'f, R, const KEY: usize> Drop for Leader<'f, R, KEY> {
+    fn drop(&mut self) {
+        let mut flight = self.0.borrow_mut();
+        flight.running = false;
+        flight.wakers.wake_all();
+    }
+}
+
+enum Role {
+    Leader,
+    Follower(u64),
+    Alone,
+}
+
+impl<S, H, Params, const KEY: usize> HandlerFunction<S, Params> for Coalesce<H, H::Response, KEY>
+where
+    H: HandlerFunction<S, Params>,
+    H::Response: Clone,
+{
+    type Response = H::Response;
+
+    async fn call<R: Read>(&self, req: Request<'_, R>, state: &S) -> Self::Response {
+        let mut key = String::<KEY>::new();
+        // Responses to requests with credentials may differ per client.
+        let credentials = ["Authorization", "Cookie"]
+            .iter()
+            .any(|name| req.parts.headers.get_first(name).is_some());
+        let keyed = req.method() == Method::GET
+            && !credentials
+            && write!(key, "{}", req.path()).is_ok()
+            && match req.parts.query {
+                Some(query) => write!(key, "?{query}").is_ok(),
+                None => true,
+            };
+
+        let role = {
+            let mut flight = self.flight.borrow_mut();
+            if !keyed {
+                Role::Alone
+            } else if !flight.running {
+                flight.key = key;
+                flight.generation += 1;
+                flight.running = true;
+                flight.response = None;
+                Role::Leader
+            } else if flight.key == key {
+                Role::Follower(flight.generation)
+            } else {
+                Role::Alone
+            }
+        };
+
+        match role {
+            Role::Leader => {
+                let leader = Leader(&self.flight);
+                let response = self.handler.call(req, state).await;
+                self.flight.borrow_mut().response = Some(response.clone());
+                drop(leader);
+                response
+            }
+            Role::Follower(generation) => {
+                let response = poll_fn(|cx| {
+                    let mut flight = self.flight.borrow_mut();
+                    if flight.generation != generation {
+                        // Missed the response, another flight started meanwhile.
+                        return Poll::Ready(None);
+                    }
+                    if !flight.running {
+                        // `None` if the leader was cancelled.
+                        return Poll::Ready(flight.response.clone());
+                    }
+                    flight.wakers.register(cx.waker());
+                    Poll::Pending
+                })
+                .await;
+
+                match response {
+                    Some(response) => response,
+                    None => self.handler.call(req, state).await,
+                }
+            }
+            Role::Alone => self.handler.call(req, state).await,
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Entity tags for responses and `304 Not Modified` for unchanged ones, see
+//! [`Router::etag`](crate::Router::etag).
+//!
+//! Clients polling a page send the entity tag of their copy in `If-None-Match`, the
+//! page is still rendered, but only sent if it changed:
+//!
+//! ```
+//! use core::fmt::Write;
+//! use low_profile::Router;
+//!
+//! async fn status() -> heapless::String<64> {
+//!     let mut status = heapless::String::new();
+//!     let _ = write!(status, "uptime: {}h", 42);
+//!     status
+//! }
+//!
+//! let router = Router::<(), _>::new().get("/status", status).etag();
+//! ```
+
+use core::fmt::Write as _;
+
+use crate::{
+    http::{if_none_match, ETag, StatusCode},
+    response::{ResponseBody, ResponseWriter, WriteError},
+    route::{Decision, Route},
+    utils, IntoResponse, Method, Read, Request, Response, Write,
+};
+
+pub(crate) struct ETagRoute<R> {
+    pub(crate) route: R,
+}
+
+impl<S, R: Route<S>> Route<S> for ETagRoute<R> {
+    type Response = Response<Tagged<<R::Response as IntoResponse>::Body>>;
+
+    async fn match_request<'a, Body: Read>(
+        &'a self,
+        req: Request<'a, Body>,
+        state: &'a S,
+    ) -> Decision<'a, Self::Response, Body> {
+        let method = req.parts.method;
+        let cacheable = method == Method::GET || method == Method::HEAD;
+        let condition = req.parts.headers.get_first("If-None-Match");
+
+        self.route.match_request(req, state).await.map(|response| {
+            let response = response.into_response();
+            let tag = match response.body().as_bytes() {
+                Some(bytes) if cacheable && response.status_code().is_success() => {
+                    Some(utils::fnv1a(bytes))
+                }
+                _ => None,
+            };
+            let modified = match (tag, condition) {
+                (Some(tag), Some(header)) => {
+                    if_none_match(header, Some(&ETag::strong(&format_tag(tag))))
+                }
+                _ => true,
+            };
+
+            let response = response.map_body(|inner| Tagged {
+                inner,
+                tag,
+                modified,
+            });
+            if modified {
+                response
+            } else {
+                (StatusCode::NOT_MODIFIED, response).into_response()
+            }
+        })
+    }
+}
+
+pub(crate) fn format_tag(tag: u64) -> heapless::String<16> {
+    let mut formatted = heapless::String::new();
+    // Can't fail, 16 hex digits fit.
+    let _ = write!(formatted, "{tag:016x}");
+    formatted
+}
+
+/// A body sent with an `ETag` header derived from its bytes, or left out for
+/// `304 Not Modified` responses.
+pub struct Tagged<B> {
+    inner: B,
+    tag: Option<u64>,
+    modified: bool,
+}
+
+impl<B: ResponseBody> ResponseBody for Tagged<B> {
+    type Error = B::Error;
+
+    fn content_length(&self) -> Option<usize> {
+        self.inner.content_length()
+    }
+
+    fn as_bytes(&self) -> Option<&[u8]> {
+        self.inner.as_bytes()
+    }
+
+    fn forward_to(&self) -> Option<&'static str> {
+        self.inner.forward_to()
+    }
+
+    async fn write_to<W: Write>(
+        self,
+        writer: &mut ResponseWriter<'_, W>,
+        buf: &mut [u8],
+    ) -> Result<(), WriteError<W::Error, Self::Error>> {
+        if let Some(tag) = self.tag {
+            let mut etag = heapless::String::<18>::new();
+            // Can't fail, the quoted tag fits.
+            let _ = write!(etag, "{}", ETag::strong(&format_tag(tag)));
+            writer
+                .write_header("ETag", &etag)
+                .await
+                .map_err(WriteError::Io)?;
+        }
+        if !self.modified {
+            return Ok(());
+        }
+        self.inner.write_to(writer, buf).await
+    }
+}
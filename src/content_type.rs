@@ -0,0 +1,121 @@
+//! Request body `Content-Type` enforcement.
+//!
+//! Routes declare the media type they accept by wrapping their handler with
+//! [`require`]. Requests without a matching `Content-Type` header are answered
+//! with `415 Unsupported Media Type` before any extractor reads the body.
+//!
+//! The media type of served files is derived from their name with [`from_extension`].
+
+use crate::{
+    either::Either, extract::utils::define_rejection, handler::HandlerFunction, Read, Request,
+};
+
+define_rejection! {
+    #[status = UNSUPPORTED_MEDIA_TYPE]
+    #[body = "Unsupported Media Type"]
+    /// The request body does not have the media type expected by the route.
+    pub struct UnsupportedMediaType;
+}
+
+/// Handler wrapper created by [`require`].
+pub struct RequireContentType<H> {
+    media_type: &'static str,
+    handler: H,
+}
+
+/// Requires the request `Content-Type` to match `media_type` before `handler` is invoked.
+///
+/// See [`matches`] for how the header is compared.
+pub fn require<H>(media_type: &'static str, handler: H) -> RequireContentType<H> {
+    RequireContentType {
+        media_type,
+        handler,
+    }
+}
+
+/// Returns `true` if the `Content-Type` header value `content_type` matches `media_type`.
+///
+/// Only the `type/subtype` essence is compared, ignoring case and parameters
+/// like `charset` or `boundary`. A `*` subtype in `media_type` matches any subtype.
+pub fn matches(content_type: &str, media_type: &str) -> bool {
+    fn split(value: &str) -> Option<(&str, &str)> {
+        let essence = value.split(';').next().unwrap_or_default().trim();
+        let (ty, subtype) = essence.split_once('/')?;
+        Some((ty.trim(), subtype.trim()))
+    }
+
+    match (split(content_type), split(media_type)) {
+        (Some((ty, subtype)), Some((expected_ty, expected_subtype))) => {
+            ty.eq_ignore_ascii_case(expected_ty)
+                && (expected_subtype == "*" || subtype.eq_ignore_ascii_case(expected_subtype))
+        }
+        _ => false,
+    }
+}
+
+/// Returns the media type of a file named `path` from its extension, compared
+/// ignoring case, `application/octet-stream` for unknown extensions.
+///
+/// ```
+/// use low_profile::content_type::from_extension;
+///
+/// assert_eq!(from_extension("/assets/app.JS"), "text/javascript; charset=utf-8");
+/// assert_eq!(from_extension("firmware.bin"), "application/octet-stream");
+/// ```
+pub fn from_extension(path: &str) -> &'static str {
+    const TYPES: &[(&str, &str)] = &[
+        ("html", "text/html; charset=utf-8"),
+        ("htm", "text/html; charset=utf-8"),
+        ("css", "text/css; charset=utf-8"),
+        ("js", "text/javascript; charset=utf-8"),
+        ("mjs", "text/javascript; charset=utf-8"),
+        ("json", "application/json"),
+        ("map", "application/json"),
+        ("txt", "text/plain; charset=utf-8"),
+        ("csv", "text/csv; charset=utf-8"),
+        ("xml", "application/xml"),
+        ("svg", "image/svg+xml"),
+        ("png", "image/png"),
+        ("jpg", "image/jpeg"),
+        ("jpeg", "image/jpeg"),
+        ("gif", "image/gif"),
+        ("webp", "image/webp"),
+        ("ico", "image/x-icon"),
+        ("woff", "font/woff"),
+        ("woff2", "font/woff2"),
+        ("ttf", "font/ttf"),
+        ("wasm", "application/wasm"),
+        ("pdf", "application/pdf"),
+        ("webmanifest", "application/manifest+json"),
+    ];
+
+    let name = path.rsplit('/').next().unwrap_or_default();
+    name.rsplit_once('.')
+        .and_then(|(_, extension)| {
+            TYPES
+                .iter()
+                .find(|(known, _)| extension.eq_ignore_ascii_case(known))
+        })
+        .map_or("application/octet-stream", |(_, media_type)| media_type)
+}
+
+impl<S, H, Params> HandlerFunction<S, Params> for RequireContentType<H>
+where
+    H: HandlerFunction<S, Params>,
+{
+    type Response = Either<H::Response, UnsupportedMediaType>;
+
+    async fn call<Body: Read>(&self, req: Request<'_, Body>, state: &S) -> Self::Response {
+        let matched = req
+            .parts
+            .headers
+            .get_first("Content-Type")
+            .is_some_and(|content_type| matches(content_type, self.media_type));
+
+        if !matched {
+            return Either::Right(UnsupportedMediaType);
+        }
+
+        Either::Left(self.handler.call(req, state).await)
+    }
+}
@@ -0,0 +1,152 @@
+//! A uniform build and version endpoint, so fleet tooling can query every device
+//! the same way.
+//!
+//! [`DeviceInfo`] is a handler serving the metadata as JSON, conventionally at
+//! [`PATH`]:
+//!
+//! ```
+//! use low_profile::{device_info::{self, DeviceInfo}, Router};
+//!
+//! struct App {
+//!     board: &'static str,
+//! }
+//!
+//! let info = DeviceInfo::new(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
+//!     .git_hash(option_env!("GIT_HASH"))
+//!     .crates(&[("embassy-net", "0.4.0")])
+//!     .hardware(|app: &App| app.board);
+//!
+//! let router = Router::new()
+//!     .get(device_info::PATH, info)
+//!     .with_state(App { board: "rev-b" });
+//! ```
+//!
+//! Responds with, leaving out the fields not set:
+//!
+//! ```json
+//! {"name":"firmware","version":"1.2.0","git":"4f1c2a9","hardware":"rev-b",
+//!  "low_profile":"0.1.0","crates":{"embassy-net":"0.4.0"}}
+//! ```
+
+use core::fmt::{self, Write as _};
+
+use crate::{
+    handler::HandlerFunction, io::Cursor, utils::JsonStr, IntoResponse, Read, Request, Response,
+};
+
+/// The path fleet tooling queries.
+pub const PATH: &str = "/.well-known/device-info";
+
+/// Maximum length of the JSON document.
+const BODY_SIZE: usize = 512;
+
+/// Handler serving build metadata of the firmware as JSON.
+///
+/// Fields not fitting into the 512 byte document are left out, except for the name
+/// and version.
+pub struct DeviceInfo<S = ()> {
+    name: &'static str,
+    version: &'static str,
+    git_hash: Option<&'static str>,
+    crates: &'static [(&'static str, &'static str)],
+    hardware: Option<fn(&S) -> &str>,
+}
+
+impl<S> DeviceInfo<S> {
+    /// Creates the metadata of the firmware `name` in `version`, usually
+    /// `env!("CARGO_PKG_NAME")` and `env!("CARGO_PKG_VERSION")` of the firmware crate.
+    pub const fn new(name: &'static str, version: &'static str) -> Self {
+        Self {
+            name,
+            version,
+            git_hash: None,
+            crates: &[],
+            hardware: None,
+        }
+    }
+
+    /// Sets the commit the firmware was built from, e.g. `option_env!("GIT_HASH")`
+    /// set by the build script.
+    pub const fn git_hash(mut self, git_hash: Option<&'static str>) -> Self {
+        self.git_hash = git_hash;
+        self
+    }
+
+    /// Lists the versions of notable dependencies as pairs of name and version.
+    pub const fn crates(mut self, crates: &'static [(&'static str, &'static str)]) -> Self {
+        self.crates = crates;
+        self
+    }
+
+    /// Reads the hardware revision from the router state.
+    pub fn hardware(mut self, hardware: fn(&S) -> &str) -> Self {
+        self.hardware = Some(hardware);
+        self
+    }
+
+    fn render(&self, state: &S) -> heapless::String<BODY_SIZE> {
+        let mut body = heapless::String::new();
+        // Names and versions are short, the fixed fields always fit.
+        let _ = write!(
+            body,
+            r#"{{"name":{},"version":{}"#,
+            JsonStr(self.name),
+            JsonStr(self.version)
+        );
+        if let Some(git_hash) = self.git_hash {
+            append(&mut body, format_args!(r#","git":{}"#, JsonStr(git_hash)));
+        }
+        if let Some(hardware) = self.hardware {
+            let hardware = hardware(state);
+            append(
+                &mut body,
+                format_args!(r#","hardware":{}"#, JsonStr(hardware)),
+            );
+        }
+        append(
+            &mut body,
+            format_args!(r#","low_profile":{}"#, JsonStr(env!("CARGO_PKG_VERSION"))),
+        );
+        if append(&mut body, format_args!(r#","crates":{{"#)) {
+            for (i, (name, version)) in self.crates.iter().enumerate() {
+                let separator = if i == 0 { "" } else { "," };
+                let item = format_args!("{separator}{}:{}", JsonStr(name), JsonStr(version));
+                if !append(&mut body, item) {
+                    break;
+                }
+            }
+            let _ = body.push('}');
+        }
+        let _ = body.push('}');
+        body
+    }
+}
+
+/// Appends `args` to `body` if it fits with the closing `}}`, so the document stays
+/// valid JSON. Returns `false` if it was left out.
+fn append(body: &mut heapless::String<BODY_SIZE>, args: fmt::Arguments<'_>) -> bool {
+    let len = body.len();
+    if body.write_fmt(args).is_err() || body.len() > BODY_SIZE - 2 {
+        body.truncate(len);
+        return false;
+    }
+    true
+}
+
+impl<S> Clone for DeviceInfo<S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S> Copy for DeviceInfo<S> {}
+
+impl<S> HandlerFunction<S, ()> for DeviceInfo<S> {
+    type Response = Response<Cursor<heapless::String<BODY_SIZE>>>;
+
+    async fn call<Body: Read>(&self, _req: Request<'_, Body>, state: &S) -> Self::Response {
+        self.render(state)
+            .into_response()
+            .with_content_type("application/json")
+    }
+}
@@ -0,0 +1,577 @@
+//! Verification of request bodies against the digest sent by the client.
+//!
+//! Wrapping a handler with [`verify`] checks the body it reads against the
+//! `Content-Digest` header ([RFC 9530]), or the older `Digest` header ([RFC 3230]),
+//! catching uploads corrupted on the way, e.g. over lossy links without TLS.
+//! Supported algorithms are `sha-256` and `crc32c`, the latter sent as the 4 bytes
+//! of the checksum in big-endian order.
+//!
+//! Reading the last bytes of a body which does not match fails with
+//! [`DigestError::Mismatch`], so handlers buffering the body never see corrupted data.
+//! Handlers streaming the body should only commit it, e.g. activate an uploaded
+//! firmware, once it has been read completely. The response of a handler reading a
+//! mismatching body is replaced with `400 Bad Request`.
+//!
+//! ```
+//! use low_profile::{digest, Router};
+//!
+//! fn apply_config(config: &[u8]) { /* ... */ }
+//!
+//! // Only called with a body matching its digest.
+//! async fn upload_config(config: heapless::Vec<u8, 1024>) -> &'static str {
+//!     apply_config(&config);
+//!     "ok"
+//! }
+//!
+//! let router = Router::<(), _>::new().put("/config", digest::verify(upload_config).required());
+//! ```
+//!
+//! Responses with bodies held in memory can be sent with a `Content-Digest` header
+//! using [`Digested`], letting clients verify them in turn.
+//!
+//! [RFC 9530]: https://www.rfc-editor.org/rfc/rfc9530
+//! [RFC 3230]: https://www.rfc-editor.org/rfc/rfc3230
+
+use core::{
+    cell::Cell,
+    fmt::{self, Write as _},
+};
+
+use embedded_io_async::ErrorKind;
+
+use crate::{
+    either::Either,
+    extract::utils::{composite_rejection, define_rejection},
+    handler::HandlerFunction,
+    request::{Body, BodyError, Headers},
+    response::{ResponseBody, ResponseWriter, WriteError},
+    ErrorType, IntoResponse, Read, Request, Response, Write,
+};
+
+define_rejection! {
+    #[status = BAD_REQUEST]
+    #[body = "Content digest mismatch"]
+    /// The body does not match its digest or the digest is malformed.
+    pub struct DigestMismatch;
+}
+
+define_rejection! {
+    #[status = BAD_REQUEST]
+    #[body = "Missing content digest"]
+    /// The request has no digest of a supported algorithm, see [`Verify::required`].
+    pub struct MissingDigest;
+}
+
+composite_rejection! {
+    /// Rejection used for [`verify`].
+    pub enum DigestRejection {
+        DigestMismatch,
+        MissingDigest,
+    }
+}
+
+/// Error reading a body verified by [`verify`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestError<E> {
+    /// The body does not match its digest.
+    Mismatch,
+    /// Reading the body failed.
+    Io(E),
+}
+
+impl<E: embedded_io_async::Error> embedded_io_async::Error for DigestError<E> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Mismatch => ErrorKind::InvalidData,
+            Self::Io(err) => err.kind(),
+        }
+    }
+}
+
+/// Handler wrapper created by [`verify`].
+pub struct Verify<H> {
+    handler: H,
+    required: bool,
+}
+
+/// Verifies the bodies read by `handler` against their digest, see the
+/// [module](self) documentation.
+///
+/// Requests without a digest of a supported algorithm are passed on unverified.
+pub fn verify<H>(handler: H) -> Verify<H> {
+    Verify {
+        handler,
+        required: false,
+    }
+}
+
+impl<H> Verify<H> {
+    /// Rejects requests without a digest of a supported algorithm.
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+}
+
+impl<S, H, Params> HandlerFunction<S, Params> for Verify<H>
+where
+    H: HandlerFunction<S, Params>,
+{
+    type Response = Either<H::Response, DigestRejection>;
+
+    async fn call<R: Read>(&self, req: Request<'_, R>, state: &S) -> Self::Response {
+        let expected = match expected(&req.parts.headers) {
+            Ok(Some(expected)) => expected,
+            Ok(None) if self.required => return Either::Right(MissingDigest.into()),
+            Ok(None) => return Either::Left(self.handler.call(req, state).await),
+            Err(err) => return Either::Right(err.into()),
+        };
+
+        let (parts, body) = req.into_parts();
+        let outcome = Cell::new(Outcome::Pending);
+        let body = body.wrap(|body| {
+            let mut verifier = Verifier {
+                body,
+                hasher: Hasher::new(expected.algorithm),
+                expected,
+                outcome: &outcome,
+            };
+            if verifier.body.remaining() == 0 {
+                verifier.finish();
+            }
+            verifier
+        });
+        if outcome.get() == Outcome::Mismatch {
+            return Either::Right(DigestMismatch.into());
+        }
+
+        let response = self
+            .handler
+            .call(Request::from_parts(parts, body), state)
+            .await;
+        match outcome.get() {
+            Outcome::Mismatch => Either::Right(DigestMismatch.into()),
+            _ => Either::Left(response),
+        }
+    }
+}
+
+/// A response or body sent with a `Content-Digest` header holding the SHA-256 of the body.
+///
+/// The digest is computed from the bytes of the body when it is created. Partial
+/// responses, see [`Router::accept_ranges`](crate::Router::accept_ranges), carry it
+/// as `Repr-Digest` instead, as it covers the whole body rather than the part sent.
+///
+/// ```
+/// use low_profile::{digest::Digested, Router};
+///
+/// const MANIFEST: &str = r#"{"version":"1.2.0","files":["app.js","style.css"]}"#;
+///
+/// let router = Router::<(), _>::new().get("/manifest.json", || async { Digested::new(MANIFEST) });
+/// ```
+pub struct Digested<T> {
+    digest: [u8; 32],
+    inner: T,
+}
+
+impl<T: AsRef<[u8]>> Digested<T> {
+    pub fn new(body: T) -> Self {
+        Self {
+            digest: sha256(body.as_ref()),
+            inner: body,
+        }
+    }
+}
+
+impl<T: IntoResponse> IntoResponse for Digested<T> {
+    type Body = Digested<T::Body>;
+
+    fn into_response(self) -> Response<Self::Body> {
+        let digest = self.digest;
+        self.inner
+            .into_response()
+            .map_body(|inner| Digested { digest, inner })
+    }
+}
+
+impl<B: ResponseBody> ResponseBody for Digested<B> {
+    type Error = B::Error;
+
+    fn content_length(&self) -> Option<usize> {
+        self.inner.content_length()
+    }
+
+    fn as_bytes(&self) -> Option<&[u8]> {
+        self.inner.as_bytes()
+    }
+
+    fn forward_to(&self) -> Option<&'static str> {
+        self.inner.forward_to()
+    }
+
+    async fn write_to<W: Write>(
+        self,
+        writer: &mut ResponseWriter<'_, W>,
+        buf: &mut [u8],
+    ) -> Result<(), WriteError<W::Error, Self::Error>> {
+        writer
+            .write_header(digest_header(writer), &content_digest(&self.digest))
+            .await
+            .map_err(WriteError::Io)?;
+        self.inner.write_to(writer, buf).await
+    }
+}
+
+/// Returns the SHA-256 of `data`.
+pub(crate) fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut sha = Sha256::new();
+    sha.update(data);
+    sha.finish()
+}
+
+/// Returns the name of the header holding the digest of the whole body, which is
+/// `Repr-Digest` if only a range of it is sent.
+pub(crate) fn digest_header<W: Write>(writer: &ResponseWriter<'_, W>) -> &'static str {
+    if writer.is_ranged() {
+        "Repr-Digest"
+    } else {
+        "Content-Digest"
+    }
+}
+
+/// Formats the `Content-Digest` header value of a SHA-256 `digest`.
+pub(crate) fn content_digest(digest: &[u8; 32]) -> heapless::String<64> {
+    let mut value = heapless::String::new();
+    // Can't overflow, the value is 54 bytes long.
+    let _ = write!(value, "sha-256=:{}:", Base64(digest));
+    value
+}
+
+/// Formats bytes as standard base64 with padding.
+struct Base64<'a>(&'a [u8]);
+
+impl<'a> fmt::Display for Base64<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const ALPHABET: &[u8; 64] =
+            b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+        for chunk in self.0.chunks(3) {
+            let bytes = [
+                chunk[0],
+                *chunk.get(1).unwrap_or(&0),
+                *chunk.get(2).unwrap_or(&0),
+            ];
+            let bits = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    let index = (bits >> (18 - 6 * i) & 0x3f) as usize;
+                    f.write_char(ALPHABET[index] as char)?;
+                } else {
+                    f.write_char('=')?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    /// The body has not been read completely yet.
+    Pending,
+    Verified,
+    Mismatch,
+}
+
+/// Reader hashing the body and comparing the digest once the body is read completely.
+struct Verifier<'a, 'o, R> {
+    body: Body<'a, R>,
+    hasher: Hasher,
+    expected: Expected,
+    outcome: &'o Cell<Outcome>,
+}
+
+impl<'a, 'o, R: Read> Verifier<'a, 'o, R> {
+    fn finish(&mut self) {
+        let outcome = if self.hasher.matches(&self.expected) {
+            Outcome::Verified
+        } else {
+            Outcome::Mismatch
+        };
+        self.outcome.set(outcome);
+    }
+}
+
+impl<'a, 'o, R: Read> ErrorType for Verifier<'a, 'o, R> {
+    type Error = DigestError<BodyError<R::Error>>;
+}
+
+impl<'a, 'o, R: Read> Read for Verifier<'a, 'o, R> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if self.outcome.get() == Outcome::Mismatch {
+            return Err(DigestError::Mismatch);
+        }
+        let len = self.body.read(buf).await.map_err(DigestError::Io)?;
+        if self.outcome.get() == Outcome::Pending {
+            self.hasher.update(&buf[..len]);
+            if len == 0 || self.body.remaining() == 0 {
+                self.finish();
+            }
+            if self.outcome.get() == Outcome::Mismatch {
+                return Err(DigestError::Mismatch);
+            }
+        }
+        Ok(len)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Algorithm {
+    Sha256,
+    Crc32c,
+}
+
+impl Algorithm {
+    fn from_name(name: &str) -> Option<Self> {
+        if name.eq_ignore_ascii_case("sha-256") {
+            Some(Self::Sha256)
+        } else if name.eq_ignore_ascii_case("crc32c") {
+            Some(Self::Crc32c)
+        } else {
+            None
+        }
+    }
+
+    fn len(self) -> usize {
+        match self {
+            Self::Sha256 => 32,
+            Self::Crc32c => 4,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Expected {
+    algorithm: Algorithm,
+    digest: [u8; 32],
+}
+
+/// Returns the digest sent with the request, preferring `Content-Digest` and `sha-256`.
+///
+/// Fails if the digest of a supported algorithm is malformed.
+fn expected(headers: &Headers<'_>) -> Result<Option<Expected>, DigestMismatch> {
+    let (header, colons) = match headers.get_first("Content-Digest") {
+        Some(header) => (header, true),
+        None => match headers.get_first("Digest") {
+            Some(header) => (header, false),
+            None => return Ok(None),
+        },
+    };
+
+    let mut found = None;
+    for member in header.split(',') {
+        let (name, value) = member.split_once('=').unwrap_or((member, ""));
+        let Some(algorithm) = Algorithm::from_name(name.trim()) else {
+            continue;
+        };
+        let value = value.trim();
+        // Content-Digest values are structured field byte sequences.
+        let value = if colons {
+            value
+                .strip_prefix(':')
+                .and_then(|value| value.strip_suffix(':'))
+                .ok_or(DigestMismatch)?
+        } else {
+            value
+        };
+
+        let mut digest = [0; 32];
+        if base64_decode(value, &mut digest) != Some(algorithm.len()) {
+            return Err(DigestMismatch);
+        }
+        let expected = Expected { algorithm, digest };
+        if algorithm == Algorithm::Sha256 {
+            return Ok(Some(expected));
+        }
+        found = Some(expected);
+    }
+    Ok(found)
+}
+
+/// Decodes standard base64 with optional padding into `out`, returns the decoded length.
+pub(crate) fn base64_decode(input: &str, out: &mut [u8]) -> Option<usize> {
+    let mut len = 0;
+    let mut acc = 0u32;
+    let mut bits = 0;
+    for c in input.trim_end_matches('=').bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        acc = acc << 6 | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            *out.get_mut(len)? = (acc >> bits) as u8;
+            len += 1;
+        }
+    }
+    Some(len)
+}
+
+enum Hasher {
+    Sha256(Sha256),
+    Crc32c(u32),
+}
+
+impl Hasher {
+    fn new(algorithm: Algorithm) -> Self {
+        match algorithm {
+            Algorithm::Sha256 => Self::Sha256(Sha256::new()),
+            Algorithm::Crc32c => Self::Crc32c(!0),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Sha256(sha) => sha.update(data),
+            Self::Crc32c(crc) => {
+                for &byte in data {
+                    *crc = CRC32C[((*crc ^ u32::from(byte)) & 0xff) as usize] ^ *crc >> 8;
+                }
+            }
+        }
+    }
+
+    fn matches(&self, expected: &Expected) -> bool {
+        match self {
+            Self::Sha256(sha) => sha.clone().finish() == expected.digest,
+            Self::Crc32c(crc) => (!crc).to_be_bytes() == expected.digest[..4],
+        }
+    }
+}
+
+/// Lookup table of the reflected CRC-32C (Castagnoli) polynomial.
+const CRC32C: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                crc >> 1 ^ 0x82f6_3b78
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// Streaming SHA-256, FIPS 180-4.
+#[derive(Clone)]
+struct Sha256 {
+    state: [u32; 8],
+    block: [u8; 64],
+    len: u64,
+}
+
+impl Sha256 {
+    fn new() -> Self {
+        Self {
+            state: [
+                0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+                0x5be0cd19,
+            ],
+            block: [0; 64],
+            len: 0,
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            let pos = (self.len % 64) as usize;
+            let len = data.len().min(64 - pos);
+            self.block[pos..pos + len].copy_from_slice(&data[..len]);
+            self.len += len as u64;
+            data = &data[len..];
+            if pos + len == 64 {
+                self.compress();
+            }
+        }
+    }
+
+    fn finish(mut self) -> [u8; 32] {
+        let bits = self.len * 8;
+        self.update(&[0x80]);
+        while self.len % 64 != 56 {
+            self.update(&[0]);
+        }
+        self.update(&bits.to_be_bytes());
+
+        let mut digest = [0; 32];
+        for (bytes, word) in digest.chunks_exact_mut(4).zip(self.state) {
+            bytes.copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+
+    fn compress(&mut self) {
+        let mut w = [0u32; 64];
+        for (word, bytes) in w.iter_mut().zip(self.block.chunks_exact(4)) {
+            *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ w[i - 15] >> 3;
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ w[i - 2] >> 10;
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+        for (k, w) in SHA256_K.iter().zip(w) {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(*k)
+                .wrapping_add(w);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (state, value) in self.state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+}
@@ -2,8 +2,11 @@ use core::fmt::Debug;
 
 use embedded_io_async::{Error, ErrorKind};
 
-use crate::{ErrorType, IntoResponse, Read, Response};
+use crate::{IntoResponse, Response};
 
+/// One of two responses, bodies or errors, e.g. returned by a [`Layer`](crate::layer::Layer)
+/// answering some requests itself.
+#[derive(Clone)]
 pub enum Either<L, R> {
     Left(L),
     Right(R),
@@ -27,19 +30,6 @@ impl<L: Error, R: Error> Error for Either<L, R> {
     }
 }
 
-impl<L: ErrorType, R: ErrorType> ErrorType for Either<L, R> {
-    type Error = Either<L::Error, R::Error>;
-}
-
-impl<L: Read, R: Read> Read for Either<L, R> {
-    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
-        match self {
-            Either::Left(left) => left.read(buf).await.map_err(Either::Left),
-            Either::Right(right) => right.read(buf).await.map_err(Either::Right),
-        }
-    }
-}
-
 impl<L: IntoResponse, R: IntoResponse> IntoResponse for Either<L, R> {
     type Body = Either<L::Body, R::Body>;
 
@@ -0,0 +1,324 @@
+//! Static data stored in flash, e.g. the files of a web UI, JSON schemas or
+//! translation bundles.
+//!
+//! [`ServeStatic`] serves files as they are embedded:
+//!
+//! ```
+//! use low_profile::{embed::ServeStatic, Router};
+//!
+//! # /*
+//! const INDEX: ServeStatic = ServeStatic::new("index.html", include_bytes!("ui/index.html"));
+//! const APP: ServeStatic = ServeStatic::new("app.js", include_bytes!("ui/app.js"))
+//!     .with_etag(env!("UI_HASH"));
+//! # */
+//! # const INDEX: ServeStatic = ServeStatic::new("index.html", b"<script src=app.js></script>");
+//! # const APP: ServeStatic = ServeStatic::new("app.js", b"alert(1)").with_etag("1f2e");
+//!
+//! let router = Router::<(), _>::new()
+//!     .get("/", INDEX)
+//!     .get("/app.js", APP);
+//! ```
+//!
+//! [`Embedded`] data is compressed at build time and sent as is with a `Content-Encoding`
+//! header, the client decompresses it. An `ETag` derived from the data at compile
+//! time lets clients revalidate their cached copy, which is answered with
+//! `304 Not Modified` without sending the data again:
+//!
+//! ```
+//! use low_profile::{embed::Embedded, Router};
+//!
+//! // Compressed with `gzip -9 -n translations/de.json`.
+//! # /*
+//! const GERMAN: Embedded = Embedded::gzip(include_bytes!("translations/de.json.gz"), "application/json");
+//! # */
+//! # const GERMAN: Embedded = Embedded::gzip(&[
+//! #     0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0xab, 0x56, 0x4a, 0x2f,
+//! #     0x4a, 0x4d, 0x2d, 0xc9, 0xcc, 0x4b, 0x57, 0xb2, 0x52, 0xf2, 0x48, 0xcc, 0xc9, 0xc9,
+//! #     0x57, 0xaa, 0x05, 0x00, 0x34, 0xc2, 0xa7, 0xfe, 0x14, 0x00, 0x00, 0x00,
+//! # ], "application/json");
+//!
+//! let router = Router::<(), _>::new().get("/i18n/de.json", GERMAN);
+//! ```
+
+use core::fmt::Write as _;
+
+use crate::{
+    content_type, digest,
+    either::Either,
+    extract::utils::define_rejection,
+    handler::HandlerFunction,
+    http::{if_none_match, ETag, StatusCode},
+    response::{ResponseBody, ResponseWriter, WriteError},
+    utils, IntoResponse, Read, Request, Response, Write,
+};
+
+/// Maximum length of the entity tag of a [`ServeStatic`] file.
+const ETAG: usize = 64;
+
+define_rejection! {
+    #[status = NOT_ACCEPTABLE]
+    #[body = "Not Acceptable"]
+    /// The client does not accept the encoding of the [`Embedded`] data.
+    pub struct NotAcceptable;
+}
+
+/// Compressed static data served as handler, see the [module](self) documentation.
+///
+/// Requests with an `Accept-Encoding` header not allowing the encoding are
+/// answered with `406 Not Acceptable`, requests without one get the compressed data.
+#[derive(Debug, Clone, Copy)]
+pub struct Embedded {
+    data: &'static [u8],
+    content_type: &'static str,
+    encoding: &'static str,
+    etag: u64,
+    digest: bool,
+}
+
+impl Embedded {
+    /// Embeds gzip compressed `data` of the media type `content_type`.
+    pub const fn gzip(data: &'static [u8], content_type: &'static str) -> Self {
+        Self::new(data, content_type, "gzip")
+    }
+
+    /// Embeds `data` compressed with `encoding`, e.g. `br`, of the media type `content_type`.
+    ///
+    /// The entity tag is a hash of `data`, computed at compile time when used in a `const`.
+    pub const fn new(
+        data: &'static [u8],
+        content_type: &'static str,
+        encoding: &'static str,
+    ) -> Self {
+        Self {
+            data,
+            content_type,
+            encoding,
+            etag: utils::fnv1a(data),
+            digest: false,
+        }
+    }
+
+    /// Sends a `Content-Digest` header with the SHA-256 of the compressed data,
+    /// computed for every response, or `Repr-Digest` for partial responses,
+    /// see [`Digested`](crate::digest::Digested).
+    pub const fn with_digest(mut self) -> Self {
+        self.digest = true;
+        self
+    }
+
+    /// The compressed data.
+    pub fn data(&self) -> &'static [u8] {
+        self.data
+    }
+
+    /// The opaque tag of the strong entity tag sent with the data.
+    pub fn etag(&self) -> heapless::String<16> {
+        let mut tag = heapless::String::new();
+        // Can't fail, 16 hex digits fit.
+        let _ = write!(tag, "{:016x}", self.etag);
+        tag
+    }
+}
+
+/// Returns `true` if the `Accept-Encoding` header value `header` allows `encoding`.
+fn accepts(header: &str, encoding: &str) -> bool {
+    header.split(',').any(|item| {
+        let mut params = item.split(';');
+        let coding = params.next().unwrap_or_default().trim();
+        let q = params
+            .find_map(|param| param.trim().strip_prefix("q="))
+            .and_then(|q| q.trim().parse::<f32>().ok())
+            .unwrap_or(1.0);
+        (coding == "*" || coding.eq_ignore_ascii_case(encoding)) && q > 0.0
+    })
+}
+
+impl<S> HandlerFunction<S, ()> for Embedded {
+    type Response = Either<Response<EmbeddedBody>, NotAcceptable>;
+
+    async fn call<Body: Read>(&self, req: Request<'_, Body>, _state: &S) -> Self::Response {
+        let headers = &req.parts.headers;
+        if headers
+            .get_first("Accept-Encoding")
+            .is_some_and(|header| !accepts(header, self.encoding))
+        {
+            return Either::Right(NotAcceptable);
+        }
+
+        let tag = self.etag();
+        let modified = match headers.get_first("If-None-Match") {
+            Some(header) => if_none_match(header, Some(&ETag::strong(&tag))),
+            None => true,
+        };
+
+        let body = EmbeddedBody {
+            embedded: *self,
+            modified,
+        };
+        Either::Left(if modified {
+            Response::new(body).with_content_type(self.content_type)
+        } else {
+            (StatusCode::NOT_MODIFIED, Response::new(body)).into_response()
+        })
+    }
+}
+
+/// Body of the responses sent for [`Embedded`] data.
+pub struct EmbeddedBody {
+    embedded: Embedded,
+    modified: bool,
+}
+
+impl ResponseBody for EmbeddedBody {
+    type Error = core::convert::Infallible;
+
+    fn content_length(&self) -> Option<usize> {
+        self.modified.then_some(self.embedded.data.len())
+    }
+
+    async fn write_to<W: Write>(
+        self,
+        writer: &mut ResponseWriter<'_, W>,
+        _buf: &mut [u8],
+    ) -> Result<(), WriteError<W::Error, Self::Error>> {
+        let mut etag = heapless::String::<18>::new();
+        // Can't fail, the quoted tag fits.
+        let _ = write!(etag, "{}", ETag::strong(&self.embedded.etag()));
+
+        writer
+            .write_header("ETag", &etag)
+            .await
+            .map_err(WriteError::Io)?;
+        writer
+            .write_header("Vary", "Accept-Encoding")
+            .await
+            .map_err(WriteError::Io)?;
+        if self.modified {
+            writer
+                .write_header("Content-Encoding", self.embedded.encoding)
+                .await
+                .map_err(WriteError::Io)?;
+            if self.embedded.digest {
+                let digest = digest::sha256(self.embedded.data);
+                writer
+                    .write_header(
+                        digest::digest_header(writer),
+                        &digest::content_digest(&digest),
+                    )
+                    .await
+                    .map_err(WriteError::Io)?;
+            }
+            writer
+                .write_all(self.embedded.data)
+                .await
+                .map_err(WriteError::Io)?;
+        }
+        Ok(())
+    }
+}
+
+/// An uncompressed static file served as handler, see the [module](self) documentation.
+///
+/// Sent with the media type derived from its name by
+/// [`content_type::from_extension`] and its `Content-Length`. With an entity tag
+/// clients revalidate their cached copy, which is answered with `304 Not Modified`.
+#[derive(Debug, Clone, Copy)]
+pub struct ServeStatic {
+    data: &'static [u8],
+    name: &'static str,
+    content_type: Option<&'static str>,
+    etag: Option<&'static str>,
+}
+
+impl ServeStatic {
+    /// Serves `data` as the file `name`, e.g. `index.html`.
+    pub const fn new(name: &'static str, data: &'static [u8]) -> Self {
+        Self {
+            data,
+            name,
+            content_type: None,
+            etag: None,
+        }
+    }
+
+    /// Sends `content_type` instead of the one derived from the name.
+    pub const fn with_content_type(mut self, content_type: &'static str) -> Self {
+        self.content_type = Some(content_type);
+        self
+    }
+
+    /// Sends the strong entity tag `tag`, the opaque tag without quotes, e.g. a hash
+    /// computed by the build script. Tags longer than 64 bytes are not sent.
+    pub const fn with_etag(mut self, tag: &'static str) -> Self {
+        self.etag = Some(tag);
+        self
+    }
+
+    /// The file contents.
+    pub fn data(&self) -> &'static [u8] {
+        self.data
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        self.content_type
+            .unwrap_or_else(|| content_type::from_extension(self.name))
+    }
+}
+
+impl<S> HandlerFunction<S, ()> for ServeStatic {
+    type Response = Response<StaticBody>;
+
+    async fn call<Body: Read>(&self, req: Request<'_, Body>, _state: &S) -> Self::Response {
+        let modified = match (self.etag, req.parts.headers.get_first("If-None-Match")) {
+            (Some(tag), Some(header)) => if_none_match(header, Some(&ETag::strong(tag))),
+            _ => true,
+        };
+
+        let body = StaticBody {
+            file: *self,
+            modified,
+        };
+        if modified {
+            Response::new(body).with_content_type(self.content_type())
+        } else {
+            (StatusCode::NOT_MODIFIED, Response::new(body)).into_response()
+        }
+    }
+}
+
+/// Body of the responses sent for a [`ServeStatic`] file.
+pub struct StaticBody {
+    file: ServeStatic,
+    modified: bool,
+}
+
+impl ResponseBody for StaticBody {
+    type Error = core::convert::Infallible;
+
+    fn content_length(&self) -> Option<usize> {
+        self.modified.then_some(self.file.data.len())
+    }
+
+    async fn write_to<W: Write>(
+        self,
+        writer: &mut ResponseWriter<'_, W>,
+        _buf: &mut [u8],
+    ) -> Result<(), WriteError<W::Error, Self::Error>> {
+        let mut etag = heapless::String::<{ ETAG + 2 }>::new();
+        if let Some(tag) = self.file.etag {
+            if write!(etag, "{}", ETag::strong(tag)).is_ok() {
+                writer
+                    .write_header("ETag", &etag)
+                    .await
+                    .map_err(WriteError::Io)?;
+            }
+        }
+        if self.modified {
+            writer
+                .write_all(self.file.data)
+                .await
+                .map_err(WriteError::Io)?;
+        }
+        Ok(())
+    }
+}
@@ -11,4 +11,7 @@ pub enum ProtocolError {
     InvalidUrl(InvalidUrl),
     InvalidMethod(InvalidMethod),
     Parser(httparse::Error),
+    /// The request head does not fit into the request buffer, it was answered with
+    /// `431 Request Header Fields Too Large`.
+    HeadTooLarge,
 }
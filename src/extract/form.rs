@@ -0,0 +1,80 @@
+use super::{
+    query::{FromUrlEncoded, UrlEncoded, UrlEncodedRejection},
+    request::{InvalidUtf8, VecRejection},
+    utils::composite_rejection,
+    FromRequest,
+};
+use crate::{
+    content_type::{self, UnsupportedMediaType},
+    Read, Request,
+};
+
+composite_rejection! {
+    /// Rejection used for [`Form`].
+    pub enum FormRejection {
+        UnsupportedMediaType,
+        VecRejection,
+        InvalidUtf8,
+        UrlEncodedRejection,
+    }
+}
+
+/// Extracts an `application/x-www-form-urlencoded` body into `T`, as sent by HTML forms.
+///
+/// The body is buffered in `N` bytes on the stack, larger bodies are rejected with
+/// `413 Payload Too Large`, other media types with `415 Unsupported Media Type`.
+/// Fields are read like a [`Query`](super::Query).
+///
+/// ```
+/// use low_profile::{
+///     extract::{Form, FromUrlEncoded, UrlEncoded, UrlEncodedRejection},
+///     Router,
+/// };
+///
+/// struct Wifi {
+///     ssid: heapless::String<32>,
+///     password: heapless::String<64>,
+///     dhcp: bool,
+/// }
+///
+/// impl FromUrlEncoded for Wifi {
+///     fn from_url_encoded(fields: &UrlEncoded<'_>) -> Result<Self, UrlEncodedRejection> {
+///         Ok(Self {
+///             ssid: fields.get("ssid")?,
+///             password: fields.get("password")?,
+///             // Unchecked checkboxes are not sent.
+///             dhcp: fields.contains("dhcp"),
+///         })
+///     }
+/// }
+///
+/// async fn save_wifi(Form(wifi): Form<Wifi>) -> heapless::String<32> {
+///     wifi.ssid
+/// }
+///
+/// let router = Router::<(), _>::new().post("/wifi", save_wifi);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Form<T, const N: usize = 512>(pub T);
+
+impl<'a, S, T: FromUrlEncoded, const N: usize> FromRequest<'a, S> for Form<T, N> {
+    type Rejection = FormRejection;
+
+    async fn from_request<R: Read>(
+        req: Request<'a, R>,
+        state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let form = req
+            .parts
+            .headers
+            .get_first("Content-Type")
+            .is_some_and(|value| content_type::matches(value, "application/x-www-form-urlencoded"));
+        if !form {
+            return Err(UnsupportedMediaType.into());
+        }
+
+        let body = heapless::Vec::<u8, N>::from_request(req, state).await?;
+        let body = core::str::from_utf8(&body).map_err(|_| InvalidUtf8)?;
+        Ok(Form(T::from_url_encoded(&UrlEncoded::new(body))?))
+    }
+}
@@ -0,0 +1,93 @@
+use super::{utils::define_rejection, FromRequestParts};
+use crate::Parts;
+
+define_rejection! {
+    #[status = BAD_REQUEST]
+    #[body = "Missing or invalid Host header"]
+    /// The request has no `Host` header or its value is not a valid host.
+    pub struct InvalidHost;
+}
+
+/// The host and optional port from the request's `Host` header.
+///
+/// The name is normalized by removing a trailing dot, compare it with
+/// [`is`](Self::is) to ignore its case.
+///
+/// ```
+/// use low_profile::extract::Host;
+///
+/// let host = Host::parse("Device.LOCAL.:8080").unwrap();
+/// assert!(host.is("device.local"));
+/// assert_eq!(host.port(), Some(8080));
+///
+/// let host = Host::parse("[::1]").unwrap();
+/// assert_eq!((host.name(), host.port()), ("[::1]", None));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Host<'a> {
+    name: &'a str,
+    port: Option<u16>,
+}
+
+impl<'a> Host<'a> {
+    /// Parses a `Host` header value, IPv6 literals keep their brackets.
+    pub fn parse(value: &'a str) -> Option<Self> {
+        let value = value.trim();
+        let (name, port) = if value.starts_with('[') {
+            let end = value.find(']')? + 1;
+            (&value[..end], &value[end..])
+        } else {
+            match value.rfind(':') {
+                Some(i) => value.split_at(i),
+                None => (value, ""),
+            }
+        };
+
+        let port = match port {
+            "" | ":" => None,
+            port => Some(port.strip_prefix(':')?.parse().ok()?),
+        };
+
+        let name = name.strip_suffix('.').unwrap_or(name);
+        let valid = !name.is_empty()
+            && name
+                .bytes()
+                .all(|c| c.is_ascii_graphic() && !matches!(c, b'/' | b'?' | b'#' | b'@'));
+
+        valid.then_some(Self { name, port })
+    }
+
+    pub(crate) fn new(name: &'a str, port: Option<u16>) -> Self {
+        Self { name, port }
+    }
+
+    /// The host name or address without port and trailing dot.
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    pub fn port(&self) -> Option<u16> {
+        self.port
+    }
+
+    /// Compares the host name with `name` ignoring its case and a trailing dot.
+    pub fn is(&self, name: &str) -> bool {
+        let name = name.strip_suffix('.').unwrap_or(name);
+        self.name.eq_ignore_ascii_case(name)
+    }
+}
+
+impl<'a, S> FromRequestParts<'a, S> for Host<'a> {
+    type Rejection = InvalidHost;
+
+    async fn from_request_parts(
+        parts: &mut Parts<'a>,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        parts
+            .headers
+            .get_first("Host")
+            .and_then(Host::parse)
+            .ok_or(InvalidHost)
+    }
+}
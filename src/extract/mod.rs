@@ -2,11 +2,29 @@ use core::future::Future;
 
 use crate::{request::Parts, IntoResponse, Read, Request};
 
+mod form;
+mod host;
+mod path;
+mod query;
 mod request;
 mod request_parts;
-mod utils;
+mod scratch;
+mod typed_header;
+pub(crate) mod utils;
+mod valid;
 
+pub use form::{Form, FormRejection};
+pub use host::{Host, InvalidHost};
+pub use path::{FromParam, FromParams, InvalidPathParam, MissingPathParams, Path, PathRejection};
+pub use query::{
+    FromUrlEncoded, InvalidParam, MissingParam, Query, UrlEncoded, UrlEncodedRejection,
+};
+#[cfg(feature = "grpc-web")]
+pub(crate) use request::VecRejection;
 pub use request_parts::{FromRef, State};
+pub use scratch::{ArenaExhausted, Scratch};
+pub use typed_header::{InvalidHeader, MissingHeader, TypedHeader, TypedHeaderRejection};
+pub use valid::{FieldError, FieldErrors, Valid, ValidRejection, Validate};
 
 mod private {
     #[derive(Debug, Clone, Copy)]
@@ -16,6 +34,39 @@ mod private {
     pub enum ViaRequest {}
 }
 
+/// Types extracted from the request head, usable as any handler argument.
+///
+/// Implement this for custom extractors, e.g. of authentication tokens or device IDs.
+/// Requests are answered with the [`Rejection`](Self::Rejection) if extracting fails,
+/// without calling the handler.
+///
+/// Handlers accept extractors valid for any request lifetime, extractors holding
+/// data of the request copy it, e.g. into a `heapless::String`.
+///
+/// ```
+/// use low_profile::{http::StatusCode, FromRequestParts, Parts, Router};
+///
+/// struct DeviceId(heapless::String<16>);
+///
+/// impl<'a, S> FromRequestParts<'a, S> for DeviceId {
+///     type Rejection = (StatusCode, &'static str);
+///
+///     async fn from_request_parts(parts: &mut Parts<'a>, _state: &S) -> Result<Self, Self::Rejection> {
+///         parts
+///             .headers
+///             .get_first("X-Device-Id")
+///             .and_then(|id| id.try_into().ok())
+///             .map(DeviceId)
+///             .ok_or((StatusCode::BAD_REQUEST, "Missing device id"))
+///     }
+/// }
+///
+/// async fn register(DeviceId(id): DeviceId) -> heapless::String<16> {
+///     id
+/// }
+///
+/// let router = Router::<(), _>::new().post("/register", register);
+/// ```
 pub trait FromRequestParts<'a, S>: Sized {
     type Rejection: IntoResponse;
 
@@ -25,6 +76,36 @@ pub trait FromRequestParts<'a, S>: Sized {
     ) -> impl Future<Output = Result<Self, Self::Rejection>>;
 }
 
+/// Types extracted from the whole request including its body, usable as the last
+/// handler argument.
+///
+/// Every [`FromRequestParts`] extractor is a `FromRequest` extractor as well,
+/// implement this only for extractors reading the body.
+///
+/// ```
+/// use low_profile::{http::StatusCode, FromRequest, Read, Request, Router};
+///
+/// /// The first byte of the body.
+/// struct Command(u8);
+///
+/// impl<'a, S> FromRequest<'a, S> for Command {
+///     type Rejection = (StatusCode, &'static str);
+///
+///     async fn from_request<R: Read>(mut req: Request<'a, R>, _state: &S) -> Result<Self, Self::Rejection> {
+///         let mut command = [0];
+///         match req.body_mut().read(&mut command).await {
+///             Ok(1) => Ok(Command(command[0])),
+///             _ => Err((StatusCode::BAD_REQUEST, "Missing command")),
+///         }
+///     }
+/// }
+///
+/// async fn control(Command(command): Command) -> &'static str {
+///     if command == b'r' { "rebooting" } else { "unknown command" }
+/// }
+///
+/// let router = Router::<(), _>::new().post("/control", control);
+/// ```
 pub trait FromRequest<'a, S, M = private::ViaRequest>: Sized {
     type Rejection: IntoResponse;
 
@@ -0,0 +1,136 @@
+use super::{
+    utils::{composite_rejection, define_rejection},
+    FromRequestParts,
+};
+use crate::{Params, Parts};
+
+define_rejection! {
+    #[status = BAD_REQUEST]
+    #[body = "Invalid path parameter"]
+    /// A captured path segment can't be parsed into the requested type.
+    pub struct InvalidPathParam;
+}
+
+define_rejection! {
+    #[status = INTERNAL_SERVER_ERROR]
+    #[body = "Path parameters don't match the route"]
+    /// The route captures a different amount of segments than requested.
+    pub struct MissingPathParams;
+}
+
+composite_rejection! {
+    /// Rejection used for [`Path`].
+    pub enum PathRejection {
+        InvalidPathParam,
+        MissingPathParams,
+    }
+}
+
+/// Extracts the segments captured by the route path, see [`Router::route`](crate::Router::route).
+///
+/// A single value is extracted for routes capturing one segment, a tuple with a
+/// value for every captured segment otherwise. The remaining path captured by a
+/// `*name` wildcard is extracted like a segment. Captures are percent-decoded
+/// like the whole path.
+///
+/// ```
+/// use low_profile::{extract::Path, Router};
+///
+/// async fn set_led(Path(id): Path<u8>) -> &'static str {
+///     if id < 8 { "ok" } else { "no such led" }
+/// }
+///
+/// async fn get_level(Path((id, color)): Path<(u8, heapless::String<8>)>) -> &'static str {
+///     match (id, color.as_str()) {
+///         (0, "red") => "on",
+///         _ => "off",
+///     }
+/// }
+///
+/// async fn asset(Path(file): Path<heapless::String<64>>) -> heapless::String<64> {
+///     file
+/// }
+///
+/// let router = Router::<(), _>::new()
+///     .post("/leds/:id", set_led)
+///     .get("/leds/:id/:color", get_level)
+///     .get("/static/*file", asset);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Path<T>(pub T);
+
+/// A value parsed from a single captured path segment or a [`UrlEncoded`](super::UrlEncoded) value.
+pub trait FromParam: Sized {
+    fn from_param(value: &str) -> Option<Self>;
+}
+
+impl<const N: usize> FromParam for heapless::String<N> {
+    fn from_param(value: &str) -> Option<Self> {
+        value.try_into().ok()
+    }
+}
+
+macro_rules! impl_from_param {
+    ($($ty:ty),*) => {
+        $(
+            impl FromParam for $ty {
+                fn from_param(value: &str) -> Option<Self> {
+                    value.parse().ok()
+                }
+            }
+        )*
+    };
+}
+
+impl_from_param!(
+    u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, bool, char
+);
+
+/// Values extracted by [`Path`] from all captured path segments.
+pub trait FromParams: Sized {
+    fn from_params(params: &Params<'_>) -> Result<Self, PathRejection>;
+}
+
+fn param<T: FromParam>(value: Option<&str>) -> Result<T, PathRejection> {
+    let value = value.ok_or(MissingPathParams)?;
+    Ok(T::from_param(value).ok_or(InvalidPathParam)?)
+}
+
+impl<T: FromParam> FromParams for T {
+    fn from_params(params: &Params<'_>) -> Result<Self, PathRejection> {
+        if params.len() != 1 {
+            return Err(MissingPathParams.into());
+        }
+        param(params.iter().next().map(|(_, value)| value))
+    }
+}
+
+macro_rules! impl_from_params {
+    ($len:literal, $($ty:ident),*) => {
+        impl<$($ty: FromParam),*> FromParams for ($($ty,)*) {
+            fn from_params(params: &Params<'_>) -> Result<Self, PathRejection> {
+                if params.len() != $len {
+                    return Err(MissingPathParams.into());
+                }
+                let mut values = params.iter().map(|(_, value)| value);
+                Ok(($(param::<$ty>(values.next())?,)*))
+            }
+        }
+    };
+}
+
+impl_from_params!(1, T1);
+impl_from_params!(2, T1, T2);
+impl_from_params!(3, T1, T2, T3);
+impl_from_params!(4, T1, T2, T3, T4);
+
+impl<'a, S, T: FromParams> FromRequestParts<'a, S> for Path<T> {
+    type Rejection = PathRejection;
+
+    async fn from_request_parts(
+        parts: &mut Parts<'a>,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        T::from_params(&parts.params).map(Path)
+    }
+}
@@ -0,0 +1,169 @@
+use super::{
+    path::FromParam,
+    utils::{composite_rejection, define_rejection},
+    FromRequestParts,
+};
+use crate::{url, Parts};
+
+/// Maximum length of a decoded value containing escapes.
+const MAX_VALUE: usize = 128;
+
+define_rejection! {
+    #[status = BAD_REQUEST]
+    #[body = "Invalid parameter"]
+    /// A value can't be decoded or parsed into the requested type.
+    pub struct InvalidParam;
+}
+
+define_rejection! {
+    #[status = BAD_REQUEST]
+    #[body = "Missing parameter"]
+    /// A required key is missing.
+    pub struct MissingParam;
+}
+
+composite_rejection! {
+    /// Rejection used for [`Query`] and the fields of a [`Form`](super::Form).
+    pub enum UrlEncodedRejection {
+        InvalidParam,
+        MissingParam,
+    }
+}
+
+/// Extracts the query string of the request into `T`.
+///
+/// Requests without a query string are handled like an empty query string.
+///
+/// ```
+/// use low_profile::{
+///     extract::{FromUrlEncoded, Query, UrlEncoded, UrlEncodedRejection},
+///     Router,
+/// };
+///
+/// struct Filter {
+///     page: u32,
+///     name: Option<heapless::String<16>>,
+///     verbose: bool,
+/// }
+///
+/// impl FromUrlEncoded for Filter {
+///     fn from_url_encoded(fields: &UrlEncoded<'_>) -> Result<Self, UrlEncodedRejection> {
+///         Ok(Self {
+///             page: fields.get_optional("page")?.unwrap_or(1),
+///             name: fields.get_optional("name")?,
+///             verbose: fields.contains("verbose"),
+///         })
+///     }
+/// }
+///
+/// // `/sensors?name=Living%20Room&verbose`
+/// async fn sensors(Query(filter): Query<Filter>) -> heapless::String<16> {
+///     filter.name.unwrap_or_default()
+/// }
+///
+/// let router = Router::<(), _>::new().get("/sensors", sensors);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Query<T>(pub T);
+
+impl<'a, S, T: FromUrlEncoded> FromRequestParts<'a, S> for Query<T> {
+    type Rejection = UrlEncodedRejection;
+
+    async fn from_request_parts(
+        parts: &mut Parts<'a>,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let fields = UrlEncoded::new(parts.query.unwrap_or_default());
+        T::from_url_encoded(&fields).map(Query)
+    }
+}
+
+/// Values extracted from `key=value` pairs separated by `&`, see [`Query`] and
+/// [`Form`](super::Form).
+pub trait FromUrlEncoded: Sized {
+    fn from_url_encoded(fields: &UrlEncoded<'_>) -> Result<Self, UrlEncodedRejection>;
+}
+
+/// `key=value` pairs separated by `&`, e.g. a query string or form body.
+///
+/// Values are percent-decoded, `+` is decoded to a space. Values containing escapes
+/// may be up to 128 bytes long once decoded. Keys are compared as sent, without decoding.
+///
+/// ```
+/// use low_profile::extract::UrlEncoded;
+///
+/// let fields = UrlEncoded::new("name=Hello%20World&led=3&on");
+/// assert_eq!(fields.get::<heapless::String<16>>("name").unwrap(), "Hello World");
+/// assert_eq!(fields.get::<u8>("led").unwrap(), 3);
+/// assert!(fields.contains("on"));
+/// assert!(fields.get::<u8>("off").is_err());
+///
+/// let fields = UrlEncoded::new("led=1&led=3");
+/// let leds: Result<heapless::Vec<u8, 4>, _> = fields.get_all("led").collect();
+/// assert_eq!(leds.unwrap(), [1, 3]);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct UrlEncoded<'a> {
+    input: &'a str,
+}
+
+impl<'a> UrlEncoded<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self { input }
+    }
+
+    /// Iterates over the raw keys and values in the order sent, values of pairs
+    /// without `=` are empty. Decode them with [`url::decode_form`].
+    pub fn pairs(&self) -> impl Iterator<Item = (&'a str, &'a str)> + 'a {
+        self.input
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+    }
+
+    /// Returns the raw value of the first pair with `key`.
+    fn find(&self, key: &str) -> Option<&'a str> {
+        self.pairs()
+            .find_map(|(k, value)| (k == key).then_some(value))
+    }
+
+    /// Returns `true` if a pair with `key` is present, with or without a value.
+    pub fn contains(&self, key: &str) -> bool {
+        self.find(key).is_some()
+    }
+
+    /// Parses the value of the first pair with `key`, failing if there is none.
+    pub fn get<T: FromParam>(&self, key: &str) -> Result<T, UrlEncodedRejection> {
+        self.get_optional(key)?.ok_or(MissingParam.into())
+    }
+
+    /// Parses the value of the first pair with `key`, if any.
+    pub fn get_optional<T: FromParam>(&self, key: &str) -> Result<Option<T>, UrlEncodedRejection> {
+        self.find(key).map(parse).transpose()
+    }
+
+    /// Parses the values of all pairs with `key`, e.g. of repeated checkboxes or
+    /// `?led=1&led=3`.
+    pub fn get_all<'k, T: FromParam>(
+        &self,
+        key: &'k str,
+    ) -> impl Iterator<Item = Result<T, UrlEncodedRejection>> + 'k
+    where
+        'a: 'k,
+    {
+        self.pairs()
+            .filter(move |&(k, _)| k == key)
+            .map(|(_, value)| parse(value))
+    }
+}
+
+/// Decodes and parses a raw value.
+fn parse<T: FromParam>(value: &str) -> Result<T, UrlEncodedRejection> {
+    let mut buf = [0; MAX_VALUE];
+    let value = if value.contains(['%', '+']) {
+        url::decode_form(value, &mut buf).map_err(|_| InvalidParam)?
+    } else {
+        value
+    };
+    Ok(T::from_param(value).ok_or(InvalidParam)?)
+}
@@ -2,7 +2,7 @@ use super::{
     utils::{composite_rejection, define_rejection},
     FromRequest,
 };
-use crate::{Read, Request};
+use crate::{request::BodyError, Read, Request};
 
 define_rejection! {
     #[status = INTERNAL_SERVER_ERROR]
@@ -17,6 +17,13 @@ define_rejection! {
     pub struct BodyTooLarge;
 }
 
+define_rejection! {
+    #[status = BAD_REQUEST]
+    #[body = "Failed to read request body, malformed chunked encoding"]
+    /// The chunked encoding of the body is malformed or ends prematurely.
+    pub struct MalformedBody;
+}
+
 define_rejection! {
     #[status = BAD_REQUEST]
     #[body = "Failed to read request body, invalid UTF-8"]
@@ -28,6 +35,7 @@ composite_rejection! {
     pub enum VecRejection {
         UnknownBodyError,
         BodyTooLarge,
+        MalformedBody,
     }
 }
 
@@ -54,7 +62,7 @@ impl<'a, const SIZE: usize, S> FromRequest<'a, S> for heapless::Vec<u8, SIZE> {
                 .body_mut()
                 .read(&mut data[current..])
                 .await
-                .map_err(|_| UnknownBodyError)?;
+                .map_err(rejection)?;
             if read == 0 {
                 break;
             }
@@ -63,11 +71,7 @@ impl<'a, const SIZE: usize, S> FromRequest<'a, S> for heapless::Vec<u8, SIZE> {
             if current == data.len() {
                 // buffer is completely full, read one more byte to check for EoF
                 let mut eof = [0u8; 1];
-                let read = req
-                    .body_mut()
-                    .read(&mut eof)
-                    .await
-                    .map_err(|_| UnknownBodyError)?;
+                let read = req.body_mut().read(&mut eof).await.map_err(rejection)?;
                 if read > 0 {
                     return Err(BodyTooLarge.into());
                 }
@@ -80,6 +84,19 @@ impl<'a, const SIZE: usize, S> FromRequest<'a, S> for heapless::Vec<u8, SIZE> {
     }
 }
 
+fn rejection<E: embedded_io_async::Error>(err: BodyError<E>) -> VecRejection {
+    match err {
+        BodyError::TooLarge => BodyTooLarge.into(),
+        BodyError::Malformed => MalformedBody.into(),
+        // A body wrapped by another reader, e.g. by `digest::verify`.
+        BodyError::Io(err) => match err.kind() {
+            embedded_io_async::ErrorKind::OutOfMemory => BodyTooLarge.into(),
+            embedded_io_async::ErrorKind::InvalidData => MalformedBody.into(),
+            _ => UnknownBodyError.into(),
+        },
+    }
+}
+
 impl<'a, const SIZE: usize, S> FromRequest<'a, S> for heapless::String<SIZE> {
     type Rejection = StringRejection;
 
@@ -0,0 +1,83 @@
+use core::ops::{Deref, DerefMut};
+
+use super::{utils::define_rejection, FromRequestParts};
+use crate::Parts;
+
+define_rejection! {
+    #[status = INTERNAL_SERVER_ERROR]
+    #[body = "Request arena exhausted"]
+    /// The request has no [`Arena`](crate::Arena) or not enough of it is left.
+    pub struct ArenaExhausted;
+}
+
+/// `N` zeroed bytes allocated from the request [`Arena`](crate::Arena), for
+/// intermediate data without large arrays in the extractor or route futures.
+///
+/// The arena is the part of the request buffer not filled by the request, sized by
+/// the `REQUEST` [`Buffers`](crate::Buffers), or the buffer passed to
+/// [`Router::serve_with_arena`](crate::Router::serve_with_arena).
+///
+/// Handler arguments can't borrow the request, the scratch buffer is used by custom
+/// extractors, [`Route`](crate::Route)s and [`Layer`](crate::layer::Layer)s instead:
+///
+/// ```
+/// use low_profile::{
+///     extract::Scratch,
+///     http::StatusCode,
+///     url, FromRequestParts, Parts, Router,
+/// };
+///
+/// /// The percent-decoded `X-Device-Name` header.
+/// struct DeviceName(heapless::String<16>);
+///
+/// impl<'a, S> FromRequestParts<'a, S> for DeviceName {
+///     type Rejection = (StatusCode, &'static str);
+///
+///     async fn from_request_parts(parts: &mut Parts<'a>, state: &S) -> Result<Self, Self::Rejection> {
+///         let invalid = (StatusCode::BAD_REQUEST, "Invalid device name");
+///         let value = parts.headers.get_first("X-Device-Name").ok_or(invalid)?;
+///         let mut scratch = Scratch::<'a, 256>::from_request_parts(parts, state)
+///             .await
+///             .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Out of memory"))?;
+///         let name = url::decode(value, &mut scratch).map_err(|_| invalid)?;
+///         name.try_into().map(DeviceName).map_err(|_| invalid)
+///     }
+/// }
+///
+/// async fn register(DeviceName(name): DeviceName) -> heapless::String<16> {
+///     name
+/// }
+///
+/// let router = Router::<(), _>::new().post("/register", register);
+/// ```
+#[derive(Debug)]
+pub struct Scratch<'a, const N: usize = 512>(&'a mut [u8]);
+
+impl<'a, S, const N: usize> FromRequestParts<'a, S> for Scratch<'a, N> {
+    type Rejection = ArenaExhausted;
+
+    async fn from_request_parts(
+        parts: &mut Parts<'a>,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        parts
+            .arena
+            .and_then(|arena| arena.alloc_bytes(N))
+            .map(Scratch)
+            .ok_or(ArenaExhausted)
+    }
+}
+
+impl<'a, const N: usize> Deref for Scratch<'a, N> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.0
+    }
+}
+
+impl<'a, const N: usize> DerefMut for Scratch<'a, N> {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        self.0
+    }
+}
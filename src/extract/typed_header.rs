@@ -0,0 +1,81 @@
+use super::{
+    utils::{composite_rejection, define_rejection},
+    FromRequestParts,
+};
+use crate::{header::Header, Parts};
+
+define_rejection! {
+    #[status = BAD_REQUEST]
+    #[body = "Missing header"]
+    /// The request lacks a required header.
+    pub struct MissingHeader;
+}
+
+define_rejection! {
+    #[status = BAD_REQUEST]
+    #[body = "Invalid header"]
+    /// A header value can't be parsed into the requested type.
+    pub struct InvalidHeader;
+}
+
+composite_rejection! {
+    /// Rejection used for [`TypedHeader`].
+    pub enum TypedHeaderRejection {
+        MissingHeader,
+        InvalidHeader,
+    }
+}
+
+/// Extracts the first header named [`T::NAME`](Header::NAME) parsed into `T`.
+///
+/// Extract an `Option<TypedHeader<T>>` for optional headers, present but invalid
+/// headers are rejected either way.
+///
+/// ```
+/// use low_profile::{
+///     extract::TypedHeader,
+///     header::{Authorization, ContentLength},
+///     http::StatusCode,
+///     Router,
+/// };
+///
+/// async fn upload(
+///     TypedHeader(auth): TypedHeader<Authorization>,
+///     length: Option<TypedHeader<ContentLength>>,
+/// ) -> (StatusCode, &'static str) {
+///     match (auth.bearer(), length) {
+///         (Some("secret"), Some(TypedHeader(ContentLength(..=4096)))) => (StatusCode::OK, "ok"),
+///         (Some("secret"), _) => (StatusCode::PAYLOAD_TOO_LARGE, "too large"),
+///         _ => (StatusCode::UNAUTHORIZED, "unauthorized"),
+///     }
+/// }
+///
+/// let router = Router::<(), _>::new().post("/upload", upload);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct TypedHeader<T>(pub T);
+
+impl<'a, S, T: Header> FromRequestParts<'a, S> for TypedHeader<T> {
+    type Rejection = TypedHeaderRejection;
+
+    async fn from_request_parts(parts: &mut Parts<'a>, state: &S) -> Result<Self, Self::Rejection> {
+        Option::<Self>::from_request_parts(parts, state)
+            .await?
+            .ok_or(MissingHeader.into())
+    }
+}
+
+impl<'a, S, T: Header> FromRequestParts<'a, S> for Option<TypedHeader<T>> {
+    type Rejection = TypedHeaderRejection;
+
+    async fn from_request_parts(
+        parts: &mut Parts<'a>,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let Some(value) = parts.headers.get_first(T::NAME) else {
+            return Ok(None);
+        };
+        let header = T::decode(value).ok_or(InvalidHeader)?;
+        Ok(Some(TypedHeader(header)))
+    }
+}
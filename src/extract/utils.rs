@@ -7,7 +7,7 @@ macro_rules! define_rejection {
         pub struct $name:ident;
     ) => {
         $(#[$m])*
-        #[derive(Debug)]
+        #[derive(Debug, Clone, Copy)]
         #[non_exhaustive]
         pub struct $name;
 
@@ -50,7 +50,7 @@ macro_rules! composite_rejection {
         }
     ) => {
         $(#[$m])*
-        #[derive(Debug)]
+        #[derive(Debug, Clone, Copy)]
         #[non_exhaustive]
         pub enum $name {
             $(
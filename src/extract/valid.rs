@@ -0,0 +1,239 @@
+use core::fmt::{self, Write as _};
+
+use super::{FromRequest, FromRequestParts};
+use crate::{
+    either::Either,
+    http::StatusCode,
+    io::Cursor,
+    request::Parts,
+    utils::{HtmlStr, JsonStr},
+    IntoResponse, Read, Request, Response,
+};
+
+/// Maximum size of the rendered validation error body.
+const ERROR_BODY_SIZE: usize = 256;
+
+/// Maximum size of the rendered body of multiple validation errors.
+const ERRORS_BODY_SIZE: usize = 1024;
+
+/// Validation of extracted values, invoked by the [`Valid`] extractor.
+pub trait Validate {
+    /// Validates `self`, returning the first invalid field.
+    fn validate(&self) -> Result<(), FieldError>;
+
+    /// Validates `self`, collecting all invalid fields into `errors`.
+    ///
+    /// Defaults to the first invalid field reported by [`validate`](Self::validate),
+    /// implement it to report all of them at once.
+    fn validate_all(&self, errors: &mut FieldErrors) {
+        if let Err(error) = self.validate() {
+            errors.push(error);
+        }
+    }
+}
+
+/// A single invalid field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldError {
+    pub field: &'static str,
+    pub message: &'static str,
+}
+
+impl FieldError {
+    pub fn new(field: &'static str, message: &'static str) -> Self {
+        Self { field, message }
+    }
+}
+
+impl IntoResponse for FieldError {
+    type Body = Cursor<heapless::String<ERROR_BODY_SIZE>>;
+
+    fn into_response(self) -> Response<Self::Body> {
+        let mut body = heapless::String::new();
+        let written = write!(
+            body,
+            r#"{{"errors":[{{"field":{},"message":{}}}]}}"#,
+            JsonStr(self.field),
+            JsonStr(self.message)
+        );
+        if written.is_err() {
+            // Keep the body valid JSON, like errors not fitting into `FieldErrors`.
+            body.clear();
+            let _ = body.push_str(r#"{"errors":[]}"#);
+        }
+        (StatusCode::UNPROCESSABLE_ENTITY, body)
+            .into_response()
+            .with_content_type("application/json")
+    }
+}
+
+/// Multiple invalid fields, rendered as a single `422 Unprocessable Entity` response.
+///
+/// Keeps up to `N` errors, further errors are dropped.
+///
+/// ```
+/// use low_profile::extract::FieldErrors;
+///
+/// let (name, port) = ("", 0u16);
+///
+/// let mut errors = FieldErrors::<4>::new();
+/// errors
+///     .ensure(!name.is_empty(), "name", "must not be empty")
+///     .ensure(port != 0, "port", "must not be 0");
+///
+/// let mut html = String::new();
+/// errors.write_html(&mut html).unwrap();
+/// assert_eq!(errors.len(), 2);
+/// assert!(html.starts_with("<ul><li><b>name</b>: must not be empty</li>"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldErrors<const N: usize = 8>(heapless::Vec<FieldError, N>);
+
+impl<const N: usize> FieldErrors<N> {
+    pub const fn new() -> Self {
+        Self(heapless::Vec::new())
+    }
+
+    /// Adds `error`, it is dropped if `N` errors were added already.
+    pub fn push(&mut self, error: FieldError) -> &mut Self {
+        let _ = self.0.push(error);
+        self
+    }
+
+    /// Adds an error for `field` unless `valid` is `true`.
+    pub fn ensure(&mut self, valid: bool, field: &'static str, message: &'static str) -> &mut Self {
+        if !valid {
+            self.push(FieldError::new(field, message));
+        }
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &FieldError> {
+        self.0.iter()
+    }
+
+    /// Returns `Err(self)` if any error was added.
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+
+    /// Renders the errors as an HTML list, e.g. to embed them into a form page.
+    pub fn write_html(&self, out: &mut impl fmt::Write) -> fmt::Result {
+        out.write_str("<ul>")?;
+        for error in self.iter() {
+            write!(
+                out,
+                "<li><b>{}</b>: {}</li>",
+                HtmlStr(error.field),
+                HtmlStr(error.message)
+            )?;
+        }
+        out.write_str("</ul>")
+    }
+}
+
+impl<const N: usize> IntoResponse for FieldErrors<N> {
+    type Body = Cursor<heapless::String<ERRORS_BODY_SIZE>>;
+
+    fn into_response(self) -> Response<Self::Body> {
+        let mut body = heapless::String::new();
+        let _ = body.push_str(r#"{"errors":["#);
+        for (i, error) in self.iter().enumerate() {
+            let len = body.len();
+            let written = write!(
+                body,
+                r#"{}{{"field":{},"message":{}}}"#,
+                if i == 0 { "" } else { "," },
+                JsonStr(error.field),
+                JsonStr(error.message)
+            );
+            // Keep the body valid JSON, errors not fitting are left out.
+            if written.is_err() || body.len() > ERRORS_BODY_SIZE - 2 {
+                body.truncate(len);
+                break;
+            }
+        }
+        let _ = body.push_str("]}");
+        (StatusCode::UNPROCESSABLE_ENTITY, body)
+            .into_response()
+            .with_content_type("application/json")
+    }
+}
+
+/// Extractor which validates the extracted value with [`Validate`].
+///
+/// Invalid values are rejected with `422 Unprocessable Entity`, listing all invalid
+/// fields reported by [`Validate::validate_all`].
+#[derive(Debug, Clone, Copy)]
+pub struct Valid<T>(pub T);
+
+/// Rejection used for [`Valid`].
+// Rejections are returned by value, there is no heap to box the errors in.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum ValidRejection<T> {
+    /// The inner extractor rejected the request.
+    Extract(T),
+    /// The extracted value failed validation.
+    Invalid(FieldErrors),
+}
+
+impl<T: IntoResponse> IntoResponse for ValidRejection<T> {
+    type Body = Either<T::Body, <FieldErrors as IntoResponse>::Body>;
+
+    fn into_response(self) -> Response<Self::Body> {
+        match self {
+            Self::Extract(rejection) => rejection.into_response().map_body(Either::Left),
+            Self::Invalid(error) => error.into_response().map_body(Either::Right),
+        }
+    }
+}
+
+impl<'a, S, T> FromRequestParts<'a, S> for Valid<T>
+where
+    T: FromRequestParts<'a, S> + Validate,
+{
+    type Rejection = ValidRejection<T::Rejection>;
+
+    async fn from_request_parts(parts: &mut Parts<'a>, state: &S) -> Result<Self, Self::Rejection> {
+        let value = T::from_request_parts(parts, state)
+            .await
+            .map_err(ValidRejection::Extract)?;
+        let mut errors = FieldErrors::new();
+        value.validate_all(&mut errors);
+        errors.into_result().map_err(ValidRejection::Invalid)?;
+        Ok(Self(value))
+    }
+}
+
+impl<'a, S, T> FromRequest<'a, S> for Valid<T>
+where
+    T: FromRequest<'a, S> + Validate,
+{
+    type Rejection = ValidRejection<T::Rejection>;
+
+    async fn from_request<R: Read>(
+        req: Request<'a, R>,
+        state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let value = T::from_request(req, state)
+            .await
+            .map_err(ValidRejection::Extract)?;
+        let mut errors = FieldErrors::new();
+        value.validate_all(&mut errors);
+        errors.into_result().map_err(ValidRejection::Invalid)?;
+        Ok(Self(value))
+    }
+}
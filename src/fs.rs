@@ -0,0 +1,181 @@
+//! Serving files from storage that is not part of the firmware image, e.g. an SD card.
+//!
+//! The storage is accessed through the [`FileSystem`] trait, implemented on top of
+//! the filesystem driver of the platform. [`ServeDir`] is a handler serving the path
+//! captured by the last segment of its route:
+//!
+//! ```
+//! use low_profile::{
+//!     fs::{File, FileSystem, ServeDir},
+//!     Read, ErrorType, Router,
+//! };
+//!
+//! /// A single file in memory, standing in for a real filesystem driver.
+//! struct Card;
+//!
+//! struct Readme(&'static [u8]);
+//!
+//! impl ErrorType for Readme {
+//!     type Error = core::convert::Infallible;
+//! }
+//!
+//! impl Read for Readme {
+//!     async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+//!         let len = buf.len().min(self.0.len());
+//!         buf[..len].copy_from_slice(&self.0[..len]);
+//!         self.0 = &self.0[len..];
+//!         Ok(len)
+//!     }
+//! }
+//!
+//! impl File for Readme {
+//!     fn size(&self) -> usize {
+//!         self.0.len()
+//!     }
+//! }
+//!
+//! impl FileSystem for Card {
+//!     type File = Readme;
+//!     type Error = core::convert::Infallible;
+//!
+//!     async fn open(&self, path: &str) -> Result<Option<Readme>, Self::Error> {
+//!         Ok((path == "README.txt").then_some(Readme(b"Hello")))
+//!     }
+//! }
+//!
+//! let router = Router::<(), _>::new().get("/sd/*path", ServeDir::new(Card));
+//! ```
+
+use core::future::Future;
+
+use crate::{
+    content_type,
+    handler::HandlerFunction,
+    http::StatusCode,
+    response::{ResponseBody, ResponseWriter, WriteError},
+    Read, Request, Response, Write,
+};
+
+/// Maximum length of a path passed to [`FileSystem::open`].
+const PATH: usize = 128;
+
+/// A file opened from a [`FileSystem`], read from start to end.
+pub trait File: Read {
+    /// Size of the file in bytes, sent as `Content-Length`.
+    ///
+    /// Must match the amount of bytes read until the end of the file.
+    fn size(&self) -> usize;
+}
+
+/// Read access to a filesystem.
+pub trait FileSystem {
+    type File: File;
+    type Error;
+
+    /// Opens the file at `path`, relative to the served directory without a leading `/`,
+    /// returns `None` if there is no such file.
+    fn open(&self, path: &str) -> impl Future<Output = Result<Option<Self::File>, Self::Error>>;
+}
+
+impl<T: FileSystem> FileSystem for &T {
+    type File = T::File;
+    type Error = T::Error;
+
+    fn open(&self, path: &str) -> impl Future<Output = Result<Option<Self::File>, Self::Error>> {
+        T::open(self, path)
+    }
+}
+
+/// Handler serving the files of a [`FileSystem`], see the [module](self) documentation.
+///
+/// The file is taken from the path captured by the last segment of the route, paths
+/// ending with `/` serve the `index.html` of the directory. Paths with `..` segments,
+/// `\` or control characters, also if percent-encoded, paths longer than 128 bytes
+/// and missing files are answered with `404 Not Found`, errors of the filesystem
+/// with `500 Internal Server Error`. The media type is derived from the file name
+/// by [`content_type::from_extension`].
+#[derive(Debug, Clone, Copy)]
+pub struct ServeDir<F> {
+    fs: F,
+    index: &'static str,
+}
+
+impl<F: FileSystem> ServeDir<F> {
+    pub const fn new(fs: F) -> Self {
+        Self {
+            fs,
+            index: "index.html",
+        }
+    }
+
+    /// Serves `index` instead of `index.html` for directories.
+    pub const fn index(mut self, index: &'static str) -> Self {
+        self.index = index;
+        self
+    }
+}
+
+impl<S, F: FileSystem> HandlerFunction<S, ()> for ServeDir<F> {
+    type Response = Result<Response<FileBody<F::File>>, (StatusCode, &'static str)>;
+
+    async fn call<Body: Read>(&self, req: Request<'_, Body>, _state: &S) -> Self::Response {
+        const NOT_FOUND: (StatusCode, &str) = (StatusCode::NOT_FOUND, "Not Found");
+
+        let captured = req.parts.params.iter().last().map_or("", |(_, path)| path);
+        if !captured.split('/').all(valid_segment) {
+            return Err(NOT_FOUND);
+        }
+
+        let mut path = heapless::String::<PATH>::new();
+        path.push_str(captured.trim_start_matches('/'))
+            .map_err(|_| NOT_FOUND)?;
+        if path.is_empty() || path.ends_with('/') {
+            path.push_str(self.index).map_err(|_| NOT_FOUND)?;
+        }
+
+        let file = match self.fs.open(&path).await {
+            Ok(Some(file)) => file,
+            Ok(None) => return Err(NOT_FOUND),
+            Err(_) => return Err((StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error")),
+        };
+        Ok(Response::new(FileBody(file)).with_content_type(content_type::from_extension(&path)))
+    }
+}
+
+/// Returns `false` for segments leaving the served directory or which filesystem drivers
+/// could interpret differently, e.g. as the end of a NUL-terminated path or a `\`
+/// separating directories.
+fn valid_segment(segment: &str) -> bool {
+    segment != ".."
+        && !segment
+            .bytes()
+            .any(|byte| byte == b'\\' || byte.is_ascii_control())
+}
+
+/// Body relaying a [`File`] served by [`ServeDir`].
+pub struct FileBody<F>(F);
+
+impl<F: File> ResponseBody for FileBody<F> {
+    type Error = F::Error;
+
+    fn content_length(&self) -> Option<usize> {
+        Some(self.0.size())
+    }
+
+    async fn write_to<W: Write>(
+        mut self,
+        writer: &mut ResponseWriter<'_, W>,
+        buf: &mut [u8],
+    ) -> Result<(), WriteError<W::Error, Self::Error>> {
+        loop {
+            let len = self.0.read(buf).await.map_err(WriteError::Body)?;
+            if len == 0 {
+                return Ok(());
+            }
+            writer
+                .write_all(&buf[..len])
+                .await
+                .map_err(WriteError::Io)?;
+        }
+    }
+}
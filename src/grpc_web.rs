@@ -0,0 +1,383 @@
+//! [gRPC-web](https://github.com/grpc/grpc/blob/master/doc/PROTOCOL-WEB.md) framing
+//! for unary and server-streaming calls from browsers.
+//!
+//! Messages are encoded by a protobuf implementation of choice through [`Message`],
+//! this module only frames them and sends the status in trailers. Only the binary
+//! `application/grpc-web+proto` format is supported, not the base64 encoded `-text`
+//! variant, and compressed messages are rejected.
+//!
+//! Calls are routed like any other request, to `POST /<package>.<Service>/<Method>`:
+//!
+//! ```
+//! use low_profile::{
+//!     grpc_web::{Code, Grpc, Message, MessageStream, Status, Streaming},
+//!     Router,
+//! };
+//!
+//! /// `message Led { uint32 id = 1; }`, hand encoded for the example.
+//! struct Led {
+//!     id: u8,
+//! }
+//!
+//! impl Message for Led {
+//!     fn decode(buf: &[u8]) -> Option<Self> {
+//!         match buf {
+//!             [] => Some(Led { id: 0 }),
+//!             [0x08, id] if *id < 0x80 => Some(Led { id: *id }),
+//!             _ => None,
+//!         }
+//!     }
+//!
+//!     fn encode(&self, buf: &mut [u8]) -> Option<usize> {
+//!         let bytes = [0x08, self.id];
+//!         buf.get_mut(..2)?.copy_from_slice(&bytes);
+//!         Some(2)
+//!     }
+//! }
+//!
+//! async fn toggle(Grpc(led): Grpc<Led>) -> Result<Grpc<Led>, Status> {
+//!     match led.id {
+//!         0..=3 => Ok(Grpc(led)),
+//!         _ => Err(Status::new(Code::NotFound, "No such LED")),
+//!     }
+//! }
+//!
+//! /// Streams all LEDs.
+//! struct Leds(u8);
+//!
+//! impl MessageStream for Leds {
+//!     type Item = Led;
+//!
+//!     async fn next(&mut self) -> Option<Result<Led, Status>> {
+//!         let id = self.0;
+//!         self.0 += 1;
+//!         (id < 4).then_some(Ok(Led { id }))
+//!     }
+//! }
+//!
+//! async fn list() -> Streaming<Leds> {
+//!     Streaming(Leds(0))
+//! }
+//!
+//! let router = Router::<(), _>::new()
+//!     .post("/device.Leds/Toggle", toggle)
+//!     .post("/device.Leds/List", list);
+//! ```
+
+use core::{convert::Infallible, fmt::Write as _, future::Future};
+
+use crate::{
+    content_type::{self, UnsupportedMediaType},
+    extract::{
+        utils::{composite_rejection, define_rejection},
+        VecRejection,
+    },
+    response::{ResponseBody, ResponseWriter, WriteError},
+    url, FromRequest, IntoResponse, Read, Request, Response, Write,
+};
+
+/// Content type of gRPC-web responses.
+const CONTENT_TYPE: &str = "application/grpc-web+proto";
+
+/// Flag of a frame carrying the trailers instead of a message.
+const TRAILERS: u8 = 0x80;
+
+/// Characters percent-encoded in the `grpc-message`.
+const MESSAGE: url::AsciiSet = url::CONTROLS.add(b'%');
+
+/// A protobuf message, implemented with the protobuf library of choice.
+pub trait Message: Sized {
+    /// Decodes a message, `None` if `buf` is not a valid encoding.
+    fn decode(buf: &[u8]) -> Option<Self>;
+
+    /// Encodes the message into `buf`, returns the length of the encoding or `None`
+    /// if it doesn't fit.
+    fn encode(&self, buf: &mut [u8]) -> Option<usize>;
+}
+
+/// gRPC status codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Code {
+    Ok = 0,
+    Cancelled = 1,
+    Unknown = 2,
+    InvalidArgument = 3,
+    DeadlineExceeded = 4,
+    NotFound = 5,
+    AlreadyExists = 6,
+    PermissionDenied = 7,
+    ResourceExhausted = 8,
+    FailedPrecondition = 9,
+    Aborted = 10,
+    OutOfRange = 11,
+    Unimplemented = 12,
+    Internal = 13,
+    Unavailable = 14,
+    DataLoss = 15,
+    Unauthenticated = 16,
+}
+
+/// Outcome of a call, sent in the trailers of the response.
+///
+/// Returned as an error, the call is answered without messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Status {
+    code: Code,
+    message: &'static str,
+}
+
+impl Status {
+    /// Creates a status with `code` and a `message` for the client, which may be empty.
+    pub const fn new(code: Code, message: &'static str) -> Self {
+        Self { code, message }
+    }
+
+    pub const fn code(&self) -> Code {
+        self.code
+    }
+
+    pub const fn message(&self) -> &'static str {
+        self.message
+    }
+
+    /// Writes the status as trailers in the format of the trailers frame and
+    /// the headers of a response without messages.
+    fn trailers(&self, separator: &str) -> heapless::String<128> {
+        let mut trailers = heapless::String::new();
+        // Can't overflow, the status is at most 17 bytes.
+        let _ = write!(trailers, "grpc-status{separator}{}\r\n", self.code as u8);
+        if !self.message.is_empty() {
+            let len = trailers.len();
+            let message = url::encode(self.message, &MESSAGE);
+            if write!(trailers, "grpc-message{separator}{message}\r\n").is_err() {
+                // Only the status is sent for messages too long to encode.
+                trailers.truncate(len);
+            }
+        }
+        trailers
+    }
+}
+
+impl IntoResponse for Status {
+    type Body = Self;
+
+    fn into_response(self) -> Response<Self::Body> {
+        Response::new(self).with_content_type(CONTENT_TYPE)
+    }
+}
+
+impl ResponseBody for Status {
+    type Error = Infallible;
+
+    fn content_length(&self) -> Option<usize> {
+        Some(0)
+    }
+
+    async fn write_to<W: Write>(
+        self,
+        writer: &mut ResponseWriter<'_, W>,
+        _buf: &mut [u8],
+    ) -> Result<(), WriteError<W::Error, Self::Error>> {
+        let trailers = self.trailers(": ");
+        for header in trailers.split_terminator("\r\n") {
+            let (name, value) = header.split_once(": ").unwrap_or_default();
+            writer
+                .write_header(name, value)
+                .await
+                .map_err(WriteError::Io)?;
+        }
+        Ok(())
+    }
+}
+
+define_rejection! {
+    #[status = BAD_REQUEST]
+    #[body = "Invalid gRPC-web message"]
+    /// The request body is not a single uncompressed message which can be decoded.
+    pub struct InvalidMessage;
+}
+
+composite_rejection! {
+    /// Rejection used for [`Grpc`].
+    pub enum GrpcRejection {
+        UnsupportedMediaType,
+        VecRejection,
+        InvalidMessage,
+    }
+}
+
+/// Extracts the request message of a call, or responds with the single message of
+/// a unary call.
+///
+/// The request is buffered in `N` bytes on the stack including the 5 bytes of
+/// framing, larger requests are rejected with `413 Payload Too Large`, other media
+/// types with `415 Unsupported Media Type`. Responses are encoded into the relay
+/// buffer of the `BODY` [`Buffers`](crate::Buffers), messages not fitting are
+/// answered with [`Code::Internal`].
+#[derive(Debug, Clone, Copy)]
+pub struct Grpc<T, const N: usize = 512>(pub T);
+
+impl<'a, S, T: Message, const N: usize> FromRequest<'a, S> for Grpc<T, N> {
+    type Rejection = GrpcRejection;
+
+    async fn from_request<R: Read>(
+        req: Request<'a, R>,
+        state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let grpc = req
+            .parts
+            .headers
+            .get_first("Content-Type")
+            .is_some_and(|value| {
+                content_type::matches(value, "application/grpc-web")
+                    || content_type::matches(value, CONTENT_TYPE)
+            });
+        if !grpc {
+            return Err(UnsupportedMediaType.into());
+        }
+
+        let body = heapless::Vec::<u8, N>::from_request(req, state).await?;
+        let message = match &body[..] {
+            [0, a, b, c, d, message @ ..]
+                if u32::from_be_bytes([*a, *b, *c, *d]) as usize == message.len() =>
+            {
+                message
+            }
+            _ => return Err(InvalidMessage.into()),
+        };
+        T::decode(message).map(Grpc).ok_or(InvalidMessage.into())
+    }
+}
+
+impl<T: Message, const N: usize> IntoResponse for Grpc<T, N> {
+    type Body = Self;
+
+    fn into_response(self) -> Response<Self::Body> {
+        Response::new(self).with_content_type(CONTENT_TYPE)
+    }
+}
+
+impl<T: Message, const N: usize> ResponseBody for Grpc<T, N> {
+    type Error = Infallible;
+
+    async fn write_to<W: Write>(
+        self,
+        writer: &mut ResponseWriter<'_, W>,
+        buf: &mut [u8],
+    ) -> Result<(), WriteError<W::Error, Self::Error>> {
+        let Some(len) = encode(&self.0, buf) else {
+            writer
+                .write_header("Content-Length", "0")
+                .await
+                .map_err(WriteError::Io)?;
+            return too_large().write_to(writer, buf).await;
+        };
+        let trailers = Status::new(Code::Ok, "").trailers(":");
+
+        let mut content_length = heapless::String::<20>::new();
+        // Can't overflow, a `usize` has at most 20 digits.
+        let _ = write!(content_length, "{}", len + 5 + trailers.len());
+        writer
+            .write_header("Content-Length", &content_length)
+            .await
+            .map_err(WriteError::Io)?;
+
+        writer
+            .write_all(&buf[..len])
+            .await
+            .map_err(WriteError::Io)?;
+        write_trailers(writer, &trailers).await
+    }
+}
+
+/// A stream of messages, see [`Streaming`].
+pub trait MessageStream {
+    type Item: Message;
+
+    /// Returns the next message, `None` once the call completed successfully.
+    ///
+    /// An error ends the call with its status.
+    fn next(&mut self) -> impl Future<Output = Option<Result<Self::Item, Status>>>;
+}
+
+/// Responds to a server-streaming call with the messages of a [`MessageStream`].
+///
+/// Each message is sent as soon as it is produced, with chunked transfer-encoding.
+/// Messages are encoded into the relay buffer of the `BODY` [`Buffers`](crate::Buffers),
+/// a message not fitting ends the call with [`Code::Internal`].
+#[derive(Debug, Clone, Copy)]
+pub struct Streaming<S>(pub S);
+
+impl<S: MessageStream> IntoResponse for Streaming<S> {
+    type Body = Self;
+
+    fn into_response(self) -> Response<Self::Body> {
+        Response::new(self).with_content_type(CONTENT_TYPE)
+    }
+}
+
+impl<S: MessageStream> ResponseBody for Streaming<S> {
+    type Error = Infallible;
+
+    async fn write_to<W: Write>(
+        mut self,
+        writer: &mut ResponseWriter<'_, W>,
+        buf: &mut [u8],
+    ) -> Result<(), WriteError<W::Error, Self::Error>> {
+        writer
+            .write_header("Transfer-Encoding", "chunked")
+            .await
+            .map_err(WriteError::Io)?;
+
+        let status = loop {
+            let message = match self.0.next().await {
+                Some(Ok(message)) => message,
+                Some(Err(status)) => break status,
+                None => break Status::new(Code::Ok, ""),
+            };
+            let Some(len) = encode(&message, buf) else {
+                break too_large();
+            };
+            writer
+                .write_all(&buf[..len])
+                .await
+                .map_err(WriteError::Io)?;
+            writer.flush().await.map_err(WriteError::Io)?;
+        };
+
+        write_trailers(writer, &status.trailers(":")).await
+    }
+}
+
+/// Encodes a message frame into `buf`, returns its length.
+fn encode<T: Message>(message: &T, buf: &mut [u8]) -> Option<usize> {
+    if buf.len() < 5 {
+        return None;
+    }
+    let len = message.encode(&mut buf[5..])?;
+    buf[..5].copy_from_slice(&frame_header(0, len));
+    Some(len + 5)
+}
+
+fn frame_header(flags: u8, len: usize) -> [u8; 5] {
+    let [a, b, c, d] = (len as u32).to_be_bytes();
+    [flags, a, b, c, d]
+}
+
+fn too_large() -> Status {
+    Status::new(Code::Internal, "Response message too large")
+}
+
+async fn write_trailers<W: Write>(
+    writer: &mut ResponseWriter<'_, W>,
+    trailers: &str,
+) -> Result<(), WriteError<W::Error, Infallible>> {
+    writer
+        .write_all(&frame_header(TRAILERS, trailers.len()))
+        .await
+        .map_err(WriteError::Io)?;
+    writer
+        .write_all(trailers.as_bytes())
+        .await
+        .map_err(WriteError::Io)
+}
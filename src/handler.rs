@@ -4,16 +4,6 @@ use crate::{
     either::Either, route::Route, FromRequest, FromRequestParts, IntoResponse, Read, Request,
 };
 
-pub trait Handler<S> {
-    type Response: IntoResponse;
-
-    fn call<Body: Read>(
-        &self,
-        req: Request<'_, Body>,
-        state: &S,
-    ) -> impl Future<Output = Self::Response>;
-}
-
 pub trait HandlerFunction<S, Params> {
     type Response: IntoResponse;
 
@@ -0,0 +1,180 @@
+//! Typed request headers, extracted with [`TypedHeader`](crate::extract::TypedHeader).
+//!
+//! The types copy the header value, handlers can take them as arguments without
+//! borrowing the request. Values longer than the capacity `N` are rejected like
+//! malformed ones.
+
+use crate::{content_type, digest, extract};
+
+/// A header parsed from its value.
+pub trait Header: Sized {
+    /// Name of the header, compared ignoring case.
+    const NAME: &'static str;
+
+    /// Parses the value of the first header named [`NAME`](Self::NAME).
+    fn decode(value: &str) -> Option<Self>;
+}
+
+/// The `Content-Type` of the request body.
+///
+/// ```
+/// use low_profile::header::{ContentType, Header};
+///
+/// let content_type = ContentType::<32>::decode("application/json; charset=utf-8").unwrap();
+/// assert_eq!(content_type.media_type(), "application/json");
+/// assert!(content_type.matches("application/*"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentType<const N: usize = 128>(heapless::String<N>);
+
+impl<const N: usize> ContentType<N> {
+    /// The whole header value including parameters.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The `type/subtype` essence without parameters.
+    pub fn media_type(&self) -> &str {
+        self.0.split(';').next().unwrap_or_default().trim()
+    }
+
+    /// Compares the media type, see [`content_type::matches`].
+    pub fn matches(&self, media_type: &str) -> bool {
+        content_type::matches(&self.0, media_type)
+    }
+}
+
+impl<const N: usize> Header for ContentType<N> {
+    const NAME: &'static str = "Content-Type";
+
+    fn decode(value: &str) -> Option<Self> {
+        if !value.contains('/') {
+            return None;
+        }
+        value.try_into().ok().map(Self)
+    }
+}
+
+/// The `Content-Length` of the request body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentLength(pub usize);
+
+impl Header for ContentLength {
+    const NAME: &'static str = "Content-Length";
+
+    fn decode(value: &str) -> Option<Self> {
+        value.trim().parse().ok().map(Self)
+    }
+}
+
+/// The credentials of the `Authorization` header, e.g. `Bearer <token>`.
+///
+/// ```
+/// use low_profile::header::{Authorization, Header};
+///
+/// let auth = Authorization::<64>::decode("Bearer abc.def").unwrap();
+/// assert_eq!(auth.bearer(), Some("abc.def"));
+///
+/// let auth = Authorization::<64>::decode("Basic YWRtaW46c2VjcmV0").unwrap();
+/// let mut buf = [0; 32];
+/// assert_eq!(auth.basic(&mut buf), Some(("admin", "secret")));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Authorization<const N: usize = 256> {
+    value: heapless::String<N>,
+    scheme: usize,
+}
+
+impl<const N: usize> Authorization<N> {
+    /// The authentication scheme, e.g. `Bearer` or `Basic`.
+    pub fn scheme(&self) -> &str {
+        &self.value[..self.scheme]
+    }
+
+    /// The credentials following the scheme.
+    pub fn credentials(&self) -> &str {
+        self.value[self.scheme..].trim_start()
+    }
+
+    /// The token of the `Bearer` scheme.
+    pub fn bearer(&self) -> Option<&str> {
+        self.scheme()
+            .eq_ignore_ascii_case("Bearer")
+            .then(|| self.credentials())
+    }
+
+    /// The user name and password of the `Basic` scheme, decoded into `buf`.
+    ///
+    /// Returns `None` for other schemes or if the credentials don't fit into `buf`.
+    pub fn basic<'b>(&self, buf: &'b mut [u8]) -> Option<(&'b str, &'b str)> {
+        if !self.scheme().eq_ignore_ascii_case("Basic") {
+            return None;
+        }
+        let len = digest::base64_decode(self.credentials(), buf)?;
+        let credentials = core::str::from_utf8(&buf[..len]).ok()?;
+        credentials.split_once(':')
+    }
+}
+
+impl<const N: usize> Header for Authorization<N> {
+    const NAME: &'static str = "Authorization";
+
+    fn decode(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let scheme = value.find(' ').unwrap_or(value.len());
+        if scheme == 0 {
+            return None;
+        }
+        Some(Self {
+            value: value.try_into().ok()?,
+            scheme,
+        })
+    }
+}
+
+/// The `Host` header, an owned [`extract::Host`].
+///
+/// ```
+/// use low_profile::header::{Header, Host};
+///
+/// let host = Host::<32>::decode("device.local:8080").unwrap();
+/// assert!(host.is("Device.local"));
+/// assert_eq!(host.port(), Some(8080));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Host<const N: usize = 64> {
+    name: heapless::String<N>,
+    port: Option<u16>,
+}
+
+impl<const N: usize> Host<N> {
+    /// The host name or address without port and trailing dot.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn port(&self) -> Option<u16> {
+        self.port
+    }
+
+    /// Compares the host name with `name` ignoring its case and a trailing dot.
+    pub fn is(&self, name: &str) -> bool {
+        self.as_host().is(name)
+    }
+
+    pub fn as_host(&self) -> extract::Host<'_> {
+        extract::Host::new(&self.name, self.port)
+    }
+}
+
+impl<const N: usize> Header for Host<N> {
+    const NAME: &'static str = "Host";
+
+    fn decode(value: &str) -> Option<Self> {
+        let host = extract::Host::parse(value)?;
+        Some(Self {
+            name: host.name().try_into().ok()?,
+            port: host.port(),
+        })
+    }
+}
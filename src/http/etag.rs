@@ -0,0 +1,172 @@
+//! Entity tags and their comparison rules, see RFC 9110 section 8.8.3.
+
+use core::fmt;
+
+/// Error returned when parsing an entity tag fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidETag;
+
+impl fmt::Display for InvalidETag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid entity tag")
+    }
+}
+
+/// An entity tag, e.g. `"xyzzy"` or the weak `W/"xyzzy"`.
+///
+/// ```
+/// use low_profile::http::ETag;
+///
+/// let strong = ETag::strong("1");
+/// let weak = ETag::parse(r#"W/"1""#).unwrap();
+///
+/// assert!(strong.weak_eq(&weak));
+/// assert!(!strong.strong_eq(&weak));
+/// assert_eq!(weak.to_string(), r#"W/"1""#);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ETag<'a> {
+    weak: bool,
+    tag: &'a str,
+}
+
+impl<'a> ETag<'a> {
+    /// Creates a strong entity tag, `tag` is the opaque tag without quotes.
+    pub const fn strong(tag: &'a str) -> Self {
+        Self { weak: false, tag }
+    }
+
+    /// Creates a weak entity tag, `tag` is the opaque tag without quotes.
+    pub const fn weak(tag: &'a str) -> Self {
+        Self { weak: true, tag }
+    }
+
+    /// Parses a single entity tag including its quotes.
+    pub fn parse(s: &'a str) -> Result<Self, InvalidETag> {
+        match Self::parse_prefix(s.trim()) {
+            Some((etag, "")) => Ok(etag),
+            _ => Err(InvalidETag),
+        }
+    }
+
+    /// Parses an entity tag at the start of `s`, returning it and the remaining input.
+    fn parse_prefix(s: &'a str) -> Option<(Self, &'a str)> {
+        let (weak, s) = match s.strip_prefix("W/") {
+            Some(s) => (true, s),
+            None => (false, s),
+        };
+        let s = s.strip_prefix('"')?;
+        let end = s.find('"')?;
+        let tag = &s[..end];
+
+        // etagc = %x21 / %x23-7E / obs-text
+        if tag.bytes().any(|c| c <= 0x20 || c == 0x7f) {
+            return None;
+        }
+        Some((Self { weak, tag }, &s[end + 1..]))
+    }
+
+    pub fn is_weak(&self) -> bool {
+        self.weak
+    }
+
+    /// The opaque tag without quotes.
+    pub fn tag(&self) -> &'a str {
+        self.tag
+    }
+
+    /// Strong comparison, both tags must be strong and identical.
+    pub fn strong_eq(&self, other: &ETag<'_>) -> bool {
+        !self.weak && !other.weak && self.tag == other.tag
+    }
+
+    /// Weak comparison, the tags must be identical regardless of being weak.
+    pub fn weak_eq(&self, other: &ETag<'_>) -> bool {
+        self.tag == other.tag
+    }
+}
+
+impl<'a> fmt::Display for ETag<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.weak {
+            f.write_str("W/")?;
+        }
+        write!(f, "\"{}\"", self.tag)
+    }
+}
+
+/// A parsed `If-Match` or `If-None-Match` header value.
+enum Condition<'a> {
+    Any,
+    List(&'a str),
+}
+
+impl<'a> Condition<'a> {
+    fn parse(header: &'a str) -> Self {
+        match header.trim() {
+            "*" => Self::Any,
+            list => Self::List(list),
+        }
+    }
+
+    /// Returns `true` if any listed entity tag matches `current` according to `eq`.
+    ///
+    /// Invalid list members are skipped.
+    fn any(list: &'a str, mut eq: impl FnMut(&ETag<'a>) -> bool) -> bool {
+        let mut rest = list;
+        loop {
+            rest = rest.trim_start_matches(|c: char| c == ',' || c.is_ascii_whitespace());
+            if rest.is_empty() {
+                return false;
+            }
+            match ETag::parse_prefix(rest) {
+                Some((etag, tail)) => {
+                    if eq(&etag) {
+                        return true;
+                    }
+                    rest = tail;
+                }
+                // Skip to the next list member.
+                None => rest = rest.find(',').map_or("", |i| &rest[i..]),
+            }
+        }
+    }
+}
+
+/// Evaluates an `If-Match` header value, returns `true` if the request may proceed.
+///
+/// `current` is the entity tag of the current representation, `None` if there is none.
+/// Uses strong comparison, `*` matches any current representation.
+pub fn if_match(header: &str, current: Option<&ETag<'_>>) -> bool {
+    match (Condition::parse(header), current) {
+        (Condition::Any, current) => current.is_some(),
+        (Condition::List(_), None) => false,
+        (Condition::List(list), Some(current)) => {
+            Condition::any(list, |etag| etag.strong_eq(current))
+        }
+    }
+}
+
+/// Evaluates an `If-None-Match` header value, returns `true` if the request may proceed.
+///
+/// `current` is the entity tag of the current representation, `None` if there is none.
+/// Uses weak comparison, `*` matches any current representation.
+/// When this fails, `GET` and `HEAD` requests are answered with `304 Not Modified`,
+/// other methods with `412 Precondition Failed`.
+///
+/// ```
+/// use low_profile::http::{if_none_match, ETag};
+///
+/// let current = ETag::strong("v2");
+/// assert!(!if_none_match(r#"W/"v1", W/"v2""#, Some(&current)));
+/// assert!(if_none_match(r#""v1""#, Some(&current)));
+/// ```
+pub fn if_none_match(header: &str, current: Option<&ETag<'_>>) -> bool {
+    match (Condition::parse(header), current) {
+        (Condition::Any, current) => current.is_none(),
+        (Condition::List(_), None) => true,
+        (Condition::List(list), Some(current)) => {
+            !Condition::any(list, |etag| etag.weak_eq(current))
+        }
+    }
+}
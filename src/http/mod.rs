@@ -1,3 +1,7 @@
+mod etag;
+mod range;
 mod status;
 
+pub use etag::*;
+pub use range::*;
 pub use status::*;
@@ -0,0 +1,68 @@
+//! Byte ranges of the `Range` header, see RFC 9110 section 14.
+
+use core::{fmt, ops::Range};
+
+/// Error returned when the requested range lies outside the representation, answered
+/// with `416 Range Not Satisfiable`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RangeNotSatisfiable;
+
+impl fmt::Display for RangeNotSatisfiable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("range not satisfiable")
+    }
+}
+
+/// Evaluates a `Range` header value against a representation of `len` bytes.
+///
+/// Returns the requested bytes, or `None` if the header is to be ignored and the whole
+/// representation sent: for units other than `bytes`, invalid syntax and several
+/// ranges, which would require a multipart response. Ranges reaching past the end are
+/// shortened, ranges starting after it are not satisfiable.
+///
+/// ```
+/// use low_profile::http::{byte_range, RangeNotSatisfiable};
+///
+/// assert_eq!(byte_range("bytes=0-99", 1000), Ok(Some(0..100)));
+/// assert_eq!(byte_range("bytes=900-", 1000), Ok(Some(900..1000)));
+/// assert_eq!(byte_range("bytes=-100", 1000), Ok(Some(900..1000)));
+/// assert_eq!(byte_range("bytes=0-0,-1", 1000), Ok(None));
+/// assert_eq!(byte_range("bytes=1000-", 1000), Err(RangeNotSatisfiable));
+/// ```
+pub fn byte_range(header: &str, len: usize) -> Result<Option<Range<usize>>, RangeNotSatisfiable> {
+    let Some((unit, spec)) = header.trim().split_once('=') else {
+        return Ok(None);
+    };
+    if !unit.trim().eq_ignore_ascii_case("bytes") || spec.contains(',') {
+        return Ok(None);
+    }
+    let Some((first, last)) = spec.trim().split_once('-') else {
+        return Ok(None);
+    };
+
+    let range = match (digits(first), digits(last)) {
+        // The last `n` bytes.
+        (None, Some(n)) if first.is_empty() => {
+            if n == 0 || len == 0 {
+                return Err(RangeNotSatisfiable);
+            }
+            len.saturating_sub(n)..len
+        }
+        (Some(first), None) if last.is_empty() => first..len,
+        (Some(first), Some(last)) if first <= last => first..len.min(last.saturating_add(1)),
+        _ => return Ok(None),
+    };
+    if range.start >= len {
+        return Err(RangeNotSatisfiable);
+    }
+    Ok(Some(range))
+}
+
+/// Parses a position, which consists of digits only. Positions too large for a `usize`
+/// are saturated, they lie beyond any representation.
+fn digits(s: &str) -> Option<usize> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some(s.parse().unwrap_or(usize::MAX))
+}
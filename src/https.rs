@@ -0,0 +1,294 @@
+//! Companions for serving HTTPS, TLS itself is terminated outside of this crate.
+//!
+//! [`HttpsRedirect`] is served on the plain HTTP port and redirects every request
+//! to the HTTPS origin, while [`Router::hsts`](crate::Router::hsts) makes clients
+//! remember to use HTTPS right away.
+//!
+//! ```
+//! use low_profile::{https::{Hsts, HttpsRedirect}, Router};
+//!
+//! // Served on port 80.
+//! let plain = HttpsRedirect::new();
+//! // Served on port 443 behind TLS.
+//! let secure = Router::<(), _>::new()
+//!     .get("/", || async { "hello" })
+//!     .hsts(Hsts::new(31_536_000).include_subdomains());
+//! ```
+
+use core::{convert::Infallible, fmt::Write as _, mem::MaybeUninit};
+
+use crate::{
+    extract::Host,
+    http::StatusCode,
+    parse::PathAndQuery,
+    request::{HeaderIndices, Headers},
+    response::{Redirect, ResponseBody, ResponseWriter, WriteError},
+    route::{Decision, Route},
+    router::{parse_head, write_response},
+    service::{ServiceConfig, ServiceError},
+    IntoResponse, Read, Request, Response, Service, Write,
+};
+
+/// Maximum length of the `Strict-Transport-Security` header value.
+const HSTS: usize = 64;
+
+/// Service redirecting all requests to HTTPS with `301 Moved Permanently`,
+/// preserving their path and query.
+///
+/// The origin is taken from the request's `Host` header unless set with
+/// [`host`](Self::host), requests without either are answered with `400 Bad Request`.
+/// `REQUEST` and `HEADERS` limit the request head like [`Buffers`](crate::Buffers),
+/// the body of requests is never read. Malformed heads, heads not fitting into
+/// `REQUEST` bytes and invalid targets are answered with `400 Bad Request` as well, locations longer than
+/// 256 bytes with `414 URI Too Long`.
+#[derive(Debug, Clone, Copy)]
+pub struct HttpsRedirect<const REQUEST: usize = 1024, const HEADERS: usize = 32> {
+    host: Option<&'static str>,
+    port: Option<u16>,
+}
+
+impl HttpsRedirect {
+    pub const fn new() -> Self {
+        Self {
+            host: None,
+            port: None,
+        }
+    }
+}
+
+impl Default for HttpsRedirect {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const REQUEST: usize, const HEADERS: usize> HttpsRedirect<REQUEST, HEADERS> {
+    /// Redirects to `host` instead of the host requested by the client.
+    pub const fn host(mut self, host: &'static str) -> Self {
+        self.host = Some(host);
+        self
+    }
+
+    /// Redirects to `port` instead of the default HTTPS port 443.
+    pub const fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Changes the buffer sizes used when reading the request head.
+    pub const fn buffers<const R: usize, const H: usize>(self) -> HttpsRedirect<R, H> {
+        HttpsRedirect {
+            host: self.host,
+            port: self.port,
+        }
+    }
+
+    fn redirect(&self, target: &str, headers: Headers<'_>) -> Response<Redirect> {
+        let host = self.host.or_else(|| {
+            headers
+                .get_first("Host")
+                .and_then(Host::parse)
+                .map(|host| host.name())
+        });
+        let Some(host) = host else {
+            return Redirect::new(StatusCode::BAD_REQUEST, None).into_response();
+        };
+
+        let mut location = heapless::String::new();
+        let written = write!(location, "https://{host}")
+            .and_then(|()| match self.port {
+                Some(port) if port != 443 => write!(location, ":{port}"),
+                _ => Ok(()),
+            })
+            .and_then(|()| location.push_str(target).map_err(|_| core::fmt::Error));
+
+        match written {
+            Ok(()) => Redirect::new(StatusCode::MOVED_PERMANENTLY, Some(location)),
+            Err(_) => Redirect::new(StatusCode::URI_TOO_LONG, None),
+        }
+        .into_response()
+    }
+}
+
+impl<const REQUEST: usize, const HEADERS: usize> Service for HttpsRedirect<REQUEST, HEADERS> {
+    type BodyError = Infallible;
+
+    async fn serve<Re: Read, Wr: Write<Error = Re::Error>>(
+        &self,
+        mut reader: Re,
+        writer: Wr,
+    ) -> Result<(), ServiceError<Re::Error, Self::BodyError>> {
+        let mut buf = [0u8; REQUEST];
+
+        let mut headers_indices: [MaybeUninit<HeaderIndices>; HEADERS] = unsafe {
+            // SAFETY: We can go safely from MaybeUninit array to array of MaybeUninit
+            MaybeUninit::uninit().assume_init()
+        };
+
+        let mut pos = 0;
+        let head = loop {
+            if pos == buf.len() {
+                // The target is too long to be redirected to.
+                return bad_request(writer).await;
+            }
+            let read = reader
+                .read(&mut buf[pos..])
+                .await
+                .map_err(ServiceError::Io)?;
+            if read == 0 {
+                return Ok(());
+            }
+            pos += read;
+
+            let mut headers: [MaybeUninit<httparse::Header<'_>>; HEADERS] =
+                unsafe { MaybeUninit::uninit().assume_init() };
+
+            match parse_head(&buf[..pos], &mut headers, &mut headers_indices) {
+                Ok(Some(head)) => break head,
+                Ok(None) => continue,
+                Err(err) => {
+                    bad_request(writer).await?;
+                    return Err(ServiceError::ProtocolError(err));
+                }
+            }
+        };
+
+        // Only validated, the target is kept as sent, including its query.
+        if PathAndQuery::parse(head.path(&buf)).is_err() {
+            return bad_request(writer).await;
+        }
+
+        let headers = Headers {
+            buf: &buf,
+            headers: head.headers,
+        };
+        let response = self.redirect(head.path(&buf), headers);
+
+        write_response::<_, _, 0>(writer, response, false, &ServiceConfig::new())
+            .await
+            .map(|_| ())
+    }
+}
+
+/// Answers a request which can't be redirected with `400 Bad Request`.
+async fn bad_request<W: Write>(writer: W) -> Result<(), ServiceError<W::Error, Infallible>> {
+    let response = (StatusCode::BAD_REQUEST, "Bad Request").into_response();
+    write_response::<_, _, 0>(writer, response, false, &ServiceConfig::new())
+        .await
+        .map(|_| ())
+}
+
+/// Settings of the `Strict-Transport-Security` header, see [`Router::hsts`](crate::Router::hsts).
+#[derive(Debug, Clone, Copy)]
+pub struct Hsts {
+    max_age: u32,
+    include_subdomains: bool,
+    preload: bool,
+}
+
+impl Hsts {
+    /// Clients only use HTTPS for the next `max_age` seconds, `0` revokes a former policy.
+    pub const fn new(max_age: u32) -> Self {
+        Self {
+            max_age,
+            include_subdomains: false,
+            preload: false,
+        }
+    }
+
+    /// Applies the policy to all subdomains as well.
+    pub const fn include_subdomains(mut self) -> Self {
+        self.include_subdomains = true;
+        self
+    }
+
+    /// Consents to being included in browser preload lists.
+    pub const fn preload(mut self) -> Self {
+        self.preload = true;
+        self
+    }
+}
+
+pub(crate) struct HstsRoute<R> {
+    pub(crate) hsts: Hsts,
+    pub(crate) route: R,
+}
+
+impl<S, R: Route<S>> Route<S> for HstsRoute<R> {
+    type Response = Secured<R::Response>;
+
+    async fn match_request<'a, Body: Read>(
+        &'a self,
+        req: Request<'a, Body>,
+        state: &'a S,
+    ) -> Decision<'a, Self::Response, Body> {
+        self.route
+            .match_request(req, state)
+            .await
+            .map(|response| Secured {
+                hsts: self.hsts,
+                inner: response,
+            })
+    }
+}
+
+/// A response or body sent with a `Strict-Transport-Security` header.
+pub struct Secured<T> {
+    hsts: Hsts,
+    inner: T,
+}
+
+impl<T: IntoResponse> IntoResponse for Secured<T> {
+    type Body = Secured<T::Body>;
+
+    fn into_response(self) -> Response<Self::Body> {
+        let hsts = self.hsts;
+        self.inner
+            .into_response()
+            .map_body(|inner| Secured { hsts, inner })
+    }
+}
+
+impl<B: ResponseBody> ResponseBody for Secured<B> {
+    type Error = B::Error;
+
+    fn content_length(&self) -> Option<usize> {
+        self.inner.content_length()
+    }
+
+    fn as_bytes(&self) -> Option<&[u8]> {
+        self.inner.as_bytes()
+    }
+
+    fn forward_to(&self) -> Option<&'static str> {
+        self.inner.forward_to()
+    }
+
+    async fn write_to<W: Write>(
+        self,
+        writer: &mut ResponseWriter<'_, W>,
+        buf: &mut [u8],
+    ) -> Result<(), WriteError<W::Error, Self::Error>> {
+        let Hsts {
+            max_age,
+            include_subdomains,
+            preload,
+        } = self.hsts;
+
+        let mut value = heapless::String::<HSTS>::new();
+        // Can't overflow, the longest value is 52 bytes.
+        let _ = write!(value, "max-age={max_age}");
+        if include_subdomains {
+            let _ = value.push_str("; includeSubDomains");
+        }
+        if preload {
+            let _ = value.push_str("; preload");
+        }
+
+        writer
+            .write_header("Strict-Transport-Security", &value)
+            .await
+            .map_err(WriteError::Io)?;
+        self.inner.write_to(writer, buf).await
+    }
+}
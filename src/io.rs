@@ -2,6 +2,7 @@ use core::convert::Infallible;
 
 pub use embedded_io_async::{ErrorType, Read, Write};
 
+#[derive(Clone)]
 pub struct Cursor<T> {
     inner: T,
     pos: usize,
@@ -0,0 +1,88 @@
+//! Middleware for concerns shared by many routes, e.g. authentication or logging.
+//!
+//! A [`Layer`] wraps a route into another [`Route`], which sees every request
+//! before the wrapped route and every response after it. It can answer requests
+//! itself, modify the request [`Parts`](crate::Parts) or replace the response.
+//! Layers are applied to single routes with [`Layer::layer`] or to all routes
+//! registered so far with [`Router::layer`](crate::Router::layer):
+//!
+//! ```
+//! use low_profile::{
+//!     http::StatusCode, layer::Layer, post, Decision, Either, IntoResponse, Read, Request,
+//!     Response, Route, Router,
+//! };
+//!
+//! /// Answers requests without an `Authorization` header with `401 Unauthorized`.
+//! struct RequireAuth;
+//!
+//! struct RequireAuthRoute<R>(R);
+//!
+//! impl<S, R: Route<S>> Layer<S, R> for RequireAuth {
+//!     type Route = RequireAuthRoute<R>;
+//!
+//!     fn layer(self, route: R) -> Self::Route {
+//!         RequireAuthRoute(route)
+//!     }
+//! }
+//!
+//! impl<S, R: Route<S>> Route<S> for RequireAuthRoute<R> {
+//!     type Response = Either<R::Response, (StatusCode, &'static str)>;
+//!
+//!     async fn match_request<'a, Body: Read>(
+//!         &'a self,
+//!         req: Request<'a, Body>,
+//!         state: &'a S,
+//!     ) -> Decision<'a, Self::Response, Body> {
+//!         if req.parts().headers.get_first("Authorization").is_none() {
+//!             let response = (StatusCode::UNAUTHORIZED, "Unauthorized");
+//!             return Decision::Match(Either::Right(response));
+//!         }
+//!         self.0.match_request(req, state).await.map(Either::Left)
+//!     }
+//! }
+//!
+//! /// Logs the status of every response.
+//! struct LogStatus;
+//!
+//! struct LogStatusRoute<R>(R);
+//!
+//! impl<S, R: Route<S>> Layer<S, R> for LogStatus {
+//!     type Route = LogStatusRoute<R>;
+//!
+//!     fn layer(self, route: R) -> Self::Route {
+//!         LogStatusRoute(route)
+//!     }
+//! }
+//!
+//! impl<S, R: Route<S>> Route<S> for LogStatusRoute<R> {
+//!     type Response = Response<<R::Response as IntoResponse>::Body>;
+//!
+//!     async fn match_request<'a, Body: Read>(
+//!         &'a self,
+//!         req: Request<'a, Body>,
+//!         state: &'a S,
+//!     ) -> Decision<'a, Self::Response, Body> {
+//!         let path = req.path();
+//!         self.0.match_request(req, state).await.map(|response| {
+//!             let response = response.into_response();
+//!             log(path, response.status_code());
+//!             response
+//!         })
+//!     }
+//! }
+//! # fn log(_path: &str, _status: StatusCode) {}
+//!
+//! let router = Router::<(), _>::new()
+//!     .get("/status", || async { "ok" })
+//!     .route("/reboot", RequireAuth.layer(post(|| async { "rebooting" })))
+//!     .layer(LogStatus);
+//! ```
+
+use crate::Route;
+
+/// Wraps a route into another route, see the [module](self) documentation.
+pub trait Layer<S, R: Route<S>> {
+    type Route: Route<S>;
+
+    fn layer(self, route: R) -> Self::Route;
+}
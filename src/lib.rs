@@ -2,34 +2,65 @@
 #![allow(stable_features)]
 #![feature(
     async_fn_in_trait,
-    type_alias_impl_trait,
     return_position_impl_trait_in_trait,
-    maybe_uninit_slice,
     impl_trait_projections,
     const_waker,
-    impl_trait_in_assoc_type
+    ip_in_core
 )]
 
+#[cfg(feature = "std")]
+extern crate std;
+
+pub mod arena;
+pub mod auth;
+pub mod coalesce;
+pub mod conditional;
+pub mod content_type;
+pub mod device_info;
+pub mod digest;
 pub(crate) mod either;
+pub mod embed;
 mod error;
 pub mod extract;
+pub mod fs;
+#[cfg(feature = "grpc-web")]
+pub mod grpc_web;
 mod handler;
+pub mod header;
 pub mod http;
+pub mod https;
 mod io;
+pub mod layer;
+pub mod log;
 mod method;
+pub mod mirror;
+pub mod mount;
 mod parse;
+pub mod pool;
+pub mod range;
 pub mod request;
 pub mod response;
+pub mod rewrite;
 mod route;
 mod router;
 mod service;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+pub mod time;
+#[cfg(feature = "trace")]
+pub mod trace;
+pub mod url;
 mod utils;
+pub mod value;
+pub mod watch;
 
+pub use arena::Arena;
+pub use either::Either;
 pub use extract::{FromRef, FromRequest, FromRequestParts};
 pub use io::{ErrorType, Read, Write};
 pub use method::Method;
-pub use request::{Headers, Parts, Request};
+pub use request::{Headers, Params, Parts, Request};
 pub use response::{IntoResponse, Response};
-pub use route::{connect, delete, get, head, options, patch, post, put, trace};
-pub use router::Router;
-pub use service::Service;
+pub use route::{connect, delete, get, head, options, patch, post, put, trace, Decision, Route};
+pub use router::{Buffers, Router};
+pub use service::{CloseReason, ConnectionEvent, HeadAction, Service, ServiceConfig};
@@ -0,0 +1,426 @@
+//! In-memory log buffer which can be served over HTTP.
+//!
+//! A [`LogBuffer`] keeps the most recent log lines in a fixed size ring buffer,
+//! dropping the oldest lines when full. Its contents can be served as plain text
+//! using [`LogBuffer::text`] or streamed as server sent events using [`LogBuffer::tail`]:
+//!
+//! ```
+//! use low_profile::{extract::State, log::{LogBuffer, LogTail, LogText}, Router};
+//!
+//! struct App {
+//!     log: LogBuffer<4096>,
+//! }
+//!
+//! async fn logs(State(app): State<&App>) -> LogText<'_, 4096> {
+//!     app.log.text()
+//! }
+//!
+//! async fn live(State(app): State<&App>) -> LogTail<'_, 4096> {
+//!     app.log.tail()
+//! }
+//!
+//! let app = App { log: LogBuffer::new() };
+//! app.log.push("booted");
+//! app.log.push_fmt(format_args!("uptime {}s", 42));
+//!
+//! let router = Router::new()
+//!     .get("/logs", logs)
+//!     .get("/logs/live", live)
+//!     .with_state(&app);
+//! ```
+
+use core::{
+    cell::RefCell,
+    fmt::{self, Write as _},
+    future::{poll_fn, Future},
+    task::Poll,
+};
+
+use heapless::Deque;
+
+use crate::{
+    response::{ResponseBody, ResponseWriter, WriteError},
+    utils::WakerSet,
+    IntoResponse, Response, Write,
+};
+
+/// Maximum amount of live tails woken individually, more tails are still served
+/// but are woken on every change of any tail.
+const TAILS: usize = 4;
+
+/// Ring buffer holding the most recent `SIZE` bytes of log lines.
+pub struct LogBuffer<const SIZE: usize> {
+    inner: RefCell<Inner<SIZE>>,
+}
+
+struct Inner<const SIZE: usize> {
+    buf: Deque<u8, SIZE>,
+    /// Total amount of bytes ever pushed, the position after the newest byte.
+    end: u64,
+    wakers: WakerSet<TAILS>,
+    /// Positions of the tails with [`LagPolicy::Block`].
+    blocking: [Option<u64>; TAILS],
+    /// Tasks waiting in [`LogBuffer::push_async`].
+    producers: WakerSet<TAILS>,
+}
+
+impl<const SIZE: usize> Inner<SIZE> {
+    fn start(&self) -> u64 {
+        self.end - self.buf.len() as u64
+    }
+
+    /// Returns `true` if `len` bytes can be pushed without dropping data not sent
+    /// by a blocking tail yet.
+    fn fits(&self, len: usize) -> bool {
+        let Some(pos) = self.blocking.iter().flatten().min() else {
+            return true;
+        };
+        // Longer lines never fit, waiting for them would never end.
+        len > SIZE || (self.end - pos) as usize + len <= SIZE
+    }
+
+    fn push(&mut self, byte: u8) {
+        if self.buf.is_full() {
+            // Drop the oldest line, or whatever is left of it.
+            while let Some(byte) = self.buf.pop_front() {
+                if byte == b'\n' {
+                    break;
+                }
+            }
+        }
+        // Cannot fail, there is room for at least one byte now.
+        let _ = self.buf.push_back(byte);
+        self.end += 1;
+    }
+
+    fn end_line(&mut self) {
+        self.push(b'\n');
+        self.wakers.wake_all();
+    }
+}
+
+impl<const SIZE: usize> fmt::Write for Inner<SIZE> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        s.bytes().for_each(|byte| self.push(byte));
+        Ok(())
+    }
+}
+
+impl<const SIZE: usize> LogBuffer<SIZE> {
+    pub const fn new() -> Self {
+        Self {
+            inner: RefCell::new(Inner {
+                buf: Deque::new(),
+                end: 0,
+                wakers: WakerSet::new(),
+                blocking: [None; TAILS],
+                producers: WakerSet::new(),
+            }),
+        }
+    }
+
+    /// Appends a line.
+    pub fn push(&self, line: &str) {
+        let mut inner = self.inner.borrow_mut();
+        line.bytes().for_each(|byte| inner.push(byte));
+        inner.end_line();
+    }
+
+    /// Appends a line like [`push`](Self::push), first waiting until the tails with
+    /// [`LagPolicy::Block`] sent the data which would be dropped for it.
+    pub async fn push_async(&self, line: &str) {
+        poll_fn(|cx| {
+            let mut inner = self.inner.borrow_mut();
+            if inner.fits(line.len() + 1) {
+                return Poll::Ready(());
+            }
+            inner.producers.register(cx.waker());
+            Poll::Pending
+        })
+        .await;
+        self.push(line);
+    }
+
+    /// Appends a formatted line.
+    pub fn push_fmt(&self, args: fmt::Arguments<'_>) {
+        let mut inner = self.inner.borrow_mut();
+        // Cannot fail, the buffer drops old data instead.
+        let _ = fmt::write(&mut *inner, args);
+        inner.end_line();
+    }
+
+    pub fn clear(&self) {
+        let mut inner = self.inner.borrow_mut();
+        inner.buf.clear();
+    }
+
+    /// Response body with the current contents as plain text.
+    ///
+    /// Lines pushed later are not included, the length of the body is known upfront
+    /// which keeps the connection reusable. If lines are dropped before they are sent,
+    /// the response ends early and the connection is closed.
+    pub fn text(&self) -> LogText<'_, SIZE> {
+        let inner = self.inner.borrow();
+        LogText {
+            log: self,
+            start: inner.start(),
+            end: inner.end,
+        }
+    }
+
+    /// Response body streaming the contents, followed by all new lines,
+    /// as server sent events.
+    ///
+    /// Every line is sent as a separate event, the stream only ends
+    /// when the connection fails.
+    pub fn tail(&self) -> LogTail<'_, SIZE> {
+        LogTail {
+            log: self,
+            on_lag: LagPolicy::default(),
+        }
+    }
+
+    /// Registers a blocking tail at `pos`, `None` if there are too many of them.
+    fn block(&self, pos: u64) -> Option<Blocking<'_, SIZE>> {
+        let mut inner = self.inner.borrow_mut();
+        let slot = inner.blocking.iter().position(Option::is_none)?;
+        inner.blocking[slot] = Some(pos);
+        Some(Blocking { log: self, slot })
+    }
+
+    /// Copies data starting at `pos` until at most `end` into `buf`.
+    ///
+    /// Returns the position the data actually starts at, which is later than `pos`
+    /// if the data was dropped in the meantime, and the amount of bytes copied.
+    fn read_at(&self, pos: u64, end: u64, buf: &mut [u8]) -> (u64, usize) {
+        let inner = self.inner.borrow();
+        let pos = pos.max(inner.start());
+        let offset = (pos - inner.start()) as usize;
+        let len = (end.min(inner.end).saturating_sub(pos) as usize).min(buf.len());
+
+        let (front, back) = inner.buf.as_slices();
+        for (i, byte) in buf[..len].iter_mut().enumerate() {
+            let i = offset + i;
+            *byte = if i < front.len() {
+                front[i]
+            } else {
+                back[i - front.len()]
+            };
+        }
+        (pos, len)
+    }
+
+    /// Waits until data after `pos` is available.
+    fn changed(&self, pos: u64) -> impl Future<Output = ()> + '_ {
+        poll_fn(move |cx| {
+            let mut inner = self.inner.borrow_mut();
+            if inner.end > pos {
+                return Poll::Ready(());
+            }
+            inner.wakers.register(cx.waker());
+            Poll::Pending
+        })
+    }
+}
+
+impl<const SIZE: usize> Default for LogBuffer<SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Plain text response body created by [`LogBuffer::text`].
+pub struct LogText<'l, const SIZE: usize> {
+    log: &'l LogBuffer<SIZE>,
+    /// Range of the buffered data when the body was created.
+    start: u64,
+    end: u64,
+}
+
+impl<'l, const SIZE: usize> ResponseBody for LogText<'l, SIZE> {
+    type Error = core::convert::Infallible;
+
+    fn content_length(&self) -> Option<usize> {
+        Some((self.end - self.start) as usize)
+    }
+
+    async fn write_to<W: Write>(
+        self,
+        writer: &mut ResponseWriter<'_, W>,
+        buf: &mut [u8],
+    ) -> Result<(), WriteError<W::Error, Self::Error>> {
+        writer
+            .write_header("Content-Type", "text/plain; charset=utf-8")
+            .await
+            .map_err(WriteError::Io)?;
+
+        let mut pos = self.start;
+        loop {
+            let (start, len) = self.log.read_at(pos, self.end, buf);
+            // Data dropped in the meantime can't be sent anymore.
+            if len == 0 || start != pos {
+                return Ok(());
+            }
+            writer
+                .write_all(&buf[..len])
+                .await
+                .map_err(WriteError::Io)?;
+            pos = start + len as u64;
+        }
+    }
+}
+
+impl<'l, const SIZE: usize> IntoResponse for LogText<'l, SIZE> {
+    type Body = Self;
+
+    fn into_response(self) -> Response<Self::Body> {
+        Response::new(self)
+    }
+}
+
+/// Behavior of a [`LogTail`] which falls behind, when lines are dropped from the
+/// buffer before they were sent.
+///
+/// Except for [`Block`](Self::Block), pushing lines never waits for tails, a slow
+/// client only affects its own stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LagPolicy {
+    /// Skip the dropped lines and continue with the oldest buffered line.
+    #[default]
+    DropOldest,
+    /// Skip all lines buffered while falling behind and continue with the lines
+    /// pushed afterwards.
+    DropNewest,
+    /// Like [`DropOldest`](Self::DropOldest), but first send a `lagged` event
+    /// carrying the amount of skipped bytes.
+    Notify,
+    /// End the stream.
+    Disconnect,
+    /// Make [`LogBuffer::push_async`] wait until the tail sent the data it would drop.
+    ///
+    /// A client which stops reading stalls the producer. Lines appended with
+    /// [`push`](LogBuffer::push) or [`push_fmt`](LogBuffer::push_fmt) don't wait and
+    /// lines longer than the buffer can't be waited for, the tail then skips the
+    /// dropped lines like [`DropOldest`](Self::DropOldest). At most 4 tails block at
+    /// once, further ones behave like `DropOldest` as well.
+    Block,
+}
+
+/// Position of a tail with [`LagPolicy::Block`], released when dropped.
+struct Blocking<'l, const SIZE: usize> {
+    log: &'l LogBuffer<SIZE>,
+    slot: usize,
+}
+
+impl<'l, const SIZE: usize> Blocking<'l, SIZE> {
+    /// Marks the data before `pos` as sent.
+    fn advance(&self, pos: u64) {
+        let mut inner = self.log.inner.borrow_mut();
+        inner.blocking[self.slot] = Some(pos);
+        inner.producers.wake_all();
+    }
+}
+
+impl<'l, const SIZE: usize> Drop for Blocking<'l, SIZE> {
+    fn drop(&mut self) {
+        let mut inner = self.log.inner.borrow_mut();
+        inner.blocking[self.slot] = None;
+        inner.producers.wake_all();
+    }
+}
+
+/// Server sent events response body created by [`LogBuffer::tail`].
+pub struct LogTail<'l, const SIZE: usize> {
+    log: &'l LogBuffer<SIZE>,
+    on_lag: LagPolicy,
+}
+
+impl<'l, const SIZE: usize> LogTail<'l, SIZE> {
+    /// Sets the behavior when the client falls behind, see [`LagPolicy`].
+    pub fn on_lag(mut self, policy: LagPolicy) -> Self {
+        self.on_lag = policy;
+        self
+    }
+}
+
+impl<'l, const SIZE: usize> ResponseBody for LogTail<'l, SIZE> {
+    type Error = core::convert::Infallible;
+
+    async fn write_to<W: Write>(
+        self,
+        writer: &mut ResponseWriter<'_, W>,
+        buf: &mut [u8],
+    ) -> Result<(), WriteError<W::Error, Self::Error>> {
+        for (name, value) in [
+            ("Content-Type", "text/event-stream"),
+            ("Cache-Control", "no-cache"),
+        ] {
+            writer
+                .write_header(name, value)
+                .await
+                .map_err(WriteError::Io)?;
+        }
+
+        let mut pos = self.log.inner.borrow().start();
+        let blocking = match self.on_lag {
+            LagPolicy::Block => self.log.block(pos),
+            _ => None,
+        };
+        let mut line_start = true;
+        loop {
+            if let Some(blocking) = &blocking {
+                blocking.advance(pos);
+            }
+            let (start, len) = self.log.read_at(pos, u64::MAX, buf);
+            if len == 0 {
+                writer.flush().await.map_err(WriteError::Io)?;
+                self.log.changed(pos).await;
+                continue;
+            }
+            if start != pos {
+                if !line_start {
+                    // The rest of the current line was dropped, terminate its event.
+                    writer.write_all(b"\n\n").await.map_err(WriteError::Io)?;
+                    line_start = true;
+                }
+                match self.on_lag {
+                    LagPolicy::DropOldest | LagPolicy::Block => {}
+                    LagPolicy::DropNewest => {
+                        pos = self.log.inner.borrow().end;
+                        continue;
+                    }
+                    LagPolicy::Notify => {
+                        let mut event = heapless::String::<48>::new();
+                        // Cannot fail, the buffer fits the largest number.
+                        let _ = write!(event, "event: lagged\ndata: {}\n\n", start - pos);
+                        writer
+                            .write_all(event.as_bytes())
+                            .await
+                            .map_err(WriteError::Io)?;
+                    }
+                    LagPolicy::Disconnect => return Ok(()),
+                }
+            }
+            pos = start + len as u64;
+
+            for chunk in buf[..len].split_inclusive(|byte| *byte == b'\n') {
+                if line_start {
+                    writer.write_all(b"data: ").await.map_err(WriteError::Io)?;
+                }
+                line_start = chunk.ends_with(b"\n");
+                writer.write_all(chunk).await.map_err(WriteError::Io)?;
+                if line_start {
+                    writer.write_all(b"\n").await.map_err(WriteError::Io)?;
+                }
+            }
+        }
+    }
+}
+
+impl<'l, const SIZE: usize> IntoResponse for LogTail<'l, SIZE> {
+    type Body = Self;
+
+    fn into_response(self) -> Response<Self::Body> {
+        Response::new(self)
+    }
+}
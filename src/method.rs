@@ -2,7 +2,20 @@ use core::fmt;
 
 use self::Inner::*;
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+/// The request method.
+///
+/// Methods are ordered with the standard methods first, followed by extension
+/// methods ordered by name, so they can be kept in sorted collections.
+///
+/// ```
+/// use low_profile::Method;
+///
+/// let method = Method::new("PUT").unwrap();
+/// assert!(!method.is_safe());
+/// assert!(method.is_idempotent());
+/// assert!(Method::GET < Method::new("PROPFIND").unwrap());
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Method<'a>(Inner<'a>);
 
 impl<'a> Method<'a> {
@@ -56,9 +69,30 @@ impl<'a> Method<'a> {
             Extension(ext) => ext,
         }
     }
+
+    /// Whether the method is safe, i.e. only retrieves data without changing state
+    /// on the server: `GET`, `HEAD`, `OPTIONS` and `TRACE`.
+    ///
+    /// See [RFC 9110, Section 9.2.1](https://www.rfc-editor.org/rfc/rfc9110#section-9.2.1).
+    pub const fn is_safe(&self) -> bool {
+        matches!(self.0, Get | Head | Options | Trace)
+    }
+
+    /// Whether sending the request several times has the same effect as sending it
+    /// once, so it can be retried: the safe methods, `PUT` and `DELETE`.
+    ///
+    /// See [RFC 9110, Section 9.2.2](https://www.rfc-editor.org/rfc/rfc9110#section-9.2.2).
+    pub const fn is_idempotent(&self) -> bool {
+        self.is_safe() || matches!(self.0, Put | Delete)
+    }
+
+    /// Whether the method is not one of the methods defined in RFC 9110 or `PATCH`.
+    pub const fn is_extension(&self) -> bool {
+        matches!(self.0, Extension(_))
+    }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 enum Inner<'a> {
     Options,
     Get,
@@ -86,7 +120,7 @@ impl<'a, 'b> PartialEq<&'a Method<'b>> for Method<'b> {
     }
 }
 
-impl<'a, 'b> PartialEq<Method<'b>> for &'a Method<'b> {
+impl<'b> PartialEq<Method<'b>> for &Method<'b> {
     #[inline]
     fn eq(&self, other: &Method<'b>) -> bool {
         *self == other
@@ -156,6 +190,43 @@ impl<'a> TryFrom<&'a str> for Method<'a> {
     }
 }
 
+/// Set of standard methods, e.g. the methods allowed for a path.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct MethodSet(u16);
+
+impl MethodSet {
+    const METHODS: [Method<'static>; 9] = [
+        Method::GET,
+        Method::HEAD,
+        Method::POST,
+        Method::PUT,
+        Method::DELETE,
+        Method::CONNECT,
+        Method::OPTIONS,
+        Method::TRACE,
+        Method::PATCH,
+    ];
+
+    /// Adds `method`, extension methods are ignored.
+    pub(crate) fn insert(&mut self, method: Method<'_>) {
+        if let Some(i) = Self::METHODS.iter().position(|m| *m == method) {
+            self.0 |= 1 << i;
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = Method<'static>> + '_ {
+        Self::METHODS
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| self.0 & 1 << i != 0)
+            .map(|(_, method)| method)
+    }
+}
+
 /// A possible error value when converting `Method` from bytes.
 pub struct InvalidMethod(());
 
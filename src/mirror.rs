@@ -0,0 +1,221 @@
+//! Request mirroring for debugging.
+//!
+//! Wrapping a handler with [`mirror`] copies the requests it handles to a
+//! [`MirrorSink`] reachable from the router state, e.g. a [`RequestLog`] which can
+//! in turn be served from a debug route:
+//!
+//! ```
+//! use low_profile::{extract::State, mirror::{self, RequestLog}, Router};
+//!
+//! struct App {
+//!     log: RequestLog<4, 256>,
+//! }
+//!
+//! async fn last_requests(State(app): State<&App>) -> heapless::Vec<u8, 1024> {
+//!     app.log.render()
+//! }
+//!
+//! let app = App { log: RequestLog::new() };
+//!
+//! let router = Router::new()
+//!     .post("/config", mirror::mirror(|app: &&App| &app.log, || async { "ok" }).with_body())
+//!     .get("/debug/last-requests", last_requests)
+//!     .with_state(&app);
+//! ```
+
+use core::cell::RefCell;
+
+use heapless::{Deque, Vec};
+
+use crate::{handler::HandlerFunction, request::Parts, ErrorType, Read, Request};
+
+/// Receives copies of mirrored requests.
+pub trait MirrorSink {
+    /// Called with the head of every mirrored request, before it is handled.
+    fn request(&self, parts: &Parts<'_>);
+
+    /// Called with body data of the current request as the handler reads it.
+    ///
+    /// Only invoked for handlers mirrored [`with_body`](Mirror::with_body).
+    fn body(&self, _data: &[u8]) {}
+}
+
+/// Handler wrapper created by [`mirror`].
+pub struct Mirror<S, M, H> {
+    sink: fn(&S) -> &M,
+    handler: H,
+    body: bool,
+}
+
+/// Copies the head of every request handled by `handler` to the sink returned by `sink`.
+pub fn mirror<S, M, H>(sink: fn(&S) -> &M, handler: H) -> Mirror<S, M, H> {
+    Mirror {
+        sink,
+        handler,
+        body: false,
+    }
+}
+
+impl<S, M, H> Mirror<S, M, H> {
+    /// Additionally copies the request body.
+    ///
+    /// The body is mirrored while the handler reads it, parts the handler does not
+    /// read are not mirrored.
+    pub fn with_body(mut self) -> Self {
+        self.body = true;
+        self
+    }
+}
+
+impl<S, M, H, Params> HandlerFunction<S, Params> for Mirror<S, M, H>
+where
+    M: MirrorSink,
+    H: HandlerFunction<S, Params>,
+{
+    type Response = H::Response;
+
+    async fn call<R: Read>(&self, req: Request<'_, R>, state: &S) -> Self::Response {
+        let sink = (self.sink)(state);
+        sink.request(&req.parts);
+
+        if !self.body {
+            return self.handler.call(req, state).await;
+        }
+
+        let (parts, body) = req.into_parts();
+        let body = body.wrap(|reader| Tee { sink, reader });
+        self.handler
+            .call(Request::from_parts(parts, body), state)
+            .await
+    }
+}
+
+/// Reader passing everything read to a [`MirrorSink`].
+struct Tee<'s, M, R> {
+    sink: &'s M,
+    reader: R,
+}
+
+impl<'s, M, R: ErrorType> ErrorType for Tee<'s, M, R> {
+    type Error = R::Error;
+}
+
+impl<'s, M: MirrorSink, R: Read> Read for Tee<'s, M, R> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let len = self.reader.read(buf).await?;
+        self.sink.body(&buf[..len]);
+        Ok(len)
+    }
+}
+
+/// Ring buffer keeping the last `N` mirrored requests, each truncated to `LEN` bytes.
+///
+/// Requests are recorded in a HTTP like text form, the request line without version
+/// followed by the headers, an empty line and the body.
+///
+/// The values of headers carrying credentials, `Authorization`, `Proxy-Authorization`
+/// and `Cookie`, are replaced by `[redacted]` unless
+/// [`keep_credentials`](Self::keep_credentials) is set.
+pub struct RequestLog<const N: usize, const LEN: usize> {
+    entries: RefCell<Deque<Vec<u8, LEN>, N>>,
+    redact: bool,
+}
+
+impl<const N: usize, const LEN: usize> RequestLog<N, LEN> {
+    pub const fn new() -> Self {
+        Self {
+            entries: RefCell::new(Deque::new()),
+            redact: true,
+        }
+    }
+
+    /// Records credential headers verbatim instead of redacting them.
+    ///
+    /// Anyone who can read the log can then impersonate the logged clients.
+    pub const fn keep_credentials(mut self) -> Self {
+        self.redact = false;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.borrow().is_empty()
+    }
+
+    pub fn clear(&self) {
+        self.entries.borrow_mut().clear();
+    }
+
+    /// Calls `f` with every recorded request, oldest first.
+    pub fn for_each(&self, mut f: impl FnMut(&[u8])) {
+        self.entries.borrow().iter().for_each(|entry| f(entry));
+    }
+
+    /// Concatenates all recorded requests, oldest first, truncated to `SIZE` bytes.
+    pub fn render<const SIZE: usize>(&self) -> Vec<u8, SIZE> {
+        let mut out = Vec::new();
+        self.for_each(|entry| {
+            extend_truncated(&mut out, entry);
+            extend_truncated(&mut out, b"\r\n\r\n");
+        });
+        out
+    }
+}
+
+impl<const N: usize, const LEN: usize> Default for RequestLog<N, LEN> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, const LEN: usize> MirrorSink for RequestLog<N, LEN> {
+    fn request(&self, parts: &Parts<'_>) {
+        let mut entry = Vec::new();
+        extend_truncated(&mut entry, parts.method.as_str().as_bytes());
+        extend_truncated(&mut entry, b" ");
+        extend_truncated(&mut entry, parts.path.as_bytes());
+        if let Some(query) = parts.query {
+            extend_truncated(&mut entry, b"?");
+            extend_truncated(&mut entry, query.as_bytes());
+        }
+        for (name, value) in parts.headers.iter() {
+            extend_truncated(&mut entry, b"\r\n");
+            extend_truncated(&mut entry, name.as_bytes());
+            extend_truncated(&mut entry, b": ");
+            if self.redact && is_credential(name) {
+                extend_truncated(&mut entry, b"[redacted]");
+            } else {
+                extend_truncated(&mut entry, value.as_bytes());
+            }
+        }
+        extend_truncated(&mut entry, b"\r\n\r\n");
+
+        let mut entries = self.entries.borrow_mut();
+        if entries.is_full() {
+            entries.pop_front();
+        }
+        // Cannot fail, there is room for at least one entry now.
+        let _ = entries.push_back(entry);
+    }
+
+    fn body(&self, data: &[u8]) {
+        if let Some(entry) = self.entries.borrow_mut().back_mut() {
+            extend_truncated(entry, data);
+        }
+    }
+}
+
+fn is_credential(name: &str) -> bool {
+    ["Authorization", "Proxy-Authorization", "Cookie"]
+        .iter()
+        .any(|credential| name.eq_ignore_ascii_case(credential))
+}
+
+fn extend_truncated<const LEN: usize>(vec: &mut Vec<u8, LEN>, data: &[u8]) {
+    let len = data.len().min(LEN - vec.len());
+    // Cannot fail, the data was truncated to the remaining capacity.
+    let _ = vec.extend_from_slice(&data[..len]);
+}
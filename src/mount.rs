@@ -0,0 +1,361 @@
+//! Serving another [`Service`] for the requests below a path, e.g. a WebDAV or
+//! gRPC-web implementation next to a [`Router`](crate::Router) on one listener.
+//!
+//! A [`Mount`] reads the head of the first request of a connection and hands the
+//! connection either to the mounted service or to the fallback, the bytes read so
+//! far are passed on to it first. The chosen service only receives this request, the
+//! connection is closed after its response, which is sent with `Connection: close`.
+//! Requests pipelined by the client are not served, a client reusing connections
+//! reconnects and its next request is handed to the right service again.
+//!
+//! ```
+//! use low_profile::{mount::Mount, Router};
+//!
+//! let dav = Router::<(), _>::new().get("/dav/*path", || async { "file" });
+//! let app = Router::<(), _>::new().get("/", || async { "index" });
+//!
+//! let service = Mount::new("/dav", dav, app);
+//! ```
+
+use crate::{
+    either::Either,
+    http::StatusCode,
+    router::write_response,
+    service::{ServiceConfig, ServiceError},
+    utils, ErrorType, IntoResponse, Read, Service, Write,
+};
+
+/// Service handing connections whose first request is for `prefix` or a path below
+/// it to the mounted service, and all other connections to the fallback.
+///
+/// The path is passed on unchanged, including `prefix`. Request heads longer than
+/// `HEAD` bytes are answered with `431 Request Header Fields Too Large`.
+///
+/// Each connection serves a single request, see the [module documentation](self).
+#[derive(Debug, Clone, Copy)]
+pub struct Mount<M, F, const HEAD: usize = 1024> {
+    prefix: &'static str,
+    service: M,
+    fallback: F,
+}
+
+impl<M, F> Mount<M, F> {
+    pub const fn new(prefix: &'static str, service: M, fallback: F) -> Self {
+        Self {
+            prefix,
+            service,
+            fallback,
+        }
+    }
+}
+
+impl<M, F, const HEAD: usize> Mount<M, F, HEAD> {
+    /// Changes the maximum length of the request head.
+    pub fn buffers<const L: usize>(self) -> Mount<M, F, L> {
+        Mount {
+            prefix: self.prefix,
+            service: self.service,
+            fallback: self.fallback,
+        }
+    }
+
+    /// Returns `true` if the request line `line` targets the mounted path.
+    fn matches(&self, line: &[u8]) -> bool {
+        let Ok(line) = core::str::from_utf8(line) else {
+            return false;
+        };
+        let Some(target) = line.split(' ').nth(1) else {
+            return false;
+        };
+        let path = target.split(['?', '#']).next().unwrap_or_default();
+        let prefix = self.prefix.trim_end_matches('/');
+        path.strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    }
+}
+
+impl<M: Service, F: Service, const HEAD: usize> Service for Mount<M, F, HEAD> {
+    type BodyError = Either<M::BodyError, F::BodyError>;
+
+    async fn serve<Re: Read, Wr: Write<Error = Re::Error>>(
+        &self,
+        mut reader: Re,
+        writer: Wr,
+    ) -> Result<(), ServiceError<Re::Error, Self::BodyError>> {
+        let mut buf = [0u8; HEAD];
+        let mut pos = 0;
+        let head = loop {
+            if let Some(head) = find_head(&buf[..pos]) {
+                break head;
+            }
+            if pos == HEAD {
+                let response = (
+                    StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE,
+                    "Request Header Fields Too Large",
+                )
+                    .into_response();
+                return write_response::<_, _, 0>(writer, response, false, &ServiceConfig::new())
+                    .await
+                    .map(|_| ())
+                    .map_err(|err| map_body_error(err, |err| match err {}));
+            }
+
+            let read = reader
+                .read(&mut buf[pos..])
+                .await
+                .map_err(ServiceError::Io)?;
+            if read == 0 {
+                return Ok(());
+            }
+            pos += read;
+        };
+
+        let mounted = self.matches(&buf[head.line.clone()]);
+        let reader = Replay {
+            buf: &buf[..pos],
+            reader,
+            remaining: head.len.saturating_add(head.content_length),
+            chunks: head.chunked.then_some(ChunkScan::Size(0)),
+        };
+        let writer = Closing {
+            writer,
+            state: HeadState::Line,
+            line: heapless::Vec::new(),
+        };
+        if mounted {
+            self.service
+                .serve(reader, writer)
+                .await
+                .map_err(|err| map_body_error(err, Either::Left))
+        } else {
+            self.fallback
+                .serve(reader, writer)
+                .await
+                .map_err(|err| map_body_error(err, Either::Right))
+        }
+    }
+}
+
+/// Request head found in the received bytes.
+struct Head {
+    /// Position of the request line, without the line break.
+    line: core::ops::Range<usize>,
+    /// Length of the head including the empty line ending it.
+    len: usize,
+    content_length: usize,
+    chunked: bool,
+}
+
+/// Returns the head of the request starting in `buf` if it is received completely.
+///
+/// Only the framing of the body is taken from the headers, invalid or ambiguous
+/// framing is left to the service to reject.
+fn find_head(buf: &[u8]) -> Option<Head> {
+    // Empty lines may precede the request line.
+    let start = buf.iter().position(|&c| c != b'\r' && c != b'\n')?;
+    let mut head = Head {
+        line: start..start,
+        len: 0,
+        content_length: 0,
+        chunked: false,
+    };
+    let mut line_start = start;
+    for (i, &c) in buf.iter().enumerate().skip(start) {
+        if c != b'\n' {
+            continue;
+        }
+        let line = buf[line_start..i]
+            .strip_suffix(b"\r")
+            .unwrap_or(&buf[line_start..i]);
+        if line_start == start {
+            head.line = line_start..line_start + line.len();
+        } else if line.is_empty() {
+            head.len = i + 1;
+            return Some(head);
+        } else if let Some((name, value)) = core::str::from_utf8(line)
+            .ok()
+            .and_then(|line| line.split_once(':'))
+        {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                head.content_length = value.trim().parse().unwrap_or(0);
+            } else if name.eq_ignore_ascii_case("Transfer-Encoding") {
+                head.chunked = utils::has_token(value, "chunked");
+            }
+        }
+        line_start = i + 1;
+    }
+    None
+}
+
+fn map_body_error<IO, B, E>(
+    err: ServiceError<IO, B>,
+    map: impl FnOnce(B) -> E,
+) -> ServiceError<IO, E> {
+    match err {
+        ServiceError::ProtocolError(err) => ServiceError::ProtocolError(err),
+        ServiceError::Io(err) => ServiceError::Io(err),
+        ServiceError::Body(err) => ServiceError::Body(map(err)),
+    }
+}
+
+/// Reader returning the bytes of `buf` before reading from `reader`, ending after
+/// the first request.
+struct Replay<'b, R> {
+    buf: &'b [u8],
+    reader: R,
+    /// Bytes of the head and body left, chunked bodies end as told by `chunks`.
+    remaining: usize,
+    chunks: Option<ChunkScan>,
+}
+
+/// Position within a chunked body, only tracked to find its end.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ChunkScan {
+    /// Reading the size line of a chunk, with the digits read so far.
+    Size(usize),
+    Data(usize),
+    /// Expecting the line break after the chunk data.
+    DataEnd,
+    /// Reading the trailer, `true` at the start of a line.
+    Trailer(bool),
+    Done,
+}
+
+impl ChunkScan {
+    /// Scans the body bytes in `buf`, returns how many of them belong to the body.
+    fn scan(&mut self, buf: &[u8]) -> usize {
+        let mut i = 0;
+        while i < buf.len() {
+            let c = buf[i];
+            *self = match *self {
+                Self::Size(size) => match c {
+                    b'\n' if size == 0 => Self::Trailer(true),
+                    b'\n' => Self::Data(size),
+                    c => match (c as char).to_digit(16) {
+                        // Saturates instead of overflowing, the service rejects the size.
+                        Some(digit) => Self::Size(size.saturating_mul(16) | digit as usize),
+                        // Chunk extensions.
+                        None => Self::Size(size),
+                    },
+                },
+                Self::Data(size) => {
+                    let len = size.min(buf.len() - i);
+                    i += len;
+                    *self = match size - len {
+                        0 => Self::DataEnd,
+                        size => Self::Data(size),
+                    };
+                    continue;
+                }
+                Self::DataEnd => match c {
+                    b'\n' => Self::Size(0),
+                    _ => Self::DataEnd,
+                },
+                Self::Trailer(start) => match c {
+                    b'\n' if start => Self::Done,
+                    b'\n' => Self::Trailer(true),
+                    b'\r' => Self::Trailer(start),
+                    _ => Self::Trailer(false),
+                },
+                Self::Done => return i,
+            };
+            i += 1;
+        }
+        i
+    }
+}
+
+impl<'b, R: Read> ErrorType for Replay<'b, R> {
+    type Error = R::Error;
+}
+
+impl<'b, R: Read> Read for Replay<'b, R> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let len = match self.remaining {
+            0 if self.chunks.is_some_and(|chunks| chunks != ChunkScan::Done) => buf.len(),
+            remaining => buf.len().min(remaining),
+        };
+        if len == 0 {
+            return Ok(0);
+        }
+        let read = if self.buf.is_empty() {
+            self.reader.read(&mut buf[..len]).await?
+        } else {
+            let read = len.min(self.buf.len());
+            buf[..read].copy_from_slice(&self.buf[..read]);
+            self.buf = &self.buf[read..];
+            read
+        };
+        let head = read.min(self.remaining);
+        self.remaining -= head;
+        Ok(match &mut self.chunks {
+            Some(chunks) if self.remaining == 0 => head + chunks.scan(&buf[head..read]),
+            _ => read,
+        })
+    }
+}
+
+/// Progress of [`Closing`] through the response head.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum HeadState {
+    /// Within the status line.
+    Line,
+    /// At the start of a header line.
+    Start,
+    /// Within a header line.
+    Header,
+    /// The head is complete, body bytes follow.
+    Body,
+}
+
+/// Writer adding `Connection: close` to the head of the response, unless sent already.
+struct Closing<W> {
+    writer: W,
+    state: HeadState,
+    /// Start of the current header line, to recognize a `Connection: close` sent.
+    line: heapless::Vec<u8, 18>,
+}
+
+impl<W: Write> ErrorType for Closing<W> {
+    type Error = W::Error;
+}
+
+impl<W: Write> Write for Closing<W> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        if self.state == HeadState::Body {
+            return self.writer.write(buf).await;
+        }
+        for (i, &c) in buf.iter().enumerate() {
+            match (self.state, c) {
+                (HeadState::Start, b'\r' | b'\n') => {
+                    self.writer.write_all(&buf[..i]).await?;
+                    self.writer.write_all(b"Connection: close\r\n").await?;
+                    self.state = HeadState::Body;
+                    self.writer.write_all(&buf[i..]).await?;
+                    return Ok(buf.len());
+                }
+                (_, b'\n') => {
+                    if self.line.eq_ignore_ascii_case(b"Connection: close") {
+                        // Already sent, e.g. because the service closes the connection.
+                        self.writer.write_all(buf).await?;
+                        self.state = HeadState::Body;
+                        return Ok(buf.len());
+                    }
+                    self.line.clear();
+                    self.state = HeadState::Start;
+                }
+                (HeadState::Line, _) | (_, b'\r') => {}
+                (_, c) => {
+                    let _ = self.line.push(c);
+                    self.state = HeadState::Header;
+                }
+            }
+        }
+        self.writer.write_all(buf).await?;
+        Ok(buf.len())
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.writer.flush().await
+    }
+}
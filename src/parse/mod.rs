@@ -1,3 +1,3 @@
 mod path;
 
-pub use path::PathAndQuery;
+pub use path::{Decoded, PathAndQuery};
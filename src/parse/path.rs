@@ -64,6 +64,63 @@ impl<'a> PathAndQuery<'a> {
         })
     }
 
+    /// Validates the request target in `buf` like [`parse`](Self::parse) and
+    /// percent-decodes its path in place, moving the query to follow the decoded path.
+    ///
+    /// The decoded path may contain any character, e.g. a `?` decoded from `%3F` or
+    /// a `/` decoded from `%2F`, the query is located by the returned [`Decoded`].
+    /// Escapes without two hex digits, decoding to control characters or to invalid
+    /// UTF-8 are rejected. Control characters are never sent unescaped, a `%00` could
+    /// end the path early for handlers passing it on as a NUL-terminated string.
+    pub fn decode(buf: &mut [u8]) -> Result<Decoded, InvalidUrl> {
+        let target = core::str::from_utf8(buf).map_err(|_| InvalidUrl::InvalidUrlCodePoint)?;
+        let (data_len, query) =
+            PathAndQuery::parse(target).map(|paq| (paq.data.len(), paq.query))?;
+        let path_len = match query {
+            NONE => data_len,
+            query => query as usize,
+        };
+
+        let mut len = 0;
+        let mut i = 0;
+        while i < path_len {
+            let byte = match buf[i] {
+                b'%' => {
+                    let digit = |c: Option<&u8>| c.and_then(|&c| (c as char).to_digit(16));
+                    match (
+                        digit(buf[..path_len].get(i + 1)),
+                        digit(buf[..path_len].get(i + 2)),
+                    ) {
+                        (Some(high), Some(low)) => {
+                            i += 2;
+                            (high << 4 | low) as u8
+                        }
+                        _ => return Err(InvalidUrl::InvalidUrlCodePoint),
+                    }
+                }
+                byte => byte,
+            };
+            if byte.is_ascii_control() {
+                return Err(InvalidUrl::InvalidUrlCodePoint);
+            }
+            buf[len] = byte;
+            len += 1;
+            i += 1;
+        }
+        core::str::from_utf8(&buf[..len]).map_err(|_| InvalidUrl::InvalidUrlCodePoint)?;
+
+        let query = match query {
+            NONE => NONE,
+            _ => len as u16,
+        };
+        buf.copy_within(path_len..data_len, len);
+
+        Ok(Decoded {
+            len: (len + data_len - path_len) as u16,
+            query,
+        })
+    }
+
     pub fn path(&self) -> &'a str {
         let ret = if self.query == NONE {
             self.data
@@ -86,3 +143,21 @@ impl<'a> PathAndQuery<'a> {
         }
     }
 }
+
+/// Location of the parts of a target decoded by [`PathAndQuery::decode`].
+#[derive(Debug, Clone, Copy)]
+pub struct Decoded {
+    len: u16,
+    query: u16,
+}
+
+impl Decoded {
+    /// The decoded target, `buf` has to be the buffer passed to [`PathAndQuery::decode`].
+    pub fn get<'a>(&self, buf: &'a [u8]) -> PathAndQuery<'a> {
+        PathAndQuery {
+            // SAFETY: `decode` validated the decoded path as UTF-8 and the query as ASCII.
+            data: unsafe { core::str::from_utf8_unchecked(&buf[..self.len as usize]) },
+            query: self.query,
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! A static pool of connection buffers, see [`Router::serve_pooled`](crate::Router::serve_pooled).
+//!
+//! Serving a connection with [`Service::serve`](crate::Service::serve) keeps the
+//! request and relay [`Buffers`](crate::Buffers) in the serve future, usually on the
+//! stack of the task serving the connection. Serving it with a [`BufferPool`]
+//! instead draws them from a static pool of `COUNT` buffers, sized for the most
+//! connections served at once. Connections arriving while all buffers are in use are
+//! answered with `503 Service Unavailable`.
+//!
+//! ```
+//! use low_profile::{pool::BufferPool, Router};
+//!
+//! // Buffers for 4 connections, each with 1024 bytes for the request and the relay
+//! // buffer of the default `Buffers`.
+//! static POOL: BufferPool<4, 2048> = BufferPool::new();
+//!
+//! let router = Router::<(), _>::new().get("/", || async { "index" });
+//!
+//! # struct Sink;
+//! # impl low_profile::ErrorType for Sink {
+//! #     type Error = core::convert::Infallible;
+//! # }
+//! # impl low_profile::Write for Sink {
+//! #     async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+//! #         Ok(buf.len())
+//! #     }
+//! # }
+//! # let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+//! # let (reader, writer) = (&b"GET / HTTP/1.1\r\n\r\n"[..], Sink);
+//! # runtime.block_on(async {
+//! // For every accepted connection:
+//! router.serve_pooled(reader, writer, &POOL).await.unwrap();
+//! # });
+//! assert_eq!(POOL.in_use(), 0);
+//! ```
+
+use core::{
+    cell::UnsafeCell,
+    ops::{Deref, DerefMut},
+    sync::atomic::{AtomicU32, AtomicUsize, Ordering},
+};
+
+/// `COUNT` buffers of `SIZE` bytes, handed out one at a time.
+///
+/// At most 32 buffers are supported. Taking and returning buffers uses atomic
+/// compare-and-swap operations.
+pub struct BufferPool<const COUNT: usize, const SIZE: usize> {
+    buffers: UnsafeCell<[[u8; SIZE]; COUNT]>,
+    /// Bit set of the buffers in use.
+    used: AtomicU32,
+    /// Most buffers in use at once.
+    peak: AtomicUsize,
+    /// Times a buffer was requested while all were in use.
+    exhausted: AtomicUsize,
+}
+
+// SAFETY: Every buffer is only borrowed by the single `PoolBuffer` which claimed its
+// bit in `used`.
+unsafe impl<const COUNT: usize, const SIZE: usize> Sync for BufferPool<COUNT, SIZE> {}
+
+impl<const COUNT: usize, const SIZE: usize> BufferPool<COUNT, SIZE> {
+    /// Creates the pool.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `COUNT` is larger than 32, at compile time in a `static`.
+    pub const fn new() -> Self {
+        assert!(COUNT <= 32, "buffer pools hold at most 32 buffers");
+        Self {
+            buffers: UnsafeCell::new([[0; SIZE]; COUNT]),
+            used: AtomicU32::new(0),
+            peak: AtomicUsize::new(0),
+            exhausted: AtomicUsize::new(0),
+        }
+    }
+
+    /// Takes a free buffer, `None` if all buffers are in use.
+    ///
+    /// The buffer is returned to the pool when dropped, it still contains the data
+    /// written by its former user.
+    pub fn take(&self) -> Option<PoolBuffer<'_>> {
+        let mut used = self.used.load(Ordering::Acquire);
+        let slot = loop {
+            let slot = used.trailing_ones() as usize;
+            if slot >= COUNT {
+                self.exhausted.fetch_add(1, Ordering::Relaxed);
+                return None;
+            }
+            match self.used.compare_exchange_weak(
+                used,
+                used | 1 << slot,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break slot,
+                Err(current) => used = current,
+            }
+        };
+        let in_use = (used | 1 << slot).count_ones() as usize;
+        self.peak.fetch_max(in_use, Ordering::Relaxed);
+
+        // SAFETY: The bit of `slot` was claimed above, no other `PoolBuffer` borrows
+        // this buffer until it is released again.
+        let buf = unsafe { &mut (*self.buffers.get())[slot] };
+        Some(PoolBuffer {
+            buf,
+            used: &self.used,
+            mask: 1 << slot,
+        })
+    }
+
+    /// Amount of buffers in the pool.
+    pub const fn capacity(&self) -> usize {
+        COUNT
+    }
+
+    /// Amount of buffers currently in use.
+    pub fn in_use(&self) -> usize {
+        self.used.load(Ordering::Relaxed).count_ones() as usize
+    }
+
+    /// Most buffers in use at once since the pool was created.
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::Relaxed)
+    }
+
+    /// Times a buffer was requested while all buffers were in use.
+    pub fn exhausted(&self) -> usize {
+        self.exhausted.load(Ordering::Relaxed)
+    }
+}
+
+impl<const COUNT: usize, const SIZE: usize> Default for BufferPool<COUNT, SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A buffer taken from a [`BufferPool`], returned to it when dropped.
+pub struct PoolBuffer<'p> {
+    buf: &'p mut [u8],
+    used: &'p AtomicU32,
+    mask: u32,
+}
+
+impl<'p> Deref for PoolBuffer<'p> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.buf
+    }
+}
+
+impl<'p> DerefMut for PoolBuffer<'p> {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        self.buf
+    }
+}
+
+impl<'p> Drop for PoolBuffer<'p> {
+    fn drop(&mut self) {
+        self.used.fetch_and(!self.mask, Ordering::Release);
+    }
+}
@@ -0,0 +1,182 @@
+//! Partial responses to `Range` requests, see [`Router::accept_ranges`](crate::Router::accept_ranges).
+//!
+//! Clients downloading large files, e.g. firmware images, resume interrupted transfers
+//! by requesting the missing bytes only:
+//!
+//! ```
+//! use low_profile::{Response, Router};
+//!
+//! static FIRMWARE: &[u8] = &[0; 4096];
+//!
+//! async fn firmware() -> Response<&'static [u8]> {
+//!     Response::new(FIRMWARE).with_content_type("application/octet-stream")
+//! }
+//!
+//! let router = Router::<(), _>::new()
+//!     .get("/firmware.bin", firmware)
+//!     .etag()
+//!     .accept_ranges();
+//! ```
+//!
+//! Requesting `Range: bytes=1024-` responds with `206 Partial Content`, the last 3072
+//! bytes and `Content-Range: bytes 1024-4095/4096`.
+
+use core::{fmt::Write as _, ops::Range};
+
+use crate::{
+    conditional::format_tag,
+    http::{byte_range, ETag, StatusCode},
+    response::{ResponseBody, ResponseWriter, WriteError},
+    route::{Decision, Route},
+    utils, IntoResponse, Method, Read, Request, Response, Write,
+};
+
+pub(crate) struct RangeRoute<R> {
+    pub(crate) route: R,
+}
+
+impl<S, R: Route<S>> Route<S> for RangeRoute<R> {
+    type Response = Response<Ranged<<R::Response as IntoResponse>::Body>>;
+
+    async fn match_request<'a, Body: Read>(
+        &'a self,
+        req: Request<'a, Body>,
+        state: &'a S,
+    ) -> Decision<'a, Self::Response, Body> {
+        let method = req.parts.method;
+        let range = req.parts.headers.get_first("Range");
+        let if_range = req.parts.headers.get_first("If-Range");
+
+        self.route.match_request(req, state).await.map(|response| {
+            let response = response.into_response();
+            let len = match response.body().content_length() {
+                Some(len) if response.status_code() == StatusCode::OK => len,
+                _ => return response.map_body(|inner| Ranged::new(inner, Part::Unranged)),
+            };
+            let range = match range {
+                Some(range) if method == Method::GET && unchanged(response.body(), if_range) => {
+                    byte_range(range, len)
+                }
+                _ => Ok(None),
+            };
+
+            match range {
+                Ok(None) => response.map_body(|inner| Ranged::new(inner, Part::Complete)),
+                Ok(Some(range)) => {
+                    let response =
+                        response.map_body(|inner| Ranged::new(inner, Part::Partial(range)));
+                    (StatusCode::PARTIAL_CONTENT, response).into_response()
+                }
+                Err(_) => {
+                    let response =
+                        response.map_body(|inner| Ranged::new(inner, Part::Unsatisfiable));
+                    (StatusCode::RANGE_NOT_SATISFIABLE, response).into_response()
+                }
+            }
+        })
+    }
+}
+
+/// Evaluates an `If-Range` header, returns `true` if the range may be sent.
+///
+/// Only strong entity tags derived from the body as by [`Router::etag`](crate::Router::etag)
+/// are recognized, for dates and other tags the whole body is sent.
+fn unchanged<B: ResponseBody>(body: &B, if_range: Option<&str>) -> bool {
+    let Some(if_range) = if_range else {
+        return true;
+    };
+    match (ETag::parse(if_range), body.as_bytes()) {
+        (Ok(etag), Some(bytes)) => etag.strong_eq(&ETag::strong(&format_tag(utils::fnv1a(bytes)))),
+        _ => false,
+    }
+}
+
+enum Part {
+    /// Not a complete representation of known length, sent as is.
+    Unranged,
+    /// Sent completely, advertising support for ranges.
+    Complete,
+    Partial(Range<usize>),
+    Unsatisfiable,
+}
+
+/// A body of which only the requested range is sent.
+///
+/// The whole body is still produced, bytes outside the range are discarded.
+pub struct Ranged<B> {
+    inner: B,
+    part: Part,
+}
+
+impl<B> Ranged<B> {
+    fn new(inner: B, part: Part) -> Self {
+        Self { inner, part }
+    }
+}
+
+impl<B: ResponseBody> ResponseBody for Ranged<B> {
+    type Error = B::Error;
+
+    fn content_length(&self) -> Option<usize> {
+        match &self.part {
+            Part::Unranged | Part::Complete => self.inner.content_length(),
+            Part::Partial(range) => Some(range.len()),
+            Part::Unsatisfiable => Some(0),
+        }
+    }
+
+    fn as_bytes(&self) -> Option<&[u8]> {
+        match &self.part {
+            Part::Unranged | Part::Complete => self.inner.as_bytes(),
+            Part::Partial(range) => self.inner.as_bytes()?.get(range.clone()),
+            Part::Unsatisfiable => Some(&[]),
+        }
+    }
+
+    fn forward_to(&self) -> Option<&'static str> {
+        self.inner.forward_to()
+    }
+
+    async fn write_to<W: Write>(
+        self,
+        writer: &mut ResponseWriter<'_, W>,
+        buf: &mut [u8],
+    ) -> Result<(), WriteError<W::Error, Self::Error>> {
+        if let Part::Unranged = self.part {
+            return self.inner.write_to(writer, buf).await;
+        }
+        let len = self.inner.content_length().unwrap_or_default();
+        let mut content_range = heapless::String::<68>::new();
+        // Can't overflow, `bytes ` and three `usize` of at most 20 digits each
+        // with their separators are 68 bytes.
+        let _ = match &self.part {
+            Part::Partial(range) => write!(
+                content_range,
+                "bytes {}-{}/{len}",
+                range.start,
+                range.end - 1
+            ),
+            Part::Unsatisfiable => write!(content_range, "bytes */{len}"),
+            Part::Unranged | Part::Complete => Ok(()),
+        };
+
+        writer
+            .write_header("Accept-Ranges", "bytes")
+            .await
+            .map_err(WriteError::Io)?;
+        if !content_range.is_empty() {
+            writer
+                .write_header("Content-Range", &content_range)
+                .await
+                .map_err(WriteError::Io)?;
+        }
+        match self.part {
+            Part::Unsatisfiable => Ok(()),
+            Part::Partial(range) => {
+                writer.set_range(range);
+                self.inner.write_to(writer, buf).await
+            }
+            Part::Unranged | Part::Complete => self.inner.write_to(writer, buf).await,
+        }
+    }
+}
@@ -1,15 +1,25 @@
 use core::{fmt, mem::MaybeUninit, str::Utf8Error};
 
-use crate::{ErrorType, Method, Read};
+use crate::{arena::Arena, extract::UrlEncoded, method::MethodSet, ErrorType, Method, Read};
 
 pub struct Request<'a, R> {
     pub(crate) parts: Parts<'a>,
     pub(crate) body: Body<'a, R>,
+    /// Whether the `404 Not Found` fallback passes the request on instead of answering
+    /// it, set while the first of two merged routers is matched.
+    pub(crate) fall_through: bool,
+    /// Methods of the routes matching the path but not the method of the request.
+    pub(crate) allowed: MethodSet,
 }
 
 impl<'a, R> Request<'a, R> {
     pub fn from_parts(parts: Parts<'a>, body: Body<'a, R>) -> Self {
-        Self { parts, body }
+        Self {
+            parts,
+            body,
+            fall_through: false,
+            allowed: MethodSet::default(),
+        }
     }
 
     pub fn into_parts(self) -> (Parts<'a>, Body<'a, R>) {
@@ -24,6 +34,14 @@ impl<'a, R> Request<'a, R> {
         self.parts.path
     }
 
+    pub fn parts(&self) -> &Parts<'a> {
+        &self.parts
+    }
+
+    pub fn parts_mut(&mut self) -> &mut Parts<'a> {
+        &mut self.parts
+    }
+
     pub fn body(&self) -> &Body<'a, R> {
         &self.body
     }
@@ -48,9 +66,82 @@ pub struct Parts<'a> {
     pub path: &'a str,
     pub query: Option<&'a str>,
     pub headers: Headers<'a>,
+    /// Request scoped arena, allocating from the part of the request buffer not filled
+    /// by the request, see [`Buffers`](crate::Buffers), or the buffer passed to
+    /// [`Router::serve_with_arena`](crate::Router::serve_with_arena).
+    pub arena: Option<&'a Arena<'a>>,
+    /// Path segments captured by the matched route, see [`Path`](crate::extract::Path).
+    pub params: Params<'a>,
+}
+
+impl<'a> Parts<'a> {
+    /// Creates the parts of a request without query, headers or arena.
+    pub fn new(method: Method<'a>, path: &'a str) -> Self {
+        Self {
+            method,
+            path,
+            query: None,
+            headers: Headers::default(),
+            arena: None,
+            params: Params::default(),
+        }
+    }
+
+    /// Iterates over the raw keys and values of the query string in the order sent,
+    /// including repeated keys.
+    ///
+    /// See [`UrlEncoded`] to parse decoded values, or [`Query`](crate::extract::Query)
+    /// to extract them into a type.
+    ///
+    /// ```
+    /// use low_profile::{Method, Parts};
+    ///
+    /// let mut parts = Parts::new(Method::new("GET").unwrap(), "/leds");
+    /// parts.query = Some("led=1&led=3&on");
+    /// let pairs: Vec<_> = parts.query_pairs().collect();
+    /// assert_eq!(pairs, [("led", "1"), ("led", "3"), ("on", "")]);
+    /// ```
+    pub fn query_pairs(&self) -> impl Iterator<Item = (&'a str, &'a str)> + 'a {
+        UrlEncoded::new(self.query.unwrap_or_default()).pairs()
+    }
+}
+
+/// Maximum amount of segments a route path can capture.
+pub(crate) const MAX_PARAMS: usize = 4;
+
+/// Named path segments captured by a route, e.g. `id` of `/leds/:id`.
+#[derive(Debug, Clone, Default)]
+pub struct Params<'a> {
+    params: heapless::Vec<(&'static str, &'a str), MAX_PARAMS>,
+}
+
+impl<'a> Params<'a> {
+    /// Returns the segment captured as `name`.
+    pub fn get(&self, name: &str) -> Option<&'a str> {
+        self.iter()
+            .find_map(|(param, value)| (param == name).then_some(value))
+    }
+
+    /// Iterates over the names and values in the order of the route path.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &'a str)> + '_ {
+        self.params.iter().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.params.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
+    /// Adds a captured segment, fails if [`MAX_PARAMS`] segments are captured.
+    pub(crate) fn push(&mut self, name: &'static str, value: &'a str) -> Result<(), ()> {
+        self.params.push((name, value)).map_err(|_| ())
+    }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Default)]
 pub struct Headers<'a> {
     pub(crate) buf: &'a [u8],
     pub(crate) headers: &'a [HeaderIndices],
@@ -69,14 +160,60 @@ impl<'a> Headers<'a> {
         })
     }
 
+    /// Iterates over the names and values in the order received, headers with values
+    /// which are not valid UTF-8 are skipped.
     pub fn iter(&self) -> impl Iterator<Item = (&'a str, &'a str)> + 'a {
         self.try_iter().flatten()
     }
 
+    /// Returns the value of the first header named `key`.
+    ///
+    /// Header names are compared ignoring ASCII case, here and in the other lookups.
     pub fn get_first(&self, key: &str) -> Option<&'a str> {
         self.iter()
             .find_map(|(header_key, value)| key.eq_ignore_ascii_case(header_key).then_some(value))
     }
+
+    /// Iterates over the values of all headers named `key` in the order received,
+    /// e.g. of repeated `Cookie` or `Accept-Encoding` headers.
+    pub fn get_all<'k>(&self, key: &'k str) -> impl Iterator<Item = &'a str> + 'k
+    where
+        'a: 'k,
+    {
+        self.iter().filter_map(move |(header_key, value)| {
+            key.eq_ignore_ascii_case(header_key).then_some(value)
+        })
+    }
+
+    /// Iterates over the trimmed items of comma separated list headers named `key`,
+    /// e.g. `Connection` or `Accept-Encoding`, across all headers of that name.
+    ///
+    /// Empty items are skipped, parameters like `;q=0.5` are kept with their item.
+    pub fn get_list<'k>(&self, key: &'k str) -> impl Iterator<Item = &'a str> + 'k
+    where
+        'a: 'k,
+    {
+        self.get_all(key)
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+    }
+
+    /// Returns `true` if a list header named `key` contains `token`, ignoring case,
+    /// e.g. `close` in `Connection: keep-alive, Close`.
+    pub fn contains_token(&self, key: &str, token: &str) -> bool {
+        self.get_list(key)
+            .any(|item| item.eq_ignore_ascii_case(token))
+    }
+
+    /// Amount of headers received, including those skipped by [`iter`](Self::iter).
+    pub fn len(&self) -> usize {
+        self.headers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty()
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -110,39 +247,248 @@ pub(crate) fn record_header_indices(
 }
 
 pub struct Body<'a, R> {
+    /// Remaining body bytes, or bytes of the current chunk for chunked bodies.
     content_length: usize,
     buf: &'a [u8],
     reader: R,
+    chunked: Option<Chunk>,
+    /// Amount of bytes chunked bodies may still announce, see [`limit`](Self::limit).
+    limit: usize,
+}
+
+/// Error reading a [`Body`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyError<E> {
+    /// Reading from the connection failed.
+    Io(E),
+    /// The chunks of the body exceed the [`max_content_length`](crate::ServiceConfig::max_content_length).
+    TooLarge,
+    /// The chunked encoding of the body is malformed or the connection was closed
+    /// before its last chunk.
+    Malformed,
+}
+
+impl<E: embedded_io_async::Error> embedded_io_async::Error for BodyError<E> {
+    fn kind(&self) -> embedded_io_async::ErrorKind {
+        match self {
+            Self::Io(err) => err.kind(),
+            Self::TooLarge => embedded_io_async::ErrorKind::OutOfMemory,
+            Self::Malformed => embedded_io_async::ErrorKind::InvalidData,
+        }
+    }
 }
 
+/// Decoding state of a `Transfer-Encoding: chunked` body.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Chunk {
+    /// Expecting a chunk size line.
+    Size,
+    /// Reading chunk data.
+    Data,
+    /// Expecting the line break after the chunk data.
+    DataEnd,
+    /// The last chunk and trailer have been read.
+    Done,
+    /// The announced chunks exceed the limit, reading fails.
+    TooLarge,
+    /// The encoding is malformed or ends prematurely, reading fails.
+    Malformed,
+    /// The reader yields the decoded chunks, see [`Body::wrap`].
+    Decoded,
+}
+
+/// Maximum length of the chunk size, chunk extensions and trailer lines.
+const CHUNK_LINE: usize = 256;
+
 impl<'a, R: Read> Body<'a, R> {
     pub(crate) fn new(content_length: usize, buf: &'a [u8], reader: R) -> Self {
         Self {
             content_length,
             buf,
             reader,
+            chunked: None,
+            limit: usize::MAX,
+        }
+    }
+
+    /// Creates a body decoding the `Transfer-Encoding: chunked` body starting in `buf`.
+    ///
+    /// Reading fails with [`BodyError::Malformed`] if the encoding is malformed or
+    /// the input ends before the last chunk, a truncated body is never read as complete.
+    pub(crate) fn chunked(buf: &'a [u8], reader: R) -> Self {
+        Self {
+            content_length: 0,
+            buf,
+            reader,
+            chunked: Some(Chunk::Size),
+            limit: usize::MAX,
+        }
+    }
+
+    /// Fails reading a chunked body with [`BodyError::TooLarge`] once its chunks add up
+    /// to more than `limit` bytes, before any bytes of the exceeding chunk are read.
+    pub(crate) fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Creates a body reading `content_length` bytes from `reader`.
+    pub fn from_reader(content_length: usize, reader: R) -> Self {
+        Self::new(content_length, &[], reader)
+    }
+
+    /// Wraps the body into the reader returned by `wrap`, e.g. to inspect the bytes read.
+    ///
+    /// The wrapped body keeps the framing of this one: it ends where this body ends,
+    /// and its length stays unknown if this body is chunked.
+    pub(crate) fn wrap<T: Read>(self, wrap: impl FnOnce(Self) -> T) -> Body<'a, T> {
+        let (content_length, chunked) = match self.chunked {
+            None => (self.content_length, None),
+            Some(Chunk::Done) => (0, None),
+            Some(_) => (0, Some(Chunk::Decoded)),
+        };
+        Body {
+            content_length,
+            buf: &[],
+            reader: wrap(self),
+            chunked,
+            limit: usize::MAX,
+        }
+    }
+
+    /// Amount of body bytes not read yet, `usize::MAX` if unknown for chunked bodies.
+    pub(crate) fn remaining(&self) -> usize {
+        match self.chunked {
+            None => self.content_length,
+            Some(Chunk::Done) => 0,
+            Some(_) => usize::MAX,
+        }
+    }
+
+    /// Reads the raw body bytes, first from the request buffer then from the reader.
+    async fn read_raw(&mut self, buf: &mut [u8]) -> Result<usize, R::Error> {
+        if !self.buf.is_empty() {
+            let read = buf.len().min(self.buf.len());
+            buf[..read].copy_from_slice(&self.buf[..read]);
+            self.buf = &self.buf[read..];
+            Ok(read)
+        } else {
+            self.reader.read(buf).await
         }
     }
+
+    /// Reads a line of a chunked body, returns the hex number it starts with,
+    /// if any, and the length of the line.
+    ///
+    /// Returns `None` at the end of the input or if the line is too long.
+    async fn read_chunk_line(&mut self) -> Result<Option<(Option<usize>, usize)>, R::Error> {
+        let mut size = None;
+        let mut digits = true;
+        let mut len = 0;
+        for _ in 0..CHUNK_LINE {
+            let mut byte = [0];
+            if self.read_raw(&mut byte).await? == 0 {
+                return Ok(None);
+            }
+            match byte[0] {
+                b'\n' => return Ok(Some((size, len))),
+                b'\r' => continue,
+                c if digits && c.is_ascii_hexdigit() => {
+                    let digit = (c as char).to_digit(16).unwrap_or(0) as usize;
+                    size = size
+                        .unwrap_or(0usize)
+                        .checked_mul(16)
+                        .and_then(|size| size.checked_add(digit));
+                    // Overflowing sizes are invalid.
+                    digits = size.is_some();
+                }
+                // Chunk extensions and trailer fields are ignored.
+                _ => digits = false,
+            }
+            len += 1;
+        }
+        Ok(None)
+    }
+}
+
+impl<'a> Body<'a, &'static [u8]> {
+    /// Creates a body consisting of `data`.
+    pub fn from_slice(data: &'a [u8]) -> Self {
+        Self::new(data.len(), data, &[])
+    }
 }
 
 impl<'a, R: ErrorType> ErrorType for Body<'a, R> {
-    type Error = R::Error;
+    type Error = BodyError<R::Error>;
 }
 
 impl<'a, R: Read> Read for Body<'a, R> {
-    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, R::Error> {
-        if self.content_length == 0 {
-            return Ok(0);
-        }
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        loop {
+            match self.chunked {
+                None | Some(Chunk::Data) => {
+                    if self.content_length == 0 {
+                        return Ok(0);
+                    }
 
-        let read = if !self.buf.is_empty() {
-            let mut reader = &self.buf[..self.content_length.min(self.buf.len())];
-            reader.read(buf).await.unwrap_or_else(|x| match x {})
-        } else {
-            self.reader.read(buf).await?
-        };
+                    let len = buf.len().min(self.content_length);
+                    let read = self
+                        .read_raw(&mut buf[..len])
+                        .await
+                        .map_err(BodyError::Io)?;
 
-        self.content_length -= read;
-        Ok(read)
+                    self.content_length -= read;
+                    if self.chunked.is_some() {
+                        if read == 0 && len > 0 {
+                            self.chunked = Some(Chunk::Malformed);
+                            continue;
+                        }
+                        if self.content_length == 0 {
+                            self.chunked = Some(Chunk::DataEnd);
+                        }
+                    }
+                    return Ok(read);
+                }
+                Some(Chunk::Size) => {
+                    let line = self.read_chunk_line().await.map_err(BodyError::Io)?;
+                    self.chunked = Some(match line {
+                        Some((Some(0), _)) => {
+                            // Skip the trailer, it ends with an empty line.
+                            loop {
+                                match self.read_chunk_line().await.map_err(BodyError::Io)? {
+                                    Some((_, 0)) => break Chunk::Done,
+                                    Some(_) => {}
+                                    None => break Chunk::Malformed,
+                                }
+                            }
+                        }
+                        Some((Some(size), _)) if size > self.limit => Chunk::TooLarge,
+                        Some((Some(size), _)) => {
+                            self.limit -= size;
+                            self.content_length = size;
+                            Chunk::Data
+                        }
+                        _ => Chunk::Malformed,
+                    });
+                }
+                Some(Chunk::DataEnd) => {
+                    let line = self.read_chunk_line().await.map_err(BodyError::Io)?;
+                    self.chunked = Some(match line {
+                        Some((_, 0)) => Chunk::Size,
+                        _ => Chunk::Malformed,
+                    });
+                }
+                Some(Chunk::Done) => return Ok(0),
+                Some(Chunk::TooLarge) => return Err(BodyError::TooLarge),
+                Some(Chunk::Malformed) => return Err(BodyError::Malformed),
+                Some(Chunk::Decoded) => {
+                    let read = self.reader.read(buf).await.map_err(BodyError::Io)?;
+                    if read == 0 && !buf.is_empty() {
+                        self.chunked = Some(Chunk::Done);
+                    }
+                    return Ok(read);
+                }
+            }
+        }
     }
 }
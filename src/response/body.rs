@@ -0,0 +1,327 @@
+use core::{convert::Infallible, future::Future};
+
+use crate::{either::Either, io::Cursor, response::ResponseWriter, Read, Write};
+
+#[derive(Debug)]
+pub enum WriteError<IO, BODY> {
+    Io(IO),
+    Body(BODY),
+}
+
+impl<IO: embedded_io_async::Error, BODY: embedded_io_async::Error> embedded_io_async::Error
+    for WriteError<IO, BODY>
+{
+    fn kind(&self) -> embedded_io_async::ErrorKind {
+        match self {
+            Self::Io(err) => err.kind(),
+            Self::Body(err) => err.kind(),
+        }
+    }
+}
+
+/// The body of a [`Response`](crate::Response).
+///
+/// Bodies push their data into a [`ResponseWriter`], which allows handlers to return
+/// bodies that generate their content incrementally instead of buffering it upfront.
+pub trait ResponseBody {
+    /// Error produced by the body itself, writer errors are reported as [`WriteError::Io`].
+    type Error: embedded_io_async::Error;
+
+    /// Length of the body if known before writing it, sent as `Content-Length`.
+    ///
+    /// Must match the amount of bytes written by [`write_to`](Self::write_to).
+    fn content_length(&self) -> Option<usize> {
+        None
+    }
+
+    /// The whole body if it is available as a slice, used to derive entity tags,
+    /// see [`Router::etag`](crate::Router::etag).
+    ///
+    /// Must match the bytes written by [`write_to`](Self::write_to).
+    fn as_bytes(&self) -> Option<&[u8]> {
+        None
+    }
+
+    /// Path the request is handed on to instead of sending this body, see
+    /// [`Forward`](crate::rewrite::Forward). Bodies wrapping others pass it on.
+    fn forward_to(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Writes the whole body to `writer`.
+    ///
+    /// `buf` is scratch space which may be used to relay data, see [`Buffers`](crate::Buffers).
+    fn write_to<W: Write>(
+        self,
+        writer: &mut ResponseWriter<'_, W>,
+        buf: &mut [u8],
+    ) -> impl Future<Output = Result<(), WriteError<W::Error, Self::Error>>>;
+}
+
+/// Body calling a hook after it has been written, see [`Response::after_response`](crate::Response::after_response).
+#[derive(Clone)]
+pub struct AfterResponse<B, F> {
+    body: B,
+    hook: F,
+}
+
+impl<B, F> AfterResponse<B, F> {
+    pub(crate) fn new(body: B, hook: F) -> Self {
+        Self { body, hook }
+    }
+}
+
+impl<B: ResponseBody, F: FnOnce()> ResponseBody for AfterResponse<B, F> {
+    type Error = B::Error;
+
+    fn content_length(&self) -> Option<usize> {
+        self.body.content_length()
+    }
+
+    fn as_bytes(&self) -> Option<&[u8]> {
+        self.body.as_bytes()
+    }
+
+    fn forward_to(&self) -> Option<&'static str> {
+        self.body.forward_to()
+    }
+
+    async fn write_to<W: Write>(
+        self,
+        writer: &mut ResponseWriter<'_, W>,
+        buf: &mut [u8],
+    ) -> Result<(), WriteError<W::Error, Self::Error>> {
+        self.body.write_to(writer, buf).await?;
+        writer.flush().await.map_err(WriteError::Io)?;
+        (self.hook)();
+        Ok(())
+    }
+}
+
+/// Sends a body of unknown length with chunked transfer-encoding,
+/// which keeps the connection reusable, see [`ResponseWriter`].
+///
+/// ```
+/// use low_profile::{response::{Chunked, ReadBody}, Response};
+///
+/// async fn stream() -> Response<Chunked<ReadBody<&'static [u8]>>> {
+///     Response::new(Chunked(ReadBody(b"sensor readings")))
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Chunked<B>(pub B);
+
+impl<B: ResponseBody> ResponseBody for Chunked<B> {
+    type Error = B::Error;
+
+    fn forward_to(&self) -> Option<&'static str> {
+        self.0.forward_to()
+    }
+
+    async fn write_to<W: Write>(
+        self,
+        writer: &mut ResponseWriter<'_, W>,
+        buf: &mut [u8],
+    ) -> Result<(), WriteError<W::Error, Self::Error>> {
+        writer
+            .write_header("Transfer-Encoding", "chunked")
+            .await
+            .map_err(WriteError::Io)?;
+        self.0.write_to(writer, buf).await
+    }
+}
+
+impl ResponseBody for &'static [u8] {
+    type Error = Infallible;
+
+    fn content_length(&self) -> Option<usize> {
+        Some(self.len())
+    }
+
+    fn as_bytes(&self) -> Option<&[u8]> {
+        Some(self)
+    }
+
+    async fn write_to<W: Write>(
+        self,
+        writer: &mut ResponseWriter<'_, W>,
+        _buf: &mut [u8],
+    ) -> Result<(), WriteError<W::Error, Self::Error>> {
+        writer.write_all(self).await.map_err(WriteError::Io)
+    }
+}
+
+impl<T: AsRef<[u8]>> ResponseBody for Cursor<T> {
+    type Error = Infallible;
+
+    fn content_length(&self) -> Option<usize> {
+        Some(self.remaining_slice().len())
+    }
+
+    fn as_bytes(&self) -> Option<&[u8]> {
+        Some(self.remaining_slice())
+    }
+
+    async fn write_to<W: Write>(
+        self,
+        writer: &mut ResponseWriter<'_, W>,
+        _buf: &mut [u8],
+    ) -> Result<(), WriteError<W::Error, Self::Error>> {
+        writer
+            .write_all(self.remaining_slice())
+            .await
+            .map_err(WriteError::Io)
+    }
+}
+
+impl<L: ResponseBody, R: ResponseBody> ResponseBody for Either<L, R> {
+    type Error = Either<L::Error, R::Error>;
+
+    fn content_length(&self) -> Option<usize> {
+        match self {
+            Either::Left(left) => left.content_length(),
+            Either::Right(right) => right.content_length(),
+        }
+    }
+
+    fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Either::Left(left) => left.as_bytes(),
+            Either::Right(right) => right.as_bytes(),
+        }
+    }
+
+    fn forward_to(&self) -> Option<&'static str> {
+        match self {
+            Either::Left(left) => left.forward_to(),
+            Either::Right(right) => right.forward_to(),
+        }
+    }
+
+    async fn write_to<W: Write>(
+        self,
+        writer: &mut ResponseWriter<'_, W>,
+        buf: &mut [u8],
+    ) -> Result<(), WriteError<W::Error, Self::Error>> {
+        match self {
+            Either::Left(left) => left.write_to(writer, buf).await.map_err(|err| match err {
+                WriteError::Io(err) => WriteError::Io(err),
+                WriteError::Body(err) => WriteError::Body(Either::Left(err)),
+            }),
+            Either::Right(right) => right.write_to(writer, buf).await.map_err(|err| match err {
+                WriteError::Io(err) => WriteError::Io(err),
+                WriteError::Body(err) => WriteError::Body(Either::Right(err)),
+            }),
+        }
+    }
+}
+
+/// Source of the chunks of a [`StaticChunks`] body.
+pub trait ChunkSource {
+    type Error: embedded_io_async::Error;
+
+    /// Returns the next chunk, `None` after the last one.
+    fn next_chunk(&mut self) -> impl Future<Output = Result<Option<&'static [u8]>, Self::Error>>;
+}
+
+impl<I: Iterator<Item = &'static [u8]>> ChunkSource for I {
+    type Error = Infallible;
+
+    async fn next_chunk(&mut self) -> Result<Option<&'static [u8]>, Self::Error> {
+        Ok(self.next())
+    }
+}
+
+/// Body writing `&'static` chunks, e.g. in flash or static DMA capable RAM.
+///
+/// The chunks are not copied through the relay buffer, every chunk is passed to the
+/// connection's [`Write`] as is, possibly split up by partial writes. The slices
+/// the writer receives point into the returned chunks, writers can recognize such
+/// data by its address and hand it to DMA without copying it.
+///
+/// There is no API for the writer to take ownership of a chunk, services accept any
+/// [`Write`] and can't tell writers supporting it apart. The writer gets a plain
+/// `&[u8]`, keeping it after `write` returned relies on such an address check.
+///
+/// Bodies with a known total length are sent with `Content-Length`, others with
+/// chunked transfer-encoding.
+///
+/// ```
+/// use low_profile::{response::StaticChunks, Response};
+///
+/// static HEADER: &[u8] = b"<html><body>";
+/// static FOOTER: &[u8] = b"</body></html>";
+///
+/// async fn page() -> Response<StaticChunks<core::array::IntoIter<&'static [u8], 2>>> {
+///     let len = HEADER.len() + FOOTER.len();
+///     Response::new(StaticChunks::new([HEADER, FOOTER].into_iter()).with_len(len))
+/// }
+/// ```
+pub struct StaticChunks<C> {
+    source: C,
+    len: Option<usize>,
+}
+
+impl<C: ChunkSource> StaticChunks<C> {
+    pub fn new(source: C) -> Self {
+        Self { source, len: None }
+    }
+
+    /// Declares the total length of all chunks, which must match the chunks returned.
+    ///
+    /// Bytes beyond the declared length are not sent, if the chunks are shorter the
+    /// connection is closed after the response.
+    pub fn with_len(mut self, len: usize) -> Self {
+        self.len = Some(len);
+        self
+    }
+}
+
+impl<C: ChunkSource> ResponseBody for StaticChunks<C> {
+    type Error = C::Error;
+
+    fn content_length(&self) -> Option<usize> {
+        self.len
+    }
+
+    async fn write_to<W: Write>(
+        mut self,
+        writer: &mut ResponseWriter<'_, W>,
+        _buf: &mut [u8],
+    ) -> Result<(), WriteError<W::Error, Self::Error>> {
+        if self.len.is_none() {
+            writer
+                .write_header("Transfer-Encoding", "chunked")
+                .await
+                .map_err(WriteError::Io)?;
+        }
+        while let Some(chunk) = self.source.next_chunk().await.map_err(WriteError::Body)? {
+            writer.write_all(chunk).await.map_err(WriteError::Io)?;
+        }
+        Ok(())
+    }
+}
+
+/// Body relaying everything read from a [`Read`]er.
+pub struct ReadBody<R>(pub R);
+
+impl<R: Read> ResponseBody for ReadBody<R> {
+    type Error = R::Error;
+
+    async fn write_to<W: Write>(
+        mut self,
+        writer: &mut ResponseWriter<'_, W>,
+        buf: &mut [u8],
+    ) -> Result<(), WriteError<W::Error, Self::Error>> {
+        loop {
+            let len = self.0.read(buf).await.map_err(WriteError::Body)?;
+            if len == 0 {
+                return Ok(());
+            }
+            writer
+                .write_all(&buf[..len])
+                .await
+                .map_err(WriteError::Io)?;
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use super::{HeaderError, IntoResponse, Response, ResponseHeaders, WithHeaders};
+use crate::http::StatusCode;
+
+/// Builds a response from its status, headers and body, see [`Response::builder`].
+///
+/// Keeps up to `N` headers with names and values of `SIZE` bytes in total, like
+/// [`ResponseHeaders`].
+#[derive(Debug, Clone)]
+pub struct Builder<const N: usize = 8, const SIZE: usize = 256> {
+    status_code: Option<StatusCode>,
+    content_type: Option<&'static str>,
+    headers: ResponseHeaders<N, SIZE>,
+    error: Option<HeaderError>,
+}
+
+impl Response<()> {
+    /// Creates a [`Builder`] for a response.
+    ///
+    /// ```
+    /// use low_profile::{http::StatusCode, IntoResponse, Response};
+    ///
+    /// async fn create() -> impl IntoResponse {
+    ///     Response::builder()
+    ///         .status(StatusCode::CREATED)
+    ///         .header("Location", "/items/42")
+    ///         .content_type("application/json")
+    ///         .body(r#"{"id":42}"#)
+    ///         .unwrap()
+    /// }
+    /// ```
+    pub fn builder() -> Builder {
+        Builder::new()
+    }
+}
+
+impl<const N: usize, const SIZE: usize> Builder<N, SIZE> {
+    pub const fn new() -> Self {
+        Self {
+            status_code: None,
+            content_type: None,
+            headers: ResponseHeaders::new(),
+            error: None,
+        }
+    }
+
+    /// Sets the status code, replacing the one of the body.
+    pub fn status(mut self, status_code: StatusCode) -> Self {
+        self.status_code = Some(status_code);
+        self
+    }
+
+    /// Sets the `Content-Type` sent unless the body writes its own, replacing the
+    /// one of the body.
+    pub fn content_type(mut self, content_type: &'static str) -> Self {
+        self.content_type = Some(content_type);
+        self
+    }
+
+    /// Adds a header, existing headers with the same name are kept.
+    ///
+    /// A header which can't be added fails [`body`](Self::body).
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        if self.error.is_none() {
+            self.error = self.headers.append(name, value).err();
+        }
+        self
+    }
+
+    /// Completes the response with `body`, which may be anything convertible into a
+    /// response, e.g. a `&'static str` or a [`Response`].
+    ///
+    /// Fails if a header could not be added.
+    pub fn body<T: IntoResponse>(
+        self,
+        body: T,
+    ) -> Result<Response<WithHeaders<T::Body, N, SIZE>>, HeaderError> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        let mut response = body.into_response();
+        if let Some(status_code) = self.status_code {
+            response.status_code = status_code;
+        }
+        if let Some(content_type) = self.content_type {
+            response.content_type = Some(content_type);
+        }
+        Ok(response.with_headers(self.headers))
+    }
+}
+
+impl<const N: usize, const SIZE: usize> Default for Builder<N, SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,245 @@
+use core::fmt;
+
+use super::{IntoResponse, Response, ResponseBody, ResponseWriter, WriteError};
+use crate::{
+    http::StatusCode,
+    route::{Decision, Route},
+    Read, Request, Write,
+};
+
+/// Error returned when a header can't be added to [`ResponseHeaders`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderError {
+    /// The maximum amount of headers or bytes is reached.
+    Full,
+    /// The name is empty or the name or value contains a line break.
+    Invalid,
+}
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full => f.write_str("too many response headers"),
+            Self::Invalid => f.write_str("invalid response header"),
+        }
+    }
+}
+
+/// Fixed capacity collection of response headers.
+///
+/// Keeps up to `N` headers with names and values of `SIZE` bytes in total.
+///
+/// ```
+/// use low_profile::{http::StatusCode, response::ResponseHeaders, IntoResponse};
+///
+/// async fn create() -> impl IntoResponse {
+///     let mut headers = ResponseHeaders::<2, 64>::new();
+///     headers.append("Location", "/items/42").unwrap();
+///     headers.append("Cache-Control", "no-store").unwrap();
+///
+///     (StatusCode::CREATED, headers, "created")
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ResponseHeaders<const N: usize = 8, const SIZE: usize = 256> {
+    buf: heapless::String<SIZE>,
+    /// End of the name and end of the value of each header within `buf`.
+    entries: heapless::Vec<(usize, usize), N>,
+}
+
+impl<const N: usize, const SIZE: usize> ResponseHeaders<N, SIZE> {
+    pub const fn new() -> Self {
+        Self {
+            buf: heapless::String::new(),
+            entries: heapless::Vec::new(),
+        }
+    }
+
+    /// Adds a header, existing headers with the same name are kept.
+    pub fn append(&mut self, name: &str, value: &str) -> Result<(), HeaderError> {
+        let invalid = |s: &str| s.bytes().any(|c| c == b'\r' || c == b'\n');
+        if name.is_empty() || name.contains(':') || invalid(name) || invalid(value) {
+            return Err(HeaderError::Invalid);
+        }
+        if self.entries.is_full() || self.buf.len() + name.len() + value.len() > SIZE {
+            return Err(HeaderError::Full);
+        }
+
+        // Can't fail, the capacity was checked above.
+        let _ = self.buf.push_str(name);
+        let name_end = self.buf.len();
+        let _ = self.buf.push_str(value);
+        let _ = self.entries.push((name_end, self.buf.len()));
+        Ok(())
+    }
+
+    /// Returns the value of the first header named `name`, ignoring case.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.iter()
+            .find_map(|(header, value)| header.eq_ignore_ascii_case(name).then_some(value))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        let mut start = 0;
+        self.entries.iter().map(move |&(name_end, value_end)| {
+            let header = (&self.buf[start..name_end], &self.buf[name_end..value_end]);
+            start = value_end;
+            header
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Body writing [`ResponseHeaders`] before the wrapped body,
+/// see [`Response::with_headers`].
+#[derive(Clone)]
+pub struct WithHeaders<B, const N: usize, const SIZE: usize> {
+    headers: ResponseHeaders<N, SIZE>,
+    body: B,
+}
+
+impl<B: ResponseBody, const N: usize, const SIZE: usize> ResponseBody for WithHeaders<B, N, SIZE> {
+    type Error = B::Error;
+
+    fn content_length(&self) -> Option<usize> {
+        self.body.content_length()
+    }
+
+    fn as_bytes(&self) -> Option<&[u8]> {
+        self.body.as_bytes()
+    }
+
+    fn forward_to(&self) -> Option<&'static str> {
+        self.body.forward_to()
+    }
+
+    async fn write_to<W: Write>(
+        self,
+        writer: &mut ResponseWriter<'_, W>,
+        buf: &mut [u8],
+    ) -> Result<(), WriteError<W::Error, Self::Error>> {
+        for (name, value) in self.headers.iter() {
+            writer
+                .write_header(name, value)
+                .await
+                .map_err(WriteError::Io)?;
+        }
+        self.body.write_to(writer, buf).await
+    }
+}
+
+impl<Body> Response<Body> {
+    /// Sends `headers` before the headers written by the body.
+    pub fn with_headers<const N: usize, const SIZE: usize>(
+        self,
+        headers: ResponseHeaders<N, SIZE>,
+    ) -> Response<WithHeaders<Body, N, SIZE>> {
+        self.map_body(|body| WithHeaders { headers, body })
+    }
+}
+
+impl<T: IntoResponse, const N: usize, const SIZE: usize> IntoResponse
+    for (ResponseHeaders<N, SIZE>, T)
+{
+    type Body = WithHeaders<T::Body, N, SIZE>;
+
+    fn into_response(self) -> Response<Self::Body> {
+        self.1.into_response().with_headers(self.0)
+    }
+}
+
+impl<T: IntoResponse, const N: usize, const SIZE: usize> IntoResponse
+    for (StatusCode, ResponseHeaders<N, SIZE>, T)
+{
+    type Body = WithHeaders<T::Body, N, SIZE>;
+
+    fn into_response(self) -> Response<Self::Body> {
+        (self.0, (self.1, self.2)).into_response()
+    }
+}
+
+/// A response or body sent with default headers, see [`Response::with_default_headers`].
+pub struct DefaultHeaders<T> {
+    headers: &'static [(&'static str, &'static str)],
+    inner: T,
+}
+
+impl<T: IntoResponse> IntoResponse for DefaultHeaders<T> {
+    type Body = DefaultHeaders<T::Body>;
+
+    fn into_response(self) -> Response<Self::Body> {
+        self.inner
+            .into_response()
+            .with_default_headers(self.headers)
+    }
+}
+
+impl<B: ResponseBody> ResponseBody for DefaultHeaders<B> {
+    type Error = B::Error;
+
+    fn content_length(&self) -> Option<usize> {
+        self.inner.content_length()
+    }
+
+    fn as_bytes(&self) -> Option<&[u8]> {
+        self.inner.as_bytes()
+    }
+
+    fn forward_to(&self) -> Option<&'static str> {
+        self.inner.forward_to()
+    }
+
+    async fn write_to<W: Write>(
+        self,
+        writer: &mut ResponseWriter<'_, W>,
+        buf: &mut [u8],
+    ) -> Result<(), WriteError<W::Error, Self::Error>> {
+        writer.set_default_headers(self.headers);
+        self.inner.write_to(writer, buf).await
+    }
+}
+
+impl<Body> Response<Body> {
+    /// Sends `headers` unless the body writes a header of the same name,
+    /// replacing default headers set by an outer route.
+    ///
+    /// `Content-Type`, `Content-Length`, `Date`, `Transfer-Encoding` and `Connection`
+    /// are set by other means and skipped in the defaults, only the first 32 headers
+    /// are sent.
+    pub fn with_default_headers(
+        self,
+        headers: &'static [(&'static str, &'static str)],
+    ) -> Response<DefaultHeaders<Body>> {
+        self.map_body(|inner| DefaultHeaders { headers, inner })
+    }
+}
+
+pub(crate) struct DefaultHeadersRoute<R> {
+    pub(crate) headers: &'static [(&'static str, &'static str)],
+    pub(crate) route: R,
+}
+
+impl<S, R: Route<S>> Route<S> for DefaultHeadersRoute<R> {
+    type Response = DefaultHeaders<R::Response>;
+
+    async fn match_request<'a, Body: Read>(
+        &'a self,
+        req: Request<'a, Body>,
+        state: &'a S,
+    ) -> Decision<'a, Self::Response, Body> {
+        self.route
+            .match_request(req, state)
+            .await
+            .map(|inner| DefaultHeaders {
+                headers: self.headers,
+                inner,
+            })
+    }
+}
@@ -0,0 +1,225 @@
+use crate::{either::Either, http::StatusCode, io::Cursor};
+
+mod body;
+mod builder;
+mod headers;
+mod redirect;
+mod writer;
+
+pub use body::{
+    AfterResponse, ChunkSource, Chunked, ReadBody, ResponseBody, StaticChunks, WriteError,
+};
+pub use builder::Builder;
+pub(crate) use headers::DefaultHeadersRoute;
+pub use headers::{DefaultHeaders, HeaderError, ResponseHeaders, WithHeaders};
+pub use redirect::Redirect;
+pub use writer::ResponseWriter;
+
+/// Content type of text responses.
+pub(crate) const TEXT: &str = "text/plain; charset=utf-8";
+
+#[derive(Clone)]
+pub struct Response<Body> {
+    status_code: StatusCode,
+    content_type: Option<&'static str>,
+    close: bool,
+    body: Body,
+}
+
+impl<Body> Response<Body> {
+    /// Creates a `200 OK` response with the given body.
+    pub fn new(body: Body) -> Self {
+        Self {
+            status_code: StatusCode::OK,
+            content_type: None,
+            close: false,
+            body,
+        }
+    }
+
+    pub fn status_code(&self) -> StatusCode {
+        self.status_code
+    }
+
+    /// The `Content-Type` sent unless the body writes its own.
+    pub fn content_type(&self) -> Option<&'static str> {
+        self.content_type
+    }
+
+    /// Sets the `Content-Type` sent unless the body writes its own.
+    pub fn with_content_type(mut self, content_type: &'static str) -> Self {
+        self.content_type = Some(content_type);
+        self
+    }
+
+    /// Closes the connection after this response, it is sent with `Connection: close`
+    /// and further requests pipelined by the client are not served.
+    ///
+    /// Useful for responses after which the client should reconnect, e.g. before
+    /// rebooting the device or after failed authentication. Bodies deciding while
+    /// being written use [`ResponseWriter::close_connection`] instead.
+    ///
+    /// ```
+    /// use low_profile::{IntoResponse, Response};
+    ///
+    /// fn reboot() { /* ... */ }
+    ///
+    /// async fn handler() -> Response<impl low_profile::response::ResponseBody> {
+    ///     "rebooting"
+    ///         .into_response()
+    ///         .close_connection()
+    ///         .after_response(reboot)
+    /// }
+    /// ```
+    pub fn close_connection(mut self) -> Self {
+        self.close = true;
+        self
+    }
+
+    /// Returns `true` if the connection is closed after this response, see
+    /// [`close_connection`](Self::close_connection).
+    pub fn closes_connection(&self) -> bool {
+        self.close
+    }
+
+    pub fn body(&self) -> &Body {
+        &self.body
+    }
+
+    pub fn into_body(self) -> Body {
+        self.body
+    }
+
+    /// Calls `hook` once the response has been completely written and flushed.
+    ///
+    /// This allows acting on a request only after the client received the reply,
+    /// e.g. rebooting the device after answering a `/reboot` request.
+    /// The hook is not called if writing the response fails, nor if the response is
+    /// [forwarded](crate::rewrite::Forward) and never written.
+    ///
+    /// ```
+    /// use low_profile::{IntoResponse, Response};
+    ///
+    /// fn reboot() { /* ... */ }
+    ///
+    /// async fn handler() -> Response<impl low_profile::response::ResponseBody> {
+    ///     "rebooting".into_response().after_response(reboot)
+    /// }
+    /// ```
+    pub fn after_response<F: FnOnce()>(self, hook: F) -> Response<AfterResponse<Body, F>> {
+        self.map_body(|body| AfterResponse::new(body, hook))
+    }
+}
+
+impl<Body> Response<Body> {
+    pub(crate) fn map_body<F, T>(self, map: F) -> Response<T>
+    where
+        F: FnOnce(Body) -> T,
+    {
+        Response {
+            status_code: self.status_code,
+            content_type: self.content_type,
+            close: self.close,
+            body: map(self.body),
+        }
+    }
+}
+
+pub trait IntoResponse {
+    type Body: ResponseBody;
+
+    fn into_response(self) -> Response<Self::Body>;
+}
+
+impl IntoResponse for core::convert::Infallible {
+    type Body = &'static [u8];
+
+    fn into_response(self) -> Response<Self::Body> {
+        match self {}
+    }
+}
+
+impl<Body: ResponseBody> IntoResponse for Response<Body> {
+    type Body = Body;
+
+    fn into_response(self) -> Response<Self::Body> {
+        self
+    }
+}
+
+impl IntoResponse for &'static str {
+    type Body = &'static [u8];
+
+    fn into_response(self) -> Response<Self::Body> {
+        Response::new(self.as_bytes()).with_content_type(TEXT)
+    }
+}
+
+impl<T: IntoResponse> IntoResponse for (StatusCode, T) {
+    type Body = T::Body;
+
+    fn into_response(self) -> Response<Self::Body> {
+        let mut response = self.1.into_response();
+        response.status_code = self.0;
+        response
+    }
+}
+
+/// Responds with `T` or the error `E`, both may be any response type.
+impl<T: IntoResponse, E: IntoResponse> IntoResponse for Result<T, E> {
+    type Body = Either<T::Body, E::Body>;
+
+    fn into_response(self) -> Response<Self::Body> {
+        match self {
+            Ok(ok) => Either::Left(ok),
+            Err(err) => Either::Right(err),
+        }
+        .into_response()
+    }
+}
+
+/// Responds with `T` or `404 Not Found` for `None`.
+///
+/// ```
+/// use low_profile::{extract::Path, Router};
+///
+/// const LEDS: [&str; 2] = ["on", "off"];
+///
+/// async fn led(Path(id): Path<usize>) -> Option<&'static str> {
+///     LEDS.get(id).copied()
+/// }
+///
+/// let router = Router::<(), _>::new().get("/leds/:id", led);
+/// ```
+impl<T: IntoResponse> IntoResponse for Option<T> {
+    type Body = Either<T::Body, &'static [u8]>;
+
+    fn into_response(self) -> Response<Self::Body> {
+        self.ok_or((StatusCode::NOT_FOUND, "Not Found"))
+            .into_response()
+    }
+}
+
+impl IntoResponse for () {
+    type Body = &'static [u8];
+
+    fn into_response(self) -> Response<Self::Body> {
+        Response::new(b"")
+    }
+}
+
+impl<const SIZE: usize> IntoResponse for heapless::Vec<u8, SIZE> {
+    type Body = Cursor<Self>;
+
+    fn into_response(self) -> Response<Self::Body> {
+        Response::new(Cursor::new(self)).with_content_type("application/octet-stream")
+    }
+}
+
+impl<const SIZE: usize> IntoResponse for heapless::String<SIZE> {
+    type Body = Cursor<Self>;
+
+    fn into_response(self) -> Response<Self::Body> {
+        Response::new(Cursor::new(self)).with_content_type(TEXT)
+    }
+}
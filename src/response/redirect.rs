@@ -0,0 +1,113 @@
+use core::convert::Infallible;
+
+use super::{IntoResponse, Response, ResponseBody, ResponseWriter, WriteError};
+use crate::{http::StatusCode, Write};
+
+/// Maximum length of the `Location` header of a [`Redirect`].
+pub(crate) const LOCATION: usize = 256;
+
+/// Response redirecting the client to another location, sent in the `Location`
+/// header.
+///
+/// Locations longer than 256 bytes can't be stored, the redirect is answered with
+/// `500 Internal Server Error` instead. So are locations containing control
+/// characters, which could inject headers into the response.
+///
+/// ```
+/// use low_profile::{response::Redirect, Router};
+///
+/// // Sends clients probing for connectivity to the captive portal.
+/// let router = Router::<(), _>::new()
+///     .get("/portal", || async { "Welcome" })
+///     .get("/hotspot-detect.html", || async { Redirect::found("/portal") });
+/// ```
+#[derive(Debug, Clone)]
+pub struct Redirect {
+    status_code: StatusCode,
+    location: Option<heapless::String<LOCATION>>,
+}
+
+impl Redirect {
+    pub(crate) fn new(
+        status_code: StatusCode,
+        location: Option<heapless::String<LOCATION>>,
+    ) -> Self {
+        let invalid = |location: &str| location.bytes().any(|c| c.is_ascii_control());
+        if location.as_deref().is_some_and(invalid) {
+            return Self::new(StatusCode::INTERNAL_SERVER_ERROR, None);
+        }
+        Self {
+            status_code,
+            location,
+        }
+    }
+
+    fn with_status(status_code: StatusCode, location: &str) -> Self {
+        match location.try_into() {
+            Ok(location) => Self::new(status_code, Some(location)),
+            Err(()) => Self::new(StatusCode::INTERNAL_SERVER_ERROR, None),
+        }
+    }
+
+    /// Redirects with `303 See Other`, the client fetches `location` with `GET`,
+    /// e.g. after submitting a form.
+    pub fn to(location: &str) -> Self {
+        Self::with_status(StatusCode::SEE_OTHER, location)
+    }
+
+    /// Redirects with `302 Found`, clients may change the method to `GET`.
+    pub fn found(location: &str) -> Self {
+        Self::with_status(StatusCode::FOUND, location)
+    }
+
+    /// Redirects with `307 Temporary Redirect`, keeping the method and body.
+    pub fn temporary(location: &str) -> Self {
+        Self::with_status(StatusCode::TEMPORARY_REDIRECT, location)
+    }
+
+    /// Redirects with `301 Moved Permanently`, clients may change the method to `GET`.
+    pub fn moved_permanently(location: &str) -> Self {
+        Self::with_status(StatusCode::MOVED_PERMANENTLY, location)
+    }
+
+    /// Redirects with `308 Permanent Redirect`, keeping the method and body.
+    pub fn permanent(location: &str) -> Self {
+        Self::with_status(StatusCode::PERMANENT_REDIRECT, location)
+    }
+
+    /// The location redirected to, `None` if it didn't fit.
+    pub fn location(&self) -> Option<&str> {
+        self.location.as_deref()
+    }
+}
+
+impl ResponseBody for Redirect {
+    type Error = Infallible;
+
+    fn content_length(&self) -> Option<usize> {
+        Some(0)
+    }
+
+    async fn write_to<W: Write>(
+        self,
+        writer: &mut ResponseWriter<'_, W>,
+        _buf: &mut [u8],
+    ) -> Result<(), WriteError<W::Error, Self::Error>> {
+        if let Some(location) = self.location {
+            writer
+                .write_header("Location", &location)
+                .await
+                .map_err(WriteError::Io)?;
+        }
+        Ok(())
+    }
+}
+
+impl IntoResponse for Redirect {
+    type Body = Self;
+
+    fn into_response(self) -> Response<Self::Body> {
+        let status_code = self.status_code;
+        (status_code, Response::new(self)).into_response()
+    }
+}
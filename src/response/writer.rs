@@ -0,0 +1,427 @@
+use core::ops::Range;
+
+use crate::{http::StatusCode, time::Timestamp, utils, ErrorType, Write};
+
+/// Sink the response is written to.
+///
+/// The response head is sent lazily together with the first header or body bytes,
+/// until then the status code can still be changed with [`set_status`](Self::set_status).
+/// This allows a [`ResponseBody`](super::ResponseBody) to decide on the status and
+/// headers while generating its content.
+///
+/// Bodies of unknown length can write the `Transfer-Encoding: chunked` header,
+/// all following writes are then sent as chunks, keeping the connection reusable.
+/// HTTP/1.0 clients don't know chunked encoding, for them the header is dropped and
+/// the end of the body is told by closing the connection.
+///
+/// The head is written in a fixed order, the same response is always sent as the same
+/// bytes: the status line, the headers written by the body in their order, then
+/// `Content-Type`, `Date`, the [default headers](crate::Router::default_headers),
+/// `Content-Length` and `Connection: close`, unless the body wrote them.
+pub struct ResponseWriter<'w, W> {
+    writer: Coalesce<'w, W>,
+    status: StatusCode,
+    head: Head,
+    /// Whether the connection can be reused after this response.
+    keep_alive: bool,
+    /// Whether the body declared its length, which is required for keep-alive.
+    framed: bool,
+    /// Whether the body is sent with chunked transfer-encoding.
+    chunked: bool,
+    /// Content type sent unless the body writes its own.
+    content_type: Option<&'static str>,
+    /// Length of the body if known upfront, sent unless the body declares its length.
+    content_length: Option<usize>,
+    /// Time sent as `Date` unless the body writes its own.
+    date: Option<Timestamp>,
+    /// Headers sent unless the body writes a header of the same name.
+    default_headers: &'static [(&'static str, &'static str)],
+    /// Bit set of the default headers written by the body.
+    overridden: u32,
+    /// Window of the body bytes still to send, relative to the next byte written,
+    /// see [`set_range`](Self::set_range).
+    range: Option<Range<usize>>,
+    /// Whether only the head is sent, as for `HEAD` requests.
+    head_only: bool,
+    /// Body bytes still to send according to the declared `Content-Length`.
+    remaining: Option<usize>,
+    /// Whether the client speaks HTTP/1.0, which has no chunked transfer-encoding.
+    http10: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Head {
+    /// Nothing written yet.
+    Pending,
+    /// The status line and possibly headers are written.
+    Headers,
+    /// The head is terminated, body bytes follow.
+    Written,
+}
+
+impl<'w, W: Write> ResponseWriter<'w, W> {
+    /// Creates a writer for a response with `status`, `keep_alive` is `true`
+    /// if the client allows reusing the connection.
+    pub(crate) fn new(writer: &'w mut W, status: StatusCode, keep_alive: bool) -> Self {
+        Self {
+            writer: Coalesce {
+                writer,
+                buf: &mut [],
+                len: 0,
+            },
+            status,
+            head: Head::Pending,
+            keep_alive,
+            framed: false,
+            chunked: false,
+            content_type: None,
+            content_length: None,
+            date: None,
+            default_headers: &[],
+            overridden: 0,
+            range: None,
+            head_only: false,
+            remaining: None,
+            http10: false,
+        }
+    }
+
+    /// Sends everything in writes of `buf.len()` bytes, buffering smaller writes in
+    /// `buf`, see [`ServiceConfig::write_chunk`](crate::ServiceConfig::write_chunk).
+    ///
+    /// Only the last write before a flush may be shorter.
+    pub(crate) fn set_write_chunk(&mut self, buf: &'w mut [u8]) {
+        self.writer.buf = buf;
+    }
+
+    /// Only sends the head, the body written is consumed without being sent.
+    ///
+    /// The head still declares the framing of the body, as the response to a `HEAD`
+    /// request carries the same headers as the response to a `GET` request.
+    pub(crate) fn set_head_only(&mut self, head_only: bool) {
+        self.head_only = head_only;
+    }
+
+    /// Sets the `Content-Type` and `Content-Length` sent unless the body writes them.
+    pub(crate) fn set_defaults(
+        &mut self,
+        content_type: Option<&'static str>,
+        content_length: Option<usize>,
+    ) {
+        self.content_type = content_type;
+        self.content_length = content_length;
+    }
+
+    /// Sets the time sent as `Date` unless the body writes its own.
+    pub(crate) fn set_date(&mut self, date: Option<Timestamp>) {
+        self.date = date;
+    }
+
+    /// Sets the headers sent unless the body writes a header of the same name,
+    /// replacing former defaults. Only the first 32 headers are considered.
+    ///
+    /// Headers already written in place of a former default stay overridden.
+    pub(crate) fn set_default_headers(&mut self, headers: &'static [(&'static str, &'static str)]) {
+        let mut overridden = 0;
+        for (i, (name, _)) in headers.iter().take(32).enumerate() {
+            let written =
+                self.default_headers
+                    .iter()
+                    .take(32)
+                    .enumerate()
+                    .any(|(j, (former, _))| {
+                        self.overridden & 1 << j != 0 && former.eq_ignore_ascii_case(name)
+                    });
+            if written {
+                overridden |= 1 << i;
+            }
+        }
+        self.default_headers = headers;
+        self.overridden = overridden;
+    }
+
+    /// Sets the minor HTTP version of the request, `0` for HTTP/1.0.
+    pub(crate) fn set_version(&mut self, version: u8) {
+        self.http10 = version == 0;
+    }
+
+    /// Only sends the bytes in `range` of the body written from now on, the others
+    /// are consumed without being sent.
+    pub(crate) fn set_range(&mut self, range: Range<usize>) {
+        self.range = Some(range);
+    }
+
+    /// Returns `true` if only a range of the body is sent, see [`set_range`](Self::set_range).
+    pub(crate) fn is_ranged(&self) -> bool {
+        self.range.is_some()
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Changes the status code of the response.
+    ///
+    /// Has no effect once the status line has been written, which happens with
+    /// the first header or body bytes.
+    pub fn set_status(&mut self, status: StatusCode) {
+        if self.head == Head::Pending {
+            self.status = status;
+        }
+    }
+
+    /// Closes the connection after this response, as
+    /// [`Response::close_connection`](super::Response::close_connection).
+    ///
+    /// Has no effect once the head has been terminated, writing a `Connection: close`
+    /// header has the same effect.
+    pub fn close_connection(&mut self) {
+        if self.head != Head::Written {
+            self.keep_alive = false;
+        }
+    }
+
+    /// Returns `true` once the head has been terminated, no more headers can be written.
+    pub fn is_head_written(&self) -> bool {
+        self.head == Head::Written
+    }
+
+    /// Writes a response header.
+    ///
+    /// Headers written after the first body bytes are silently dropped.
+    ///
+    /// The connection is only kept alive for responses declaring a `Content-Length`,
+    /// writing `Connection: close` closes it after the response. Body bytes beyond the
+    /// declared length are dropped, if fewer are written the connection is closed.
+    pub async fn write_header(&mut self, name: &str, value: &str) -> Result<(), W::Error> {
+        if self.head == Head::Written {
+            return Ok(());
+        }
+        if name.eq_ignore_ascii_case("Content-Length") {
+            self.framed = true;
+            self.remaining = value.trim().parse().ok();
+            if self.remaining.is_none() {
+                // The client can't tell where the body ends either.
+                self.keep_alive = false;
+            }
+        } else if name.eq_ignore_ascii_case("Content-Type") {
+            self.content_type = None;
+        } else if name.eq_ignore_ascii_case("Date") {
+            self.date = None;
+        } else if name.eq_ignore_ascii_case("Transfer-Encoding")
+            && utils::has_token(value, "chunked")
+        {
+            if self.http10 {
+                // Sent as is, delimited by closing the connection.
+                return Ok(());
+            }
+            self.framed = true;
+            self.chunked = true;
+        } else if name.eq_ignore_ascii_case("Connection") && utils::has_token(value, "close") {
+            // Written once the head is complete.
+            self.keep_alive = false;
+            return Ok(());
+        }
+        for (i, (default, _)) in self.default_headers.iter().take(32).enumerate() {
+            if name.eq_ignore_ascii_case(default) {
+                self.overridden |= 1 << i;
+            }
+        }
+        self.write_status_line().await?;
+
+        for part in [name, ": ", value, "\r\n"] {
+            self.writer.write_all(part.as_bytes()).await?;
+        }
+        Ok(())
+    }
+
+    async fn write_status_line(&mut self) -> Result<(), W::Error> {
+        use utils::{WriteExt, WriteFmtError};
+
+        if self.head != Head::Pending {
+            return Ok(());
+        }
+        self.head = Head::Headers;
+
+        write!(self.writer, "HTTP/1.1 {}\r\n", self.status)
+            .await
+            .map_err(|err| match err {
+                WriteFmtError::FmtError => unreachable!("internal format buffer too small"),
+                WriteFmtError::Other(err) => err,
+            })
+    }
+
+    async fn write_head(&mut self) -> Result<(), W::Error> {
+        if self.head == Head::Written {
+            return Ok(());
+        }
+        self.write_status_line().await?;
+        self.head = Head::Written;
+
+        let status = self.status.as_u16();
+        if self.status.is_informational() || status == 204 || status == 304 {
+            // Responses without a body.
+            self.framed = true;
+            self.remaining = Some(0);
+        } else if let Some(content_type) = self.content_type {
+            for part in ["Content-Type: ", content_type, "\r\n"] {
+                self.writer.write_all(part.as_bytes()).await?;
+            }
+        }
+
+        if let Some(date) = self.date {
+            let mut value = heapless::String::<40>::new();
+            // Can't overflow, the year of a `Timestamp` has at most 12 digits.
+            let _ = core::fmt::write(&mut value, format_args!("{}", date.http_date()));
+            for part in ["Date: ", &value, "\r\n"] {
+                self.writer.write_all(part.as_bytes()).await?;
+            }
+        }
+
+        for (i, (name, value)) in self.default_headers.iter().take(32).enumerate() {
+            if self.overridden & 1 << i == 0 && !is_managed(name) {
+                for part in [name, ": ", value, "\r\n"] {
+                    self.writer.write_all(part.as_bytes()).await?;
+                }
+            }
+        }
+
+        if let (false, Some(length)) = (self.framed, self.content_length) {
+            let mut value = heapless::String::<20>::new();
+            // Can't overflow, a `usize` has at most 20 digits.
+            let _ = core::fmt::write(&mut value, format_args!("{length}"));
+            for part in ["Content-Length: ", &value, "\r\n"] {
+                self.writer.write_all(part.as_bytes()).await?;
+            }
+            self.framed = true;
+            self.remaining = Some(length);
+        }
+
+        if !self.framed {
+            // The end of the body can only be told by closing the connection.
+            self.keep_alive = false;
+        }
+        if !self.keep_alive {
+            self.writer.write_all(b"Connection: close\r\n").await?;
+        }
+        self.writer.write_all(b"\r\n").await
+    }
+
+    /// Completes and flushes the response, writing the head if no body was written.
+    ///
+    /// Returns `true` if the connection can be reused for another request, which is
+    /// not the case if the body was shorter than its declared length.
+    pub(crate) async fn finish(mut self) -> Result<bool, W::Error> {
+        self.write_head().await?;
+        if self.chunked && !self.head_only {
+            self.writer.write_all(b"0\r\n\r\n").await?;
+        }
+        self.writer.flush().await?;
+        let complete = self.head_only || self.remaining.unwrap_or(0) == 0;
+        Ok(self.keep_alive && complete)
+    }
+}
+
+/// Returns `true` for headers written by the writer itself, which are never sent
+/// as default headers.
+fn is_managed(name: &str) -> bool {
+    [
+        "Content-Type",
+        "Content-Length",
+        "Date",
+        "Transfer-Encoding",
+        "Connection",
+    ]
+    .iter()
+    .any(|managed| name.eq_ignore_ascii_case(managed))
+}
+
+/// Writer passing data on in writes of `buf.len()` bytes, unless `buf` is empty.
+struct Coalesce<'w, W> {
+    writer: &'w mut W,
+    buf: &'w mut [u8],
+    /// Bytes buffered in `buf`.
+    len: usize,
+}
+
+impl<'w, W: Write> ErrorType for Coalesce<'w, W> {
+    type Error = W::Error;
+}
+
+impl<'w, W: Write> Write for Coalesce<'w, W> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        let size = self.buf.len();
+        if size == 0 {
+            return self.writer.write(buf).await;
+        }
+        if self.len == 0 && buf.len() >= size {
+            // Whole chunks are written without copying them.
+            let len = buf.len() - buf.len() % size;
+            self.writer.write_all(&buf[..len]).await?;
+            return Ok(len);
+        }
+        let len = buf.len().min(size - self.len);
+        self.buf[self.len..self.len + len].copy_from_slice(&buf[..len]);
+        self.len += len;
+        if self.len == size {
+            self.writer.write_all(self.buf).await?;
+            self.len = 0;
+        }
+        Ok(len)
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        if self.len > 0 {
+            self.writer.write_all(&self.buf[..self.len]).await?;
+            self.len = 0;
+        }
+        self.writer.flush().await
+    }
+}
+
+impl<'w, W: Write> ErrorType for ResponseWriter<'w, W> {
+    type Error = W::Error;
+}
+
+impl<'w, W: Write> Write for ResponseWriter<'w, W> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.write_head().await?;
+        // An empty chunk would end the body.
+        if self.head_only || buf.is_empty() {
+            return Ok(buf.len());
+        }
+        let mut send = buf;
+        if let Some(range) = &mut self.range {
+            let start = range.start.min(buf.len());
+            let end = range.end.min(buf.len());
+            range.start -= start;
+            range.end -= end;
+            send = &buf[start..end];
+        }
+        if let Some(remaining) = &mut self.remaining {
+            let len = send.len().min(*remaining);
+            *remaining -= len;
+            send = &send[..len];
+        }
+        if send.is_empty() {
+            return Ok(buf.len());
+        }
+
+        if self.chunked {
+            let mut size = heapless::String::<20>::new();
+            // Can't overflow, a `usize` has at most 16 hex digits.
+            let _ = core::fmt::write(&mut size, format_args!("{:x}\r\n", send.len()));
+            self.writer.write_all(size.as_bytes()).await?;
+            self.writer.write_all(send).await?;
+            self.writer.write_all(b"\r\n").await?;
+        } else {
+            self.writer.write_all(send).await?;
+        }
+        Ok(buf.len())
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.write_head().await?;
+        self.writer.flush().await
+    }
+}
@@ -0,0 +1,268 @@
+//! Path alias and rewrite rules, see [`Router::rewrite`](crate::Router::rewrite).
+//!
+//! ```
+//! use low_profile::{rewrite::Rewrite, Router};
+//!
+//! let router = Router::<(), _>::new()
+//!     .get("/api/v2/status", || async { "ok" })
+//!     .rewrite(Rewrite::exact("/status", "/api/v2/status"))
+//!     .rewrite(Rewrite::prefix("/api/v1", "/api/v2").redirect());
+//! ```
+//!
+//! Handlers deciding on the new path themselves respond with [`Forward`], the request
+//! is then handed to the route for that path, see [`Router::forwarding`](crate::Router::forwarding).
+
+use core::{convert::Infallible, fmt::Write as _};
+
+use crate::{
+    either::Either,
+    http::StatusCode,
+    request,
+    response::{Redirect, ResponseBody, ResponseWriter, WriteError},
+    route::{Decision, Route},
+    url, IntoResponse, Parts, Read, Request, Response, Write,
+};
+
+/// Maximum amount of forwards of a single request, further forwards are answered
+/// with `500 Internal Server Error`.
+const FORWARDS: usize = 4;
+
+/// A rule mapping an old path to a new one.
+#[derive(Debug, Clone, Copy)]
+pub struct Rewrite {
+    from: &'static str,
+    to: &'static str,
+    prefix: bool,
+    redirect: bool,
+}
+
+impl Rewrite {
+    /// Maps the path `from` to `to`.
+    pub const fn exact(from: &'static str, to: &'static str) -> Self {
+        Self {
+            from,
+            to,
+            prefix: false,
+            redirect: false,
+        }
+    }
+
+    /// Maps all paths starting with the segments of `from` to the same path below `to`,
+    /// e.g. `/api/v1/status` to `/api/v2/status` for the prefix `/api/v1`.
+    ///
+    /// Transparent prefix rewrites build the new path in the request
+    /// [`Arena`](crate::Arena), requests without one or without enough space left are
+    /// redirected instead.
+    pub const fn prefix(from: &'static str, to: &'static str) -> Self {
+        Self {
+            from,
+            to,
+            prefix: true,
+            redirect: false,
+        }
+    }
+
+    /// Answers with `301 Moved Permanently` to the new path,
+    /// instead of serving it transparently.
+    pub const fn redirect(mut self) -> Self {
+        self.redirect = true;
+        self
+    }
+
+    /// Returns the new path as its replaced part and the unchanged rest.
+    fn apply<'p>(&self, path: &'p str) -> Option<(&'static str, &'p str)> {
+        let (to, rest) = if self.prefix {
+            let rest = path.strip_prefix(self.from.trim_end_matches('/'))?;
+            if !rest.is_empty() && !rest.starts_with('/') {
+                return None;
+            }
+            (self.to.trim_end_matches('/'), rest)
+        } else if path == self.from {
+            (self.to, "")
+        } else {
+            return None;
+        };
+        // Rewriting the whole path to the root, e.g. with the prefix `/`.
+        Some(match (to, rest) {
+            ("", "") => ("/", ""),
+            new => new,
+        })
+    }
+}
+
+pub(crate) struct Rewritten<R> {
+    pub(crate) rule: Rewrite,
+    pub(crate) route: R,
+}
+
+impl<S, R: Route<S>> Route<S> for Rewritten<R> {
+    type Response = Either<R::Response, Response<Redirect>>;
+
+    async fn match_request<'a, Body: Read>(
+        &'a self,
+        mut req: Request<'a, Body>,
+        state: &'a S,
+    ) -> Decision<'a, Self::Response, Body> {
+        let Some((to, rest)) = self.rule.apply(req.path()) else {
+            return self.route.match_request(req, state).await.map(Either::Left);
+        };
+
+        let path = if self.rule.redirect {
+            None
+        } else if rest.is_empty() {
+            Some(to)
+        } else {
+            req.parts
+                .arena
+                .and_then(|arena| arena.alloc_fmt(format_args!("{to}{rest}")))
+                .map(|path| &*path)
+        };
+
+        match path {
+            Some(path) => {
+                req.parts.path = path;
+                self.route.match_request(req, state).await.map(Either::Left)
+            }
+            None => {
+                let mut location = heapless::String::new();
+                let written =
+                    write!(location, "{to}{}", url::encode(rest, &url::PATH)).and_then(|()| {
+                        match req.parts.query {
+                            Some(query) => write!(location, "?{query}"),
+                            None => Ok(()),
+                        }
+                    });
+                let response = match written {
+                    Ok(()) => Redirect::new(StatusCode::MOVED_PERMANENTLY, Some(location)),
+                    Err(_) => Redirect::new(StatusCode::URI_TOO_LONG, None),
+                };
+                Decision::Match(Either::Right(response.into_response()))
+            }
+        }
+    }
+}
+
+/// Route answering all requests with `302 Found` to `target`, see
+/// [`Router::redirect`](crate::Router::redirect).
+pub(crate) struct Redirecting {
+    pub(crate) target: &'static str,
+}
+
+impl<S> Route<S> for Redirecting {
+    type Response = Redirect;
+
+    async fn match_request<'a, Body: Read>(
+        &'a self,
+        _req: Request<'a, Body>,
+        _state: &'a S,
+    ) -> Decision<'a, Self::Response, Body> {
+        Decision::Match(Redirect::found(self.target))
+    }
+}
+
+/// Response handing the request on to the route for another path, instead of
+/// answering it, e.g. to serve a custom error page or an alias decided at runtime.
+///
+/// Forwards are only followed by routers with [`Router::forwarding`](crate::Router::forwarding),
+/// otherwise and after too many forwards of one request, it is answered with
+/// `500 Internal Server Error`.
+///
+/// ```
+/// use low_profile::{extract::Path, rewrite::Forward, Either, Router};
+///
+/// async fn led(Path(id): Path<u8>) -> Either<&'static str, Forward> {
+///     match id {
+///         0..=3 => Either::Left("on"),
+///         _ => Either::Right(Forward::to("/missing")),
+///     }
+/// }
+///
+/// let router = Router::<(), _>::new()
+///     .get("/leds/:id", led)
+///     .get("/missing", || async { "No such LED" })
+///     .forwarding();
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Forward {
+    target: &'static str,
+}
+
+impl Forward {
+    /// Forwards to `target`, a path optionally followed by a query.
+    pub const fn to(target: &'static str) -> Self {
+        Self { target }
+    }
+}
+
+impl ResponseBody for Forward {
+    type Error = Infallible;
+
+    fn content_length(&self) -> Option<usize> {
+        Some(0)
+    }
+
+    fn forward_to(&self) -> Option<&'static str> {
+        Some(self.target)
+    }
+
+    async fn write_to<W: Write>(
+        self,
+        _writer: &mut ResponseWriter<'_, W>,
+        _buf: &mut [u8],
+    ) -> Result<(), WriteError<W::Error, Self::Error>> {
+        Ok(())
+    }
+}
+
+impl IntoResponse for Forward {
+    type Body = Self;
+
+    fn into_response(self) -> Response<Self::Body> {
+        (StatusCode::INTERNAL_SERVER_ERROR, Response::new(self)).into_response()
+    }
+}
+
+/// Route following [`Forward`] responses, see [`Router::forwarding`](crate::Router::forwarding).
+pub(crate) struct Forwarding<R> {
+    pub(crate) route: R,
+}
+
+impl<S, R: Route<S>> Route<S> for Forwarding<R> {
+    type Response = Response<<R::Response as IntoResponse>::Body>;
+
+    async fn match_request<'a, Body: Read>(
+        &'a self,
+        req: Request<'a, Body>,
+        state: &'a S,
+    ) -> Decision<'a, Self::Response, Body> {
+        let method = req.parts.method;
+        let headers = req.parts.headers;
+        let arena = req.parts.arena;
+
+        let mut response = match self.route.match_request(req, state).await {
+            Decision::Match(response) => response.into_response(),
+            Decision::NoMatch(req) => return Decision::NoMatch(req),
+        };
+        for _ in 0..FORWARDS {
+            let Some(target) = response.body().forward_to() else {
+                break;
+            };
+            let (path, query) = match target.split_once('?') {
+                Some((path, query)) => (path, Some(query)),
+                None => (target, None),
+            };
+            let mut parts = Parts::new(method, path);
+            parts.query = query;
+            parts.headers = headers;
+            parts.arena = arena;
+
+            // The body was handed to the forwarding handler.
+            let req = Request::from_parts(parts, request::Body::from_slice(&[]));
+            match self.route.match_request(req, state).await {
+                Decision::Match(forwarded) => response = forwarded.into_response(),
+                Decision::NoMatch(_) => break,
+            }
+        }
+        Decision::Match(response)
+    }
+}
@@ -1,9 +1,17 @@
-use core::future::Future;
+use core::{convert::Infallible, fmt::Write as _, future::Future};
 
-use crate::{either::Either, handler, http::StatusCode, IntoResponse, Read, Request, Response};
+use crate::{
+    either::Either,
+    handler,
+    http::StatusCode,
+    method::MethodSet,
+    response::{self, ResponseBody, ResponseWriter, WriteError},
+    IntoResponse, Params, Read, Request, Response, Write,
+};
 
 macro_rules! impl_handler_func {
-    ($name:ident, $method:ident) => {
+    ($(#[$attr:meta])* $name:ident, $method:ident) => {
+        $(#[$attr])*
         pub fn $name<H, S, FuncParams>(handler: H) -> impl Route<S>
         where
             H: handler::HandlerFunction<S, FuncParams>,
@@ -19,7 +27,12 @@ macro_rules! impl_handler_func {
     };
 }
 
-impl_handler_func!(get, GET);
+impl_handler_func!(
+    /// Route calling `handler` for `GET` and `HEAD` requests, the response to `HEAD`
+    /// requests is sent without its body.
+    get,
+    GET
+);
 impl_handler_func!(post, POST);
 impl_handler_func!(put, PUT);
 impl_handler_func!(delete, DELETE);
@@ -29,13 +42,19 @@ impl_handler_func!(connect, CONNECT);
 impl_handler_func!(patch, PATCH);
 impl_handler_func!(trace, TRACE);
 
+/// Outcome of matching a request against a [`Route`].
+///
+/// Unmatched requests are handed back to be matched against the next route.
+// Unmatched requests are handed on by value, there is no allocator to box them.
+#[allow(clippy::large_enum_variant)]
 pub enum Decision<'a, T, R> {
     Match(T),
     NoMatch(Request<'a, R>),
 }
 
 impl<'a, T, R> Decision<'a, T, R> {
-    fn map<F, U>(self, f: F) -> Decision<'a, U, R>
+    /// Maps the response of a matched request.
+    pub fn map<F, U>(self, f: F) -> Decision<'a, U, R>
     where
         F: FnOnce(T) -> U,
     {
@@ -64,27 +83,116 @@ pub trait Route<S> {
     ) -> impl Future<Output = Decision<'a, Self::Response, Body>>;
 }
 
-// impl<S, T: Handler<S>> Route<S> for T {
-//     async fn match_request<'a, Body: Read>(
-//         &self,
-//         req: Request<'a, Body>,
-//         state: S,
-//     ) -> Decision<'a, impl IntoResponse, Body, S> {
-//         Decision::Match(self.call(req, state).await)
-//     }
-// }
-
+/// Answers requests not matched by any route with `404 Not Found`, or with
+/// `405 Method Not Allowed` if a route matched the path but not the method.
 pub struct NotFound;
 
 impl<S> Route<S> for NotFound {
-    type Response = Response<&'static [u8]>;
+    type Response = Response<Unrouted>;
 
     async fn match_request<'a, Body: Read>(
         &'a self,
-        _req: Request<'a, Body>,
+        req: Request<'a, Body>,
         _state: &'a S,
     ) -> Decision<'a, Self::Response, Body> {
-        Decision::Match((StatusCode::NOT_FOUND, "Not Found").into_response())
+        if req.fall_through {
+            return Decision::NoMatch(req);
+        }
+
+        let status = if req.allowed.is_empty() {
+            StatusCode::NOT_FOUND
+        } else {
+            StatusCode::METHOD_NOT_ALLOWED
+        };
+        let body = Unrouted {
+            allowed: req.allowed,
+        };
+        let response = Response::new(body).with_content_type(response::TEXT);
+        Decision::Match((status, response).into_response())
+    }
+}
+
+/// Routes answering requests not matched by `route` with `fallback` instead of [`NotFound`].
+pub struct WithFallback<R, F> {
+    pub(crate) route: R,
+    pub(crate) fallback: F,
+}
+
+impl<S, R: Route<S>, F: Route<S>> Route<S> for WithFallback<R, F> {
+    type Response = Either<R::Response, Either<F::Response, Response<Unrouted>>>;
+
+    async fn match_request<'a, Body: Read>(
+        &'a self,
+        mut req: Request<'a, Body>,
+        state: &'a S,
+    ) -> Decision<'a, Self::Response, Body> {
+        let fall_through = req.fall_through;
+        req.fall_through = true;
+        let mut req = match self.route.match_request(req, state).await {
+            Decision::Match(t) => return Decision::Match(Either::Left(t)),
+            Decision::NoMatch(req) => req,
+        };
+        req.fall_through = fall_through;
+        if fall_through {
+            return Decision::NoMatch(req);
+        }
+
+        let response = if req.allowed.is_empty() {
+            self.fallback
+                .match_request(req, state)
+                .await
+                .map(Either::Left)
+        } else {
+            // `405 Method Not Allowed` takes precedence.
+            NotFound.match_request(req, state).await.map(Either::Right)
+        };
+        response.map(Either::Right)
+    }
+}
+
+/// Body of the [`NotFound`] responses, writing the `Allow` header for `405` responses.
+pub struct Unrouted {
+    allowed: MethodSet,
+}
+
+impl Unrouted {
+    fn text(&self) -> &'static str {
+        if self.allowed.is_empty() {
+            "Not Found"
+        } else {
+            "Method Not Allowed"
+        }
+    }
+}
+
+impl ResponseBody for Unrouted {
+    type Error = Infallible;
+
+    fn content_length(&self) -> Option<usize> {
+        Some(self.text().len())
+    }
+
+    async fn write_to<W: Write>(
+        self,
+        writer: &mut ResponseWriter<'_, W>,
+        _buf: &mut [u8],
+    ) -> Result<(), WriteError<W::Error, Self::Error>> {
+        if !self.allowed.is_empty() {
+            let mut allow = heapless::String::<64>::new();
+            for (i, method) in self.allowed.iter().enumerate() {
+                let separator = if i == 0 { "" } else { ", " };
+                // Can't fail, all standard methods fit.
+                let _ = write!(allow, "{separator}{method}");
+            }
+            writer
+                .write_header("Allow", &allow)
+                .await
+                .map_err(WriteError::Io)?;
+        }
+        writer
+            .write_all(self.text().as_bytes())
+            .await
+            .map_err(WriteError::Io)
     }
 }
 
@@ -101,10 +209,75 @@ impl<S, R: Route<S>> Route<S> for Path<R> {
         req: Request<'a, Body>,
         state: &'a S,
     ) -> Decision<'a, Self::Response, Body> {
-        if self.path == req.path() {
-            self.route.match_request(req, state).await
-        } else {
-            Decision::NoMatch(req)
+        match match_path(self.path, req.path()) {
+            Some(params) => {
+                let mut req = req;
+                req.parts.params = params;
+                self.route.match_request(req, state).await
+            }
+            None => Decision::NoMatch(req),
+        }
+    }
+}
+
+/// Matches `path` against `pattern`, returns the segments captured by `:name` segments
+/// and the remaining path captured by a trailing `*name` segment.
+fn match_path<'a>(pattern: &'static str, path: &'a str) -> Option<Params<'a>> {
+    let mut params = Params::default();
+    let mut rest = Some(path);
+    for expected in pattern.split('/') {
+        let remaining = rest?;
+        if let Some(name) = expected.strip_prefix('*') {
+            params.push(name, remaining).ok()?;
+            return Some(params);
+        }
+
+        let segment = match remaining.split_once('/') {
+            Some((segment, next)) => {
+                rest = Some(next);
+                segment
+            }
+            None => {
+                rest = None;
+                remaining
+            }
+        };
+        match expected.strip_prefix(':') {
+            Some(name) if !segment.is_empty() => params.push(name, segment).ok()?,
+            None if expected == segment => {}
+            _ => return None,
+        }
+    }
+    rest.is_none().then_some(params)
+}
+
+pub struct Nested<R> {
+    pub(crate) prefix: &'static str,
+    pub(crate) route: R,
+}
+
+impl<S, R: Route<S>> Route<S> for Nested<R> {
+    type Response = R::Response;
+
+    async fn match_request<'a, Body: Read>(
+        &'a self,
+        mut req: Request<'a, Body>,
+        state: &'a S,
+    ) -> Decision<'a, Self::Response, Body> {
+        let path = req.path();
+        let rest = match path.strip_prefix(self.prefix.trim_end_matches('/')) {
+            Some("") => "/",
+            Some(rest) if rest.starts_with('/') => rest,
+            _ => return Decision::NoMatch(req),
+        };
+
+        req.parts.path = rest;
+        match self.route.match_request(req, state).await {
+            Decision::Match(response) => Decision::Match(response),
+            Decision::NoMatch(mut req) => {
+                req.parts.path = path;
+                Decision::NoMatch(req)
+            }
         }
     }
 }
@@ -122,9 +295,16 @@ impl<S, R: Route<S>> Route<S> for Method<R> {
         req: Request<'a, Body>,
         state: &'a S,
     ) -> Decision<'a, Self::Response, Body> {
-        if self.method == req.method() {
+        // `GET` routes answer `HEAD` requests as well, only the head is sent.
+        let get = self.method == crate::Method::GET;
+        if self.method == req.method() || get && req.method() == crate::Method::HEAD {
             self.route.match_request(req, state).await
         } else {
+            let mut req = req;
+            req.allowed.insert(self.method);
+            if get {
+                req.allowed.insert(crate::Method::HEAD);
+            }
             Decision::NoMatch(req)
         }
     }
@@ -144,7 +324,7 @@ impl<S, R1: Route<S>, R2: Route<S>> Route<S> for Fallback<R1, R2> {
         state: &'a S,
     ) -> Decision<'a, Self::Response, Body> {
         match self.route.match_request(req, state).await {
-            Decision::Match(t) => return Decision::Match(Either::Left(t)),
+            Decision::Match(t) => Decision::Match(Either::Left(t)),
             Decision::NoMatch(req) => self
                 .fallback
                 .match_request(req, state)
@@ -153,3 +333,32 @@ impl<S, R1: Route<S>, R2: Route<S>> Route<S> for Fallback<R1, R2> {
         }
     }
 }
+
+/// Routes of two routers, the `404 Not Found` fallback of the first one is skipped.
+pub struct Merge<R1, R2> {
+    pub(crate) first: R1,
+    pub(crate) second: R2,
+}
+
+impl<S, R1: Route<S>, R2: Route<S>> Route<S> for Merge<R1, R2> {
+    type Response = Either<R1::Response, R2::Response>;
+
+    async fn match_request<'a, Body: Read>(
+        &'a self,
+        mut req: Request<'a, Body>,
+        state: &'a S,
+    ) -> Decision<'a, Self::Response, Body> {
+        let fall_through = req.fall_through;
+        req.fall_through = true;
+        match self.first.match_request(req, state).await {
+            Decision::Match(t) => Decision::Match(Either::Left(t)),
+            Decision::NoMatch(mut req) => {
+                req.fall_through = fall_through;
+                self.second
+                    .match_request(req, state)
+                    .await
+                    .map(Either::Right)
+            }
+        }
+    }
+}
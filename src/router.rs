@@ -1,13 +1,23 @@
-use core::{marker::PhantomData, mem::MaybeUninit};
+use core::{cell::Cell, future::Future, marker::PhantomData, mem::MaybeUninit};
 
 use crate::{
-    error::ProtocolError,
+    arena::Arena,
+    conditional,
+    either::Either,
+    error::{InvalidUrl, ProtocolError},
     handler,
-    parse::PathAndQuery,
-    request::{record_header_indices, Body, HeaderIndices, Headers, Parts},
+    http::StatusCode,
+    https::{Hsts, HstsRoute},
+    layer::Layer,
+    parse::{Decoded, PathAndQuery},
+    pool::BufferPool,
+    range,
+    request::{record_header_indices, Body, HeaderIndices, Headers, Params, Parts, MAX_PARAMS},
+    response::{DefaultHeadersRoute, ResponseBody, ResponseWriter, WriteError},
+    rewrite::{self, Rewrite},
     route::{self, Route},
-    service::ServiceError,
-    utils, ErrorType, IntoResponse, Method, Read, Request, Service, Write,
+    service::{CloseReason, ConnectionEvent, HeadAction, ServiceConfig, ServiceError},
+    utils, ErrorType, IntoResponse, Method, Read, Request, Response, Service, Write,
 };
 
 mod private {
@@ -18,10 +28,29 @@ mod private {
     pub enum Untouched {}
 }
 
-pub struct Router<RS, R: Route<RS>, S = (), HasRoute = private::Untouched> {
+/// Buffer sizes used while serving a request.
+///
+/// - `REQUEST`: buffer holding the request head, body bytes received together
+///   with the head are kept in it as well. The part not filled by the request is
+///   reused as the request scoped [`Arena`](crate::Arena) while handling it.
+/// - `HEADERS`: maximum amount of request headers.
+/// - `BODY`: scratch buffer handed to the [`ResponseBody`](crate::response::ResponseBody),
+///   used to relay response bodies which are not available as a slice.
+///
+/// All buffers live on the stack of the [`Service::serve`] future, the request and
+/// relay buffers can be taken from a [`BufferPool`] with [`Router::serve_pooled`] instead.
+#[derive(Debug, Clone, Copy)]
+pub struct Buffers<
+    const REQUEST: usize = 2048,
+    const HEADERS: usize = 100,
+    const BODY: usize = 1024,
+>;
+
+pub struct Router<RS, R: Route<RS>, S = (), HasRoute = private::Untouched, B = Buffers> {
     state: S,
     route: R,
-    _priv: PhantomData<(RS, HasRoute)>,
+    config: ServiceConfig,
+    _priv: PhantomData<(RS, HasRoute, B)>,
 }
 
 impl<RS> Router<RS, route::NotFound> {
@@ -29,6 +58,7 @@ impl<RS> Router<RS, route::NotFound> {
         Self {
             state: (),
             route: route::NotFound,
+            config: ServiceConfig::new(),
             _priv: Default::default(),
         }
     }
@@ -40,49 +70,105 @@ impl<RS> Default for Router<RS, route::NotFound> {
     }
 }
 
-impl<R, S> Router<(), R, S, private::Untouched>
+impl<R, S, B> Router<(), R, S, private::Untouched, B>
 where
     R: Route<()>,
 {
-    pub fn with_state<S2>(self, state: S2) -> Router<S2, R, S2, private::HasAnyState>
+    pub fn with_state<S2>(self, state: S2) -> Router<S2, R, S2, private::HasAnyState, B>
     where
         R: Route<S2>,
     {
         Router {
             route: self.route,
             state,
+            config: self.config,
             _priv: Default::default(),
         }
     }
+
+    /// Sets the state created by `init`, for state requiring async setup like reading
+    /// the configuration from flash or calibrating sensors.
+    ///
+    /// The state is created before the router serves the first request, an error of
+    /// `init` is returned instead of the router.
+    ///
+    /// ```
+    /// use low_profile::{extract::State, Route, Router};
+    ///
+    /// #[derive(Clone)]
+    /// struct Calibration {
+    ///     offset: i16,
+    /// }
+    ///
+    /// #[derive(Debug)]
+    /// struct FlashError;
+    ///
+    /// async fn load_calibration() -> Result<Calibration, FlashError> {
+    ///     Ok(Calibration { offset: -3 })
+    /// }
+    ///
+    /// async fn offset(State(calibration): State<Calibration>) -> &'static str {
+    ///     if calibration.offset < 0 { "negative" } else { "positive" }
+    /// }
+    ///
+    /// async fn app() -> Result<Router<Calibration, impl Route<Calibration>, Calibration, impl Sized>, FlashError> {
+    ///     Router::new()
+    ///         .get("/offset", offset)
+    ///         .with_state_async(load_calibration())
+    ///         .await
+    /// }
+    /// ```
+    pub async fn with_state_async<S2, E>(
+        self,
+        init: impl Future<Output = Result<S2, E>>,
+    ) -> Result<Router<S2, R, S2, private::HasAnyState, B>, E>
+    where
+        R: Route<S2>,
+    {
+        Ok(self.with_state(init.await?))
+    }
 }
 
-impl<RS, R, S> Router<RS, R, S, private::HasAnyState>
+impl<RS, R, S, B> Router<RS, R, S, private::HasAnyState, B>
 where
     R: Route<RS>,
 {
-    pub fn with_state<S2>(self, state: S2) -> Router<S2, R, S2, private::HasAnyState>
+    pub fn with_state<S2>(self, state: S2) -> Router<S2, R, S2, private::HasAnyState, B>
     where
         R: Route<S2>,
     {
         Router {
             route: self.route,
             state,
+            config: self.config,
             _priv: Default::default(),
         }
     }
+
+    /// Sets the state created by `init`, see [`Router::with_state_async`].
+    pub async fn with_state_async<S2, E>(
+        self,
+        init: impl Future<Output = Result<S2, E>>,
+    ) -> Result<Router<S2, R, S2, private::HasAnyState, B>, E>
+    where
+        R: Route<S2>,
+    {
+        Ok(self.with_state(init.await?))
+    }
 }
 
 macro_rules! impl_method {
-    ($method:ident) => {
-        impl<RS, R, S, HasRoute> Router<RS, R, S, HasRoute>
+    ($(#[$attr:meta])* $method:ident) => {
+        impl<RS, R, S, HasRoute, B> Router<RS, R, S, HasRoute, B>
         where
             R: Route<RS>,
         {
+            $(#[$attr])*
             pub fn $method<H, X>(
                 self,
                 path: &'static str,
                 handler: H,
-            ) -> Router<RS, impl Route<RS>, S, private::HasAnyState>
+            ) -> Router<RS, impl Route<RS>, S, private::HasAnyState, B>
             where
                 H: handler::HandlerFunction<RS, X>,
             {
@@ -92,7 +178,13 @@ macro_rules! impl_method {
     };
 }
 
-impl_method!(get);
+impl_method!(
+    /// Routes `GET` requests for `path` to `handler`.
+    ///
+    /// `HEAD` requests are routed to `handler` as well, unless matched by a former
+    /// route, the response is sent without its body.
+    get
+);
 impl_method!(post);
 impl_method!(put);
 impl_method!(delete);
@@ -102,130 +194,1038 @@ impl_method!(connect);
 impl_method!(patch);
 impl_method!(trace);
 
-impl<RS, R, S, HasRoute> Router<RS, R, S, HasRoute>
+impl<RS, R, S, HasRoute, B> Router<RS, R, S, HasRoute, B>
 where
     R: Route<RS>,
 {
+    /// Routes requests for `path` to `route`.
+    ///
+    /// Segments of `path` starting with `:` match any non-empty segment, which is
+    /// captured under the following name. A last segment starting with `*` matches
+    /// the remaining path, including further `/`, which might be empty.
+    /// See [`Path`](crate::extract::Path) to extract the captures.
+    ///
+    /// Request paths are percent-decoded before matching, `/files/my file.txt`
+    /// matches a request for `/files/my%20file.txt`. A `%2F` is matched like a `/`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `path` captures more than 4 segments or has a `*` segment
+    /// which is not the last one.
     pub fn route<T: Route<RS>>(
         self,
         path: &'static str,
         route: T,
-    ) -> Router<RS, impl Route<RS>, S, private::HasAnyState> {
+    ) -> Router<RS, impl Route<RS>, S, private::HasAnyState, B> {
+        let captures = path.split('/').filter(|s| s.starts_with([':', '*']));
+        assert!(
+            captures.count() <= MAX_PARAMS,
+            "too many path parameters in {path}"
+        );
+        assert!(
+            path.rsplit('/').skip(1).all(|s| !s.starts_with('*')),
+            "wildcard before the last segment in {path}"
+        );
         Router {
             route: route::Fallback {
                 route: route::Path { path, route },
                 fallback: self.route,
             },
             state: self.state,
+            config: self.config,
+            _priv: Default::default(),
+        }
+    }
+
+    /// Routes requests for `prefix` and paths below it to the routes of `router`,
+    /// with `prefix` stripped from the path.
+    ///
+    /// Requests below `prefix` not matched by `router` are answered with
+    /// `404 Not Found`. The state, [`ServiceConfig`] and [`Buffers`] of this router
+    /// are used for the nested routes.
+    ///
+    /// ```
+    /// use low_profile::Router;
+    ///
+    /// let api = Router::new()
+    ///     .get("/status", || async { "ok" })
+    ///     .get("/leds", || async { "[]" });
+    ///
+    /// // Serves `/api/status` and `/api/leds`.
+    /// let router = Router::<(), _>::new()
+    ///     .get("/", || async { "index" })
+    ///     .nest("/api", api);
+    /// ```
+    pub fn nest<R2: Route<RS>, HasRoute2, B2>(
+        self,
+        prefix: &'static str,
+        router: Router<RS, R2, (), HasRoute2, B2>,
+    ) -> Router<RS, impl Route<RS>, S, private::HasAnyState, B> {
+        Router {
+            route: route::Fallback {
+                route: route::Nested {
+                    prefix,
+                    route: router.route,
+                },
+                fallback: self.route,
+            },
+            state: self.state,
+            config: self.config,
+            _priv: Default::default(),
+        }
+    }
+
+    /// Combines the routes of this router and `router`, e.g. built by separate modules.
+    ///
+    /// Requests are matched against the routes of this router first, requests not
+    /// matched by either router are answered by the fallback of `router`, see
+    /// [`fallback`](Self::fallback). The state, [`ServiceConfig`] and [`Buffers`] of this router are used.
+    ///
+    /// ```
+    /// mod leds {
+    ///     use low_profile::{Route, Router};
+    ///
+    ///     pub fn router() -> Router<(), impl Route<()>, (), impl Sized> {
+    ///         Router::new().get("/leds", || async { "[]" })
+    ///     }
+    /// }
+    ///
+    /// mod network {
+    ///     use low_profile::{Route, Router};
+    ///
+    ///     pub fn router() -> Router<(), impl Route<()>, (), impl Sized> {
+    ///         Router::new().get("/network", || async { "{}" })
+    ///     }
+    /// }
+    ///
+    /// let router = leds::router().merge(network::router());
+    /// ```
+    pub fn merge<R2: Route<RS>, HasRoute2, B2>(
+        self,
+        router: Router<RS, R2, (), HasRoute2, B2>,
+    ) -> Router<RS, impl Route<RS>, S, private::HasAnyState, B> {
+        Router {
+            route: route::Merge {
+                first: self.route,
+                second: router.route,
+            },
+            state: self.state,
+            config: self.config,
+            _priv: Default::default(),
+        }
+    }
+
+    /// Passes the router to `configure`, which may register further routes.
+    ///
+    /// This allows compiling routes in or out with `#[cfg]` attributes without
+    /// breaking up the builder chain, e.g. with `#[cfg(feature = "debug-endpoints")]`
+    /// for endpoints which must never ship in release firmware. The routes, and their
+    /// handlers if gated as well, are not part of builds with the attribute disabled.
+    ///
+    /// ```
+    /// use low_profile::Router;
+    ///
+    /// #[cfg(debug_assertions)]
+    /// async fn heap() -> &'static str {
+    ///     "free: 1234"
+    /// }
+    ///
+    /// let router = Router::<(), _>::new()
+    ///     .get("/", || async { "index" })
+    ///     .configure(|router| {
+    ///         #[cfg(debug_assertions)]
+    ///         let router = router.get("/debug/heap", heap);
+    ///         router
+    ///     })
+    ///     .get("/status", || async { "ok" });
+    /// ```
+    pub fn configure<F, R2, HasRoute2>(self, configure: F) -> Router<RS, R2, S, HasRoute2, B>
+    where
+        F: FnOnce(Self) -> Router<RS, R2, S, HasRoute2, B>,
+        R2: Route<RS>,
+    {
+        configure(self)
+    }
+
+    /// Calls `handler` for requests not matched by the routes registered so far,
+    /// instead of answering with `404 Not Found`.
+    ///
+    /// Requests matching the path but not the method of a route are still answered
+    /// with `405 Method Not Allowed`.
+    ///
+    /// ```
+    /// use low_profile::{http::StatusCode, IntoResponse, Router};
+    ///
+    /// async fn not_found() -> impl IntoResponse {
+    ///     (StatusCode::NOT_FOUND, "Nothing to see here, try /status")
+    /// }
+    ///
+    /// let router = Router::<(), _>::new()
+    ///     .get("/status", || async { "ok" })
+    ///     .fallback(not_found);
+    /// ```
+    pub fn fallback<H, X>(
+        self,
+        handler: H,
+    ) -> Router<RS, impl Route<RS>, S, private::HasAnyState, B>
+    where
+        H: handler::HandlerFunction<RS, X>,
+    {
+        Router {
+            route: route::WithFallback {
+                route: self.route,
+                fallback: handler::HandlerFunctionHandlerAdapter {
+                    handler,
+                    _params: Default::default(),
+                },
+            },
+            state: self.state,
+            config: self.config,
+            _priv: Default::default(),
+        }
+    }
+
+    /// Applies `rule` to requests before they are matched against the routes
+    /// registered so far, see [`rewrite`](crate::rewrite).
+    pub fn rewrite(self, rule: Rewrite) -> Router<RS, impl Route<RS>, S, private::HasAnyState, B> {
+        Router {
+            route: rewrite::Rewritten {
+                rule,
+                route: self.route,
+            },
+            state: self.state,
+            config: self.config,
+            _priv: Default::default(),
+        }
+    }
+
+    /// Follows [`Forward`](crate::rewrite::Forward) responses of the routes registered
+    /// so far, handing the request on to the route for the path forwarded to.
+    ///
+    /// The forwarded request keeps the method and headers, its body is empty as the
+    /// body was handed to the forwarding handler.
+    pub fn forwarding(self) -> Router<RS, impl Route<RS>, S, private::HasAnyState, B> {
+        Router {
+            route: rewrite::Forwarding { route: self.route },
+            state: self.state,
+            config: self.config,
+            _priv: Default::default(),
+        }
+    }
+
+    /// Answers requests for `path` with any method with `302 Found` to `target`,
+    /// which may be a path or an absolute URL.
+    ///
+    /// Use [`Redirect`](crate::response::Redirect) in a handler for other status
+    /// codes or computed locations.
+    ///
+    /// ```
+    /// use low_profile::Router;
+    ///
+    /// let router = Router::<(), _>::new()
+    ///     .get("/portal", || async { "Welcome" })
+    ///     .redirect("/generate_204", "http://192.168.4.1/portal");
+    /// ```
+    pub fn redirect(
+        self,
+        path: &'static str,
+        target: &'static str,
+    ) -> Router<RS, impl Route<RS>, S, private::HasAnyState, B> {
+        self.route(path, rewrite::Redirecting { target })
+    }
+
+    /// Sends an `ETag` derived from the body with successful responses to `GET` and
+    /// `HEAD` requests, and answers requests with a matching `If-None-Match` with
+    /// `304 Not Modified`, see [`conditional`](crate::conditional).
+    ///
+    /// Only bodies available as a slice are tagged, see
+    /// [`ResponseBody::as_bytes`](crate::response::ResponseBody::as_bytes).
+    /// Routes registered so far are covered, their handlers still run for every request.
+    pub fn etag(self) -> Router<RS, impl Route<RS>, S, private::HasAnyState, B> {
+        Router {
+            route: conditional::ETagRoute { route: self.route },
+            state: self.state,
+            config: self.config,
+            _priv: Default::default(),
+        }
+    }
+
+    /// Answers `GET` requests with a `Range` header with `206 Partial Content` and the
+    /// requested bytes, or `416 Range Not Satisfiable` if they lie outside the body,
+    /// see [`range`](crate::range).
+    ///
+    /// Only successful responses with a known
+    /// [`content_length`](crate::response::ResponseBody::content_length) are ranged,
+    /// they advertise it with `Accept-Ranges: bytes`. A single range is supported, other
+    /// requests are answered with the whole body, as are requests with an `If-Range`
+    /// not matching the entity tag [`etag`](Self::etag) derives from the body.
+    /// Call this after `etag` so the tag is derived from the whole body.
+    pub fn accept_ranges(self) -> Router<RS, impl Route<RS>, S, private::HasAnyState, B> {
+        Router {
+            route: range::RangeRoute { route: self.route },
+            state: self.state,
+            config: self.config,
+            _priv: Default::default(),
+        }
+    }
+
+    /// Sends a `Strict-Transport-Security` header with every response,
+    /// only use this for routers served behind TLS, see [`https`](crate::https).
+    pub fn hsts(self, hsts: Hsts) -> Router<RS, impl Route<RS>, S, private::HasAnyState, B> {
+        Router {
+            route: HstsRoute {
+                hsts,
+                route: self.route,
+            },
+            state: self.state,
+            config: self.config,
+            _priv: Default::default(),
+        }
+    }
+
+    /// Sends `headers` with the responses of the routes registered so far, including
+    /// the `404 Not Found` fallback, unless a response writes a header of the same name,
+    /// see [`Response::with_default_headers`].
+    ///
+    /// Together with [`nest`](Self::nest) this sets defaults for all routes below a prefix:
+    ///
+    /// ```
+    /// use low_profile::Router;
+    ///
+    /// let api = Router::new()
+    ///     .get("/status", || async { "ok" })
+    ///     .default_headers(&[("Cache-Control", "no-store")]);
+    ///
+    /// let assets = Router::new()
+    ///     .get("/logo.svg", || async { "<svg/>" })
+    ///     .default_headers(&[("Cache-Control", "max-age=86400")]);
+    ///
+    /// let router = Router::<(), _>::new()
+    ///     .nest("/api", api)
+    ///     .nest("/assets", assets);
+    /// ```
+    pub fn default_headers(
+        self,
+        headers: &'static [(&'static str, &'static str)],
+    ) -> Router<RS, impl Route<RS>, S, private::HasAnyState, B> {
+        Router {
+            route: DefaultHeadersRoute {
+                headers,
+                route: self.route,
+            },
+            state: self.state,
+            config: self.config,
+            _priv: Default::default(),
+        }
+    }
+
+    /// Wraps the routes registered so far, including the `404 Not Found` fallback,
+    /// with `layer`, see [`layer`](crate::layer).
+    ///
+    /// Layers applied later see the requests first and the responses last.
+    pub fn layer<L: Layer<RS, R>>(
+        self,
+        layer: L,
+    ) -> Router<RS, impl Route<RS>, S, private::HasAnyState, B> {
+        Router {
+            route: layer.layer(self.route),
+            state: self.state,
+            config: self.config,
+            _priv: Default::default(),
+        }
+    }
+
+    /// Replaces the [`ServiceConfig`] used when serving requests.
+    pub fn with_config(mut self, config: ServiceConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Changes the buffer sizes used when serving requests, see [`Buffers`].
+    pub fn buffers<const REQUEST: usize, const HEADERS: usize, const BODY: usize>(
+        self,
+    ) -> Router<RS, R, S, HasRoute, Buffers<REQUEST, HEADERS, BODY>> {
+        Router {
+            route: self.route,
+            state: self.state,
+            config: self.config,
             _priv: Default::default(),
         }
     }
 }
 
-impl<R: Route<S> + 'static, S, HasRoute> Service for Router<S, R, S, HasRoute> {
-    type BodyError = <<R::Response as IntoResponse>::Body as ErrorType>::Error;
+impl<R, S, HasRoute, B> Router<S, R, S, HasRoute, B>
+where
+    R: Route<S>,
+{
+    /// Runs `request` through the routes without any I/O and returns the response.
+    ///
+    /// This serves synthetic requests, e.g. built with [`Parts::new`] and
+    /// [`Body::from_slice`], the same way as requests received by [`Service::serve`].
+    pub async fn dispatch<'a, Re: Read>(
+        &'a self,
+        request: Request<'a, Re>,
+    ) -> Response<<R::Response as IntoResponse>::Body> {
+        self.route
+            .match_request(request, &self.state)
+            .await
+            // It is safe to unwrap here, we always have a `NotFound` fallback handler.
+            .unwrap()
+            .into_response()
+    }
+}
+
+impl<R, S, HasRoute, const REQUEST: usize, const HEADERS: usize, const BODY: usize> Service
+    for Router<S, R, S, HasRoute, Buffers<REQUEST, HEADERS, BODY>>
+where
+    R: Route<S>,
+{
+    type BodyError = <<R::Response as IntoResponse>::Body as ResponseBody>::Error;
 
     async fn serve<Re: Read, Wr: Write<Error = Re::Error>>(
         &self,
-        mut reader: Re,
-        mut writer: Wr,
+        reader: Re,
+        writer: Wr,
     ) -> Result<(), ServiceError<Re::Error, Self::BodyError>> {
-        // TODO: buf size, optinally make the buffer an arg
-        let mut buf = [0u8; 2048];
+        self.serve_inner(reader, writer, None).await
+    }
+}
 
-        const MAX_HEADERS: usize = 100;
+impl<R, S, HasRoute, const REQUEST: usize, const HEADERS: usize, const BODY: usize>
+    Router<S, R, S, HasRoute, Buffers<REQUEST, HEADERS, BODY>>
+where
+    R: Route<S>,
+{
+    /// Size in bytes of the future returned by [`Service::serve`] for the reader `Re`
+    /// and writer `Wr`.
+    ///
+    /// The future contains all request buffers, see [`Buffers`], and usually makes up
+    /// most of the memory required to serve a connection.
+    pub fn serve_future_size<Re: Read, Wr: Write<Error = Re::Error>>(&self) -> usize {
+        utils::return_size(&|reader: Re, writer: Wr| self.serve(reader, writer))
+    }
 
-        let mut headers_indices: [MaybeUninit<HeaderIndices>; MAX_HEADERS] = unsafe {
+    /// Fails compilation if the future returned by [`Service::serve`] for the reader `Re`
+    /// and writer `Wr` is larger than `MAX` bytes.
+    ///
+    /// The assertion is evaluated when the call is monomorphized,
+    /// it is reported by `cargo build` but not by `cargo check`.
+    pub fn assert_serve_future_size<Re: Read, Wr: Write<Error = Re::Error>, const MAX: usize>(
+        &self,
+    ) {
+        utils::assert_return_size::<_, _, _, _, MAX>(&|reader: Re, writer: Wr| {
+            self.serve(reader, writer)
+        })
+    }
+
+    /// Serves a connection like [`Service::serve`], providing a request scoped
+    /// [`Arena`] allocating from `arena` instead of the unused request buffer.
+    pub async fn serve_with_arena<Re: Read, Wr: Write<Error = Re::Error>>(
+        &self,
+        reader: Re,
+        writer: Wr,
+        arena: &mut [u8],
+    ) -> Result<(), ServiceError<Re::Error, <Self as Service>::BodyError>> {
+        self.serve_inner(reader, writer, Some(arena)).await
+    }
+
+    /// Serves a connection like [`Service::serve`], with the request and relay buffers
+    /// taken from `pool` instead of being part of the returned future, see
+    /// [`pool`](crate::pool).
+    ///
+    /// The last `BODY` bytes of the pool buffer relay response bodies, the rest
+    /// receives the requests. Connections arriving while all buffers are in use are
+    /// answered with `503 Service Unavailable` and closed, after reading the head of
+    /// their first request. Unread data would make the connection be reset when
+    /// closed, before the client read the response.
+    ///
+    /// Fails compilation if the pool buffers are not larger than `BODY`, the assertion
+    /// is reported by `cargo build` but not by `cargo check`.
+    pub async fn serve_pooled<
+        Re: Read,
+        Wr: Write<Error = Re::Error>,
+        const COUNT: usize,
+        const SIZE: usize,
+    >(
+        &self,
+        reader: Re,
+        writer: Wr,
+        pool: &BufferPool<COUNT, SIZE>,
+    ) -> Result<(), ServiceError<Re::Error, <Self as Service>::BodyError>> {
+        struct Assert<const SIZE: usize, const BODY: usize>;
+
+        impl<const SIZE: usize, const BODY: usize> Assert<SIZE, BODY> {
+            const OK: () = assert!(
+                SIZE > BODY,
+                "pool buffers must be larger than the relay buffer"
+            );
+        }
+
+        #[allow(clippy::let_unit_value)]
+        let () = Assert::<SIZE, BODY>::OK;
+
+        let mut connection = Connection::open(&self.config);
+        let Some(mut buffer) = pool.take() else {
+            let mut reader = reader;
+            if let Err(err) = skip_head(&mut reader, SIZE - BODY).await {
+                connection.reason = CloseReason::Error;
+                return Err(ServiceError::Io(err));
+            }
+            let response = (StatusCode::SERVICE_UNAVAILABLE, "Service Unavailable").into_response();
+            let result = write_response::<_, _, 0>(writer, response, false, &self.config).await;
+            connection.reason = match result {
+                Ok(_) => CloseReason::Done,
+                Err(_) => CloseReason::Error,
+            };
+            return result.map(|_| ()).map_err(|err| match err {
+                ServiceError::ProtocolError(err) => ServiceError::ProtocolError(err),
+                ServiceError::Io(err) => ServiceError::Io(err),
+                ServiceError::Body(err) => match err {},
+            });
+        };
+
+        let (buf, relay) = buffer.split_at_mut(SIZE - BODY);
+        let result = self.serve_requests(reader, writer, None, buf, relay).await;
+        connection.reason = match result {
+            Ok(reason) => reason,
+            Err(_) => CloseReason::Error,
+        };
+        result.map(|_| ())
+    }
+
+    async fn serve_inner<Re: Read, Wr: Write<Error = Re::Error>>(
+        &self,
+        reader: Re,
+        writer: Wr,
+        arena: Option<&mut [u8]>,
+    ) -> Result<(), ServiceError<Re::Error, <Self as Service>::BodyError>> {
+        let mut connection = Connection::open(&self.config);
+        let mut buf = [0u8; REQUEST];
+        let mut relay = [0u8; BODY];
+        let result = self
+            .serve_requests(reader, writer, arena, &mut buf, &mut relay)
+            .await;
+        connection.reason = match result {
+            Ok(reason) => reason,
+            Err(_) => CloseReason::Error,
+        };
+        result.map(|_| ())
+    }
+
+    /// Serves requests until the connection is closed, returns why it was closed.
+    ///
+    /// `buf` receives the requests, `relay` is handed to the response bodies.
+    async fn serve_requests<Re: Read, Wr: Write<Error = Re::Error>>(
+        &self,
+        mut reader: Re,
+        mut writer: Wr,
+        mut arena: Option<&mut [u8]>,
+        buf: &mut [u8],
+        relay: &mut [u8],
+    ) -> Result<CloseReason, ServiceError<Re::Error, <Self as Service>::BodyError>> {
+        let mut headers_indices: [MaybeUninit<HeaderIndices>; HEADERS] = unsafe {
             // SAFETY: We can go safely from MaybeUninit array to array of MaybeUninit
             MaybeUninit::uninit().assume_init()
         };
 
+        // Amount of bytes received for the current request, pipelined requests
+        // may already be partially or fully received.
         let mut pos = 0;
-        let (method, path, headers, body_start) = loop {
-            // TODO check if buffer is full first
-            let read = reader
-                .read(&mut buf[pos..])
+        // Amount of requests of the connection, including the current one.
+        let mut requests = 0;
+        loop {
+            let mut _activity = (pos > 0).then(|| Activity::start(&self.config));
+            let head = loop {
+                if pos > 0 {
+                    let mut headers: [MaybeUninit<httparse::Header<'_>>; HEADERS] =
+                        unsafe { MaybeUninit::uninit().assume_init() };
+
+                    match parse_head(&buf[..pos], &mut headers, &mut headers_indices) {
+                        Ok(Some(head)) => break head,
+                        Ok(None) => {}
+                        Err(err) => return Err(ServiceError::ProtocolError(err)),
+                    }
+                }
+
+                if pos == buf.len() {
+                    let status = StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE;
+                    let err = ProtocolError::HeadTooLarge;
+                    return Err(reject(&mut writer, status, err, &self.config, relay).await);
+                }
+                let read = reader
+                    .read(&mut buf[pos..])
+                    .await
+                    .map_err(ServiceError::Io)?;
+                if read == 0 {
+                    return Ok(CloseReason::ClientClosed);
+                }
+                if _activity.is_none() {
+                    _activity = Some(Activity::start(&self.config));
+                }
+                pos += read;
+            };
+
+            // The part of the buffer not filled by the request is used as arena,
+            // unless an arena was passed explicitly.
+            let (received, spare) = buf.split_at_mut(pos);
+            let target = match head.decode_target(received) {
+                Ok(target) => target,
+                Err(err) => {
+                    let err = ProtocolError::InvalidUrl(err);
+                    let status = StatusCode::BAD_REQUEST;
+                    return Err(reject(&mut writer, status, err, &self.config, relay).await);
+                }
+            };
+            let arena = Arena::new(arena.as_deref_mut().unwrap_or(spare));
+            let parts = request_parts(&head, target, received, Some(&arena))
+                .map_err(ServiceError::ProtocolError)?;
+
+            let head_only = parts.method == Method::HEAD;
+            let version = head.version;
+            let transfer_encoding = parts.headers.get_first("Transfer-Encoding");
+            let chunked = transfer_encoding.is_some_and(|value| utils::has_token(value, "chunked"));
+            let framing = content_length(&parts.headers, transfer_encoding.is_some());
+            let content_length = framing.unwrap_or(0);
+            requests += 1;
+
+            let mut keep_alive = self.config.keep_alive
+                && head.version == 1
+                && !parts.headers.contains_token("Connection", "close")
+                // The end of a chunked body within the request buffer is not tracked.
+                && transfer_encoding.is_none()
+                && requests < self.config.max_requests_per_connection;
+
+            let action = match framing {
+                Ok(_) => self.config.on_head.map(|hook| hook(&parts)),
+                // The end of a body with ambiguous framing is unknown, the next request
+                // read from the connection could start within the body.
+                Err(()) => Some(HeadAction::Reject(
+                    (StatusCode::BAD_REQUEST, "Bad Request").into_response(),
+                )),
+            };
+            let rejection = match action {
+                Some(HeadAction::Reject(response)) => Some(response),
+                Some(HeadAction::Close) => {
+                    keep_alive = false;
+                    None
+                }
+                Some(HeadAction::Continue) | None => None,
+            };
+            let rejection = rejection.or_else(|| {
+                self.config
+                    .max_content_length
+                    .is_some_and(|max| content_length > max)
+                    .then(|| (StatusCode::PAYLOAD_TOO_LARGE, "Payload Too Large").into_response())
+            });
+
+            if let Some(response) = rejection {
+                // Reject before routing, the body is never read.
+                return write_response_in(
+                    &mut writer,
+                    response,
+                    false,
+                    head_only,
+                    version,
+                    &self.config,
+                    relay,
+                )
                 .await
-                .map_err(ServiceError::Io)?;
-            if read == 0 {
-                // TODO
-                return Ok(());
+                .map(|_| {
+                    self.config.event(ConnectionEvent::RequestFinished);
+                    CloseReason::Done
+                })
+                .map_err(|err| match err {
+                    ServiceError::ProtocolError(err) => ServiceError::ProtocolError(err),
+                    ServiceError::Io(err) => ServiceError::Io(err),
+                    ServiceError::Body(err) => match err {},
+                });
             }
-            pos += read;
-
-            let mut headers: [MaybeUninit<httparse::Header<'_>>; MAX_HEADERS] =
-                unsafe { MaybeUninit::uninit().assume_init() };
-            let mut req = httparse::Request::new(&mut []);
 
-            match req.parse_with_uninit_headers(&buf, &mut headers) {
-                Ok(httparse::Status::Complete(len)) => {
-                    record_header_indices(&buf, req.headers, &mut headers_indices);
+            let head_len = head.len;
+            let body_read = Cell::new(0);
+            let counted = Counted {
+                reader: &mut reader,
+                read: &body_read,
+            };
+            let (body, buffered) = if chunked {
+                let body = Body::chunked(&received[head_len..], counted)
+                    .limit(self.config.max_content_length.unwrap_or(usize::MAX));
+                (body, pos - head_len)
+            } else {
+                let buffered = content_length.min(pos - head_len);
+                let body = Body::new(
+                    content_length,
+                    &received[head_len..head_len + buffered],
+                    counted,
+                );
+                (body, buffered)
+            };
+            let request = Request::from_parts(parts, body);
 
-                    let headers = unsafe {
-                        MaybeUninit::slice_assume_init_ref(&headers_indices[..req.headers.len()])
-                    };
+            let response = self.dispatch(request).await;
 
-                    // TODO: I think these unwraps cant happen, double check
-                    break (req.method.unwrap(), req.path.unwrap(), headers, len);
+            let write = write_response_in(
+                &mut writer,
+                response,
+                keep_alive,
+                head_only,
+                version,
+                &self.config,
+                relay,
+            );
+            let watch = self.config.cancel_on_close
+                && !chunked
+                && buffered + body_read.get() == content_length;
+            let keep_alive = if watch {
+                // Bytes of pipelined requests received while writing the response.
+                let received = Cell::new(0);
+                let closed = watch_close(&mut reader, &mut buf[pos..], &received);
+                let result = utils::select(write, closed).await;
+                pos += received.get();
+                match result {
+                    Either::Left(result) => result?,
+                    Either::Right(Ok(())) => return Ok(CloseReason::ClientClosed),
+                    Either::Right(Err(err)) => return Err(ServiceError::Io(err)),
                 }
-                Ok(httparse::Status::Partial) => {
-                    continue;
+            } else {
+                write.await?
+            };
+            self.config.event(ConnectionEvent::RequestFinished);
+            if !keep_alive {
+                return Ok(CloseReason::Done);
+            }
+
+            // Move the pipelined bytes following the request to the start of the buffer
+            // and skip the part of the body the handler did not read.
+            buf.copy_within(head_len + buffered..pos, 0);
+            pos -= head_len + buffered;
+
+            let mut unread = content_length - buffered - body_read.get();
+            while unread > 0 {
+                let len = unread.min(buf.len());
+                let read = reader
+                    .read(&mut buf[..len])
+                    .await
+                    .map_err(ServiceError::Io)?;
+                if read == 0 {
+                    return Ok(CloseReason::ClientClosed);
                 }
-                Err(err) => return Err(ServiceError::ProtocolError(ProtocolError::Parser(err))),
+                unread -= read;
             }
-        };
+        }
+    }
+}
 
-        let paq = PathAndQuery::parse(path)
-            .map_err(ProtocolError::InvalidUrl)
-            .map_err(ServiceError::ProtocolError)?;
-        let parts = Parts {
-            method: Method::new(method)
-                .map_err(ProtocolError::InvalidMethod)
-                .map_err(ServiceError::ProtocolError)?,
-            path: paq.path(),
-            query: paq.query(),
-            headers: Headers { headers, buf: &buf },
-        };
+// The functions below are intentionally not generic over the routes, state or body
+// and are only compiled once, instead of once per `Router`.
 
-        let content_length = parts
-            .headers
-            .get_first("Content-Length")
-            .and_then(|value| value.parse::<usize>().ok())
-            .unwrap_or(0);
+/// Calls the [`ServiceConfig::on_idle`] hook when dropped.
+struct Activity(Option<fn()>);
 
-        let body = Body::new(content_length, &buf[body_start..pos], reader);
-        let request = Request::from_parts(parts, body);
+impl Activity {
+    fn start(config: &ServiceConfig) -> Self {
+        if let Some(on_active) = config.on_active {
+            on_active();
+        }
+        config.event(ConnectionEvent::RequestStarted);
+        Self(config.on_idle)
+    }
+}
 
-        let response = self
-            .route
-            .match_request(request, &self.state)
-            .await
-            // It is safe to unwrap here, we always have a `NotFound` fallback handler.
-            .unwrap()
-            .into_response();
+/// Reports the [`ConnectionEvent::Closed`] event when dropped.
+struct Connection {
+    on_event: Option<fn(ConnectionEvent)>,
+    reason: CloseReason,
+}
 
-        use utils::{WriteExt, WriteFmtError};
-        write!(writer, "HTTP/1.1 {}\r\n", response.status_code())
-            .await
-            .map_err(|err| match err {
-                WriteFmtError::FmtError => unreachable!("internal format buffer too small"),
-                WriteFmtError::Other(err) => ServiceError::Io(err),
-            })?;
-        writer.write_all(b"\r\n").await.map_err(ServiceError::Io)?;
+impl Connection {
+    fn open(config: &ServiceConfig) -> Self {
+        config.event(ConnectionEvent::Opened);
+        Self {
+            on_event: config.on_event,
+            // Replaced unless the future serving the connection is dropped.
+            reason: CloseReason::Cancelled,
+        }
+    }
+}
 
-        let mut body = response.into_body();
-        loop {
-            let mut buf = [0; 1024];
-            let len = body.read(&mut buf).await.map_err(ServiceError::Body)?;
-            if len == 0 {
-                break;
+impl Drop for Connection {
+    fn drop(&mut self) {
+        if let Some(on_event) = self.on_event {
+            on_event(ConnectionEvent::Closed(self.reason));
+        }
+    }
+}
+
+impl Drop for Activity {
+    fn drop(&mut self) {
+        if let Some(on_idle) = self.0 {
+            on_idle();
+        }
+    }
+}
+
+/// Reads the bytes following a request into `buf` until the client closes the
+/// connection, stops reading once `buf` is full.
+async fn watch_close<R: Read>(
+    reader: &mut R,
+    buf: &mut [u8],
+    received: &Cell<usize>,
+) -> Result<(), R::Error> {
+    loop {
+        let pos = received.get();
+        if pos == buf.len() {
+            return core::future::pending().await;
+        }
+        match reader.read(&mut buf[pos..]).await? {
+            0 => return Ok(()),
+            read => received.set(pos + read),
+        }
+    }
+}
+
+/// Reader counting the bytes read from it.
+struct Counted<'r, R> {
+    reader: &'r mut R,
+    read: &'r Cell<usize>,
+}
+
+impl<'r, R: Read> ErrorType for Counted<'r, R> {
+    type Error = R::Error;
+}
+
+impl<'r, R: Read> Read for Counted<'r, R> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let read = self.reader.read(buf).await?;
+        self.read.set(self.read.get() + read);
+        Ok(read)
+    }
+}
+
+/// Returns the `Content-Length` of a request, `0` if there is none.
+///
+/// Fails for invalid values, several differing values and requests with both a
+/// `Content-Length` and a `Transfer-Encoding`, see RFC 9112 section 6.3.
+fn content_length(headers: &Headers<'_>, transfer_encoding: bool) -> Result<usize, ()> {
+    let mut content_length = None;
+    // Checks the raw headers, `Headers` skips values which are not valid UTF-8.
+    for indices in headers.headers {
+        let name = &headers.buf[indices.name.0..indices.name.1];
+        if !name.eq_ignore_ascii_case(b"Content-Length") {
+            continue;
+        }
+        let value =
+            core::str::from_utf8(&headers.buf[indices.value.0..indices.value.1]).map_err(|_| ())?;
+        // A list of identical values is allowed, e.g. `5, 5`.
+        for item in value.split(',').map(str::trim) {
+            if item.is_empty() || !item.bytes().all(|c| c.is_ascii_digit()) {
+                return Err(());
             }
-            writer
-                .write_all(&buf[..len])
-                .await
-                .map_err(ServiceError::Io)?;
+            let len = item.parse::<usize>().map_err(|_| ())?;
+            if content_length.is_some_and(|previous| previous != len) {
+                return Err(());
+            }
+            content_length = Some(len);
+        }
+    }
+    match content_length {
+        Some(_) if transfer_encoding => Err(()),
+        content_length => Ok(content_length.unwrap_or(0)),
+    }
+}
+
+/// A complete request head, its parts are stored as indices into the request buffer.
+pub(crate) struct Head<'b> {
+    method: (usize, usize),
+    /// Minor HTTP version, `1` for HTTP/1.1.
+    pub(crate) version: u8,
+    path: (usize, usize),
+    pub(crate) headers: &'b [HeaderIndices],
+    /// Length of the head in bytes, the body starts right after it.
+    pub(crate) len: usize,
+}
+
+impl<'b> Head<'b> {
+    fn method<'a>(&self, buf: &'a [u8]) -> &'a str {
+        // SAFETY: we converted from str to indices of `buf`, so we can convert back to str
+        unsafe { core::str::from_utf8_unchecked(&buf[self.method.0..self.method.1]) }
+    }
+
+    /// The request target, `buf` has to be the buffer the head was parsed from.
+    pub(crate) fn path<'a>(&self, buf: &'a [u8]) -> &'a str {
+        // SAFETY: we converted from str to indices of `buf`, so we can convert back to str
+        unsafe { core::str::from_utf8_unchecked(&buf[self.path.0..self.path.1]) }
+    }
+
+    /// Percent-decodes the path of the request target in place, see [`PathAndQuery::decode`].
+    fn decode_target(&self, buf: &mut [u8]) -> Result<Decoded, InvalidUrl> {
+        PathAndQuery::decode(&mut buf[self.path.0..self.path.1])
+    }
+}
+
+/// Parses the request head received so far, returns `None` if it is incomplete.
+///
+/// The head does not borrow `buf`, this allows the part of the buffer following
+/// the received bytes to be used mutably while the head is in use.
+pub(crate) fn parse_head<'h, 'b>(
+    buf: &'h [u8],
+    headers: &mut [MaybeUninit<httparse::Header<'h>>],
+    indices: &'b mut [MaybeUninit<HeaderIndices>],
+) -> Result<Option<Head<'b>>, ProtocolError> {
+    let mut req = httparse::Request::new(&mut []);
+
+    match req
+        .parse_with_uninit_headers(buf, headers)
+        .map_err(ProtocolError::Parser)?
+    {
+        httparse::Status::Complete(len) => {
+            record_header_indices(buf, req.headers, indices);
+
+            // SAFETY: `record_header_indices` initialized the first `req.headers.len()` items.
+            let headers = unsafe {
+                &*(&indices[..req.headers.len()] as *const [MaybeUninit<HeaderIndices>]
+                    as *const [HeaderIndices])
+            };
+
+            let indices = |s: &str| {
+                let start = s.as_ptr() as usize - buf.as_ptr() as usize;
+                (start, start + s.len())
+            };
+
+            // The method is not necessarily borrowed from `buf`, httparse returns static
+            // strings for common methods. It starts the request line, which may be
+            // preceded by empty lines.
+            let method_start = buf
+                .iter()
+                .position(|&c| c != b'\r' && c != b'\n')
+                .unwrap_or(0);
+
+            Ok(Some(Head {
+                // TODO: I think these unwraps cant happen, double check
+                method: (method_start, method_start + req.method.unwrap().len()),
+                version: req.version.unwrap(),
+                path: indices(req.path.unwrap()),
+                headers,
+                len,
+            }))
         }
+        httparse::Status::Partial => Ok(None),
+    }
+}
+
+fn request_parts<'b>(
+    head: &Head<'b>,
+    target: Decoded,
+    buf: &'b [u8],
+    arena: Option<&'b Arena<'b>>,
+) -> Result<Parts<'b>, ProtocolError> {
+    let paq = target.get(&buf[head.path.0..]);
+
+    Ok(Parts {
+        method: Method::new(head.method(buf)).map_err(ProtocolError::InvalidMethod)?,
+        path: paq.path(),
+        query: paq.query(),
+        headers: Headers {
+            headers: head.headers,
+            buf,
+        },
+        arena,
+        params: Params::default(),
+    })
+}
 
-        Ok(())
+/// Writes `response`, returns `true` if the connection can be reused afterwards.
+pub(crate) async fn write_response<W: Write, B: ResponseBody, const BODY: usize>(
+    writer: W,
+    response: Response<B>,
+    keep_alive: bool,
+    config: &ServiceConfig,
+) -> Result<bool, ServiceError<W::Error, B::Error>> {
+    let mut buf = [0; BODY];
+    write_response_in(writer, response, keep_alive, false, 1, config, &mut buf).await
+}
+
+/// Writes `response` like [`write_response`], relaying the body through `buf`.
+///
+/// Only the head is sent if `head_only`, e.g. when answering a `HEAD` request.
+/// `version` is the minor HTTP version of the request.
+/// Reads and discards the head of a request, at most `limit` bytes.
+async fn skip_head<R: Read>(reader: &mut R, limit: usize) -> Result<(), R::Error> {
+    let mut buf = [0; 64];
+    // The last bytes read, to find the empty line ending the head.
+    let mut last = [0; 2];
+    let mut skipped = 0;
+    while skipped < limit {
+        let len = buf.len().min(limit - skipped);
+        let read = reader.read(&mut buf[..len]).await?;
+        if read == 0 {
+            break;
+        }
+        skipped += read;
+        for &c in &buf[..read] {
+            if c == b'\n' && (last[1] == b'\n' || last == *b"\n\r") {
+                return Ok(());
+            }
+            last = [last[1], c];
+        }
     }
+    Ok(())
+}
+
+/// Answers a request which can't be served with `status` and closes the connection,
+/// returns the error serving the connection fails with.
+async fn reject<W: Write, E>(
+    writer: W,
+    status: StatusCode,
+    err: ProtocolError,
+    config: &ServiceConfig,
+    buf: &mut [u8],
+) -> ServiceError<W::Error, E> {
+    let response = (status, status.canonical_reason().unwrap_or_default()).into_response();
+    match write_response_in(writer, response, false, false, 1, config, buf).await {
+        Ok(_) => ServiceError::ProtocolError(err),
+        Err(ServiceError::Io(err)) => ServiceError::Io(err),
+        Err(ServiceError::ProtocolError(err)) => ServiceError::ProtocolError(err),
+        Err(ServiceError::Body(err)) => match err {},
+    }
+}
+
+async fn write_response_in<W: Write, B: ResponseBody>(
+    mut writer: W,
+    response: Response<B>,
+    keep_alive: bool,
+    head_only: bool,
+    version: u8,
+    config: &ServiceConfig,
+    buf: &mut [u8],
+) -> Result<bool, ServiceError<W::Error, B::Error>> {
+    let keep_alive = keep_alive && !response.closes_connection();
+    let mut writer = ResponseWriter::new(&mut writer, response.status_code(), keep_alive);
+    writer.set_head_only(head_only);
+    writer.set_version(version);
+    let content_type = response.content_type();
+    let body = response.into_body();
+    writer.set_defaults(content_type, body.content_length());
+    writer.set_date(config.now());
+    let buf = match config.write_chunk {
+        Some(size) => {
+            assert!(
+                size < buf.len(),
+                "write chunk size must be smaller than the relay buffer"
+            );
+            let (chunk, buf) = buf.split_at_mut(size);
+            writer.set_write_chunk(chunk);
+            buf
+        }
+        None => buf,
+    };
+
+    body.write_to(&mut writer, buf)
+        .await
+        .map_err(|err| match err {
+            WriteError::Io(err) => ServiceError::Io(err),
+            WriteError::Body(err) => ServiceError::Body(err),
+        })?;
+
+    writer.finish().await.map_err(ServiceError::Io)
 }
@@ -1,6 +1,6 @@
 use core::future::Future;
 
-use crate::{error::ProtocolError, Read, Write};
+use crate::{error::ProtocolError, time::Timestamp, Parts, Read, Response, Write};
 
 #[derive(Debug)]
 pub enum ServiceError<IO, BODY> {
@@ -21,6 +21,262 @@ impl<IO: embedded_io_async::Error, BODY: embedded_io_async::Error> embedded_io_a
     }
 }
 
+/// What to do with a request, decided by the [`ServiceConfig::on_head`] hook.
+#[derive(Clone)]
+pub enum HeadAction {
+    /// Routes the request.
+    Continue,
+    /// Routes the request and closes the connection after the response.
+    Close,
+    /// Sends the response without routing the request or reading its body,
+    /// then closes the connection.
+    Reject(Response<&'static [u8]>),
+}
+
+/// Events in the life of a connection, see [`ServiceConfig::on_event`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    /// Serving a connection started.
+    Opened,
+    /// The first bytes of a request were received.
+    RequestStarted,
+    /// The response to a request has been written and flushed.
+    RequestFinished,
+    /// Serving the connection ended.
+    Closed(CloseReason),
+}
+
+/// Why serving a connection ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloseReason {
+    /// The client closed the connection.
+    ClientClosed,
+    /// The connection was closed after a response, as the client or the server
+    /// did not keep it alive.
+    Done,
+    /// Reading or writing failed, or the client sent an invalid request.
+    Error,
+    /// The future serving the connection was dropped, e.g. by a timeout.
+    Cancelled,
+}
+
+/// Limits and settings applied while serving requests.
+#[derive(Debug, Clone, Copy)]
+pub struct ServiceConfig {
+    pub(crate) max_content_length: Option<usize>,
+    pub(crate) keep_alive: bool,
+    pub(crate) max_requests_per_connection: usize,
+    pub(crate) on_active: Option<fn()>,
+    pub(crate) on_idle: Option<fn()>,
+    pub(crate) on_head: Option<fn(&Parts<'_>) -> HeadAction>,
+    pub(crate) on_event: Option<fn(ConnectionEvent)>,
+    pub(crate) cancel_on_close: bool,
+    pub(crate) date: Option<fn() -> Option<Timestamp>>,
+    pub(crate) write_chunk: Option<usize>,
+}
+
+impl ServiceConfig {
+    pub const fn new() -> Self {
+        Self {
+            max_content_length: None,
+            keep_alive: true,
+            max_requests_per_connection: usize::MAX,
+            on_active: None,
+            on_idle: None,
+            on_head: None,
+            on_event: None,
+            cancel_on_close: false,
+            date: None,
+            write_chunk: None,
+        }
+    }
+
+    /// Rejects requests declaring a `Content-Length` larger than `limit`.
+    ///
+    /// The limit is evaluated as soon as the request head is parsed,
+    /// the request is answered with `413 Payload Too Large` without being routed
+    /// and without reading any of its body.
+    ///
+    /// Chunked request bodies are routed, reading them fails with
+    /// [`BodyError::TooLarge`](crate::request::BodyError::TooLarge) once their chunks
+    /// add up to more than `limit` bytes. Extractors buffering the body answer this
+    /// with `413 Payload Too Large` as well.
+    pub const fn max_content_length(mut self, limit: usize) -> Self {
+        self.max_content_length = Some(limit);
+        self
+    }
+
+    /// Calls `hook` with every request as soon as its head is parsed, before it is
+    /// routed and before any of its body is read.
+    ///
+    /// This allows rejecting requests early, e.g. unauthorized ones, or applying a
+    /// connection policy in one place. The hook runs before the
+    /// [`max_content_length`](Self::max_content_length) check.
+    ///
+    /// ```
+    /// use low_profile::{http::StatusCode, HeadAction, IntoResponse, Parts, ServiceConfig};
+    ///
+    /// fn policy(parts: &Parts<'_>) -> HeadAction {
+    ///     if parts.path.starts_with("/admin") && parts.headers.get_first("Authorization").is_none() {
+    ///         HeadAction::Reject((StatusCode::UNAUTHORIZED, "Unauthorized").into_response())
+    ///     } else if parts.headers.get_first("Upgrade").is_some() {
+    ///         HeadAction::Close
+    ///     } else {
+    ///         HeadAction::Continue
+    ///     }
+    /// }
+    ///
+    /// let config = ServiceConfig::new().on_head(policy);
+    /// ```
+    pub const fn on_head(mut self, hook: fn(&Parts<'_>) -> HeadAction) -> Self {
+        self.on_head = Some(hook);
+        self
+    }
+
+    /// Enables or disables serving multiple requests per connection, enabled by default.
+    ///
+    /// Connections are kept alive for HTTP/1.1 requests unless the client sends
+    /// `Connection: close` or a response does not declare its `Content-Length`.
+    pub const fn keep_alive(mut self, enabled: bool) -> Self {
+        self.keep_alive = enabled;
+        self
+    }
+
+    /// Closes connections after `max` requests, sending `Connection: close` with the
+    /// last response, unlimited by default.
+    ///
+    /// This spreads long-lived clients over the available connections, e.g. when a
+    /// device only accepts a few at a time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max` is zero.
+    pub const fn max_requests_per_connection(mut self, max: usize) -> Self {
+        assert!(max > 0, "zero requests per connection");
+        self.max_requests_per_connection = max;
+        self
+    }
+
+    /// Stops writing a response as soon as the client closes the connection,
+    /// disabled by default.
+    ///
+    /// Streaming responses, e.g. server sent events, may not write anything for a long
+    /// time, a closed connection is otherwise only noticed by the next write. With this
+    /// enabled the connection is read while a response is written, reaching the end of
+    /// input drops the response body, which releases the subscriptions or other
+    /// resources it holds. Bytes of pipelined requests received meanwhile are kept.
+    ///
+    /// Only the responses to requests whose body was read completely are watched.
+    /// Enable this only for readers which lose no data when a pending read is dropped,
+    /// and clients which do not shut down their sending side after a request.
+    pub const fn cancel_on_close(mut self, enabled: bool) -> Self {
+        self.cancel_on_close = enabled;
+        self
+    }
+
+    /// Sends the time returned by `now` as `Date` header with every response, unless
+    /// the response writes its own. Nothing is sent while `now` returns `None`, e.g.
+    /// before the clock is synchronized.
+    ///
+    /// Responses are written in a fixed order, see
+    /// [`ResponseWriter`](crate::response::ResponseWriter). With a clock returning a
+    /// fixed time, the same request is always answered with the same bytes, e.g. to
+    /// compare the output of tests or firmware builds byte by byte.
+    ///
+    /// ```
+    /// use low_profile::{time::Timestamp, ServiceConfig};
+    ///
+    /// fn now() -> Option<Timestamp> {
+    ///     Some(Timestamp::from_unix(784111777))
+    /// }
+    ///
+    /// let config = ServiceConfig::new().date(now);
+    /// ```
+    pub const fn date(mut self, now: fn() -> Option<Timestamp>) -> Self {
+        self.date = Some(now);
+        self
+    }
+
+    /// Sends responses in writes of `size` bytes, the preferred write size of the
+    /// transport, e.g. the TCP MSS or the USB packet size.
+    ///
+    /// Head and body are collected into writes of whole packets, only the last write
+    /// of a response, or before a body flushes, is shorter. Stacks splitting writes
+    /// into awkward packets may perform better this way. Data written by a body in
+    /// whole packets is passed on without copying it.
+    ///
+    /// The packet is collected in the first `size` bytes of the `BODY` buffer of the
+    /// [`Buffers`](crate::Buffers), the rest relays response bodies. The buffer
+    /// should therefore be at least twice as large.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero. Serving a response panics if `size` is not smaller
+    /// than the `BODY` buffer.
+    pub const fn write_chunk(mut self, size: usize) -> Self {
+        assert!(size > 0, "zero write chunk size");
+        self.write_chunk = Some(size);
+        self
+    }
+
+    /// Calls `hook` as soon as the first bytes of a request are received.
+    ///
+    /// Together with [`on_idle`](Self::on_idle) this allows gating clocks or radio power
+    /// states while no request is processed, the hooks should return quickly.
+    pub const fn on_active(mut self, hook: fn()) -> Self {
+        self.on_active = Some(hook);
+        self
+    }
+
+    /// Calls `hook` once a request which triggered [`on_active`](Self::on_active)
+    /// is done, including when serving it failed or was cancelled.
+    pub const fn on_idle(mut self, hook: fn()) -> Self {
+        self.on_idle = Some(hook);
+        self
+    }
+
+    /// Calls `hook` with the [`ConnectionEvent`]s of every served connection,
+    /// e.g. to drive status LEDs or count open connections.
+    ///
+    /// ```
+    /// use core::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// use low_profile::{ConnectionEvent, ServiceConfig};
+    ///
+    /// static OPEN: AtomicUsize = AtomicUsize::new(0);
+    ///
+    /// fn count(event: ConnectionEvent) {
+    ///     match event {
+    ///         ConnectionEvent::Opened => OPEN.fetch_add(1, Ordering::Relaxed),
+    ///         ConnectionEvent::Closed(_) => OPEN.fetch_sub(1, Ordering::Relaxed),
+    ///         _ => 0,
+    ///     };
+    /// }
+    ///
+    /// let config = ServiceConfig::new().on_event(count);
+    /// ```
+    pub const fn on_event(mut self, hook: fn(ConnectionEvent)) -> Self {
+        self.on_event = Some(hook);
+        self
+    }
+
+    pub(crate) fn now(&self) -> Option<Timestamp> {
+        self.date.and_then(|now| now())
+    }
+
+    pub(crate) fn event(&self, event: ConnectionEvent) {
+        if let Some(on_event) = self.on_event {
+            on_event(event);
+        }
+    }
+}
+
+impl Default for ServiceConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub trait Service {
     // TODO: this should come from crate::io or somewhere else
     type BodyError: embedded_io_async::Error;
@@ -0,0 +1,334 @@
+//! Helpers for testing code built on this crate, enabled by the `test-util` feature.
+
+use core::{
+    future::{poll_fn, Future},
+    task::Poll,
+};
+
+use embedded_io_async::ErrorKind;
+
+use crate::{
+    io::Cursor,
+    response::{ResponseBody, WriteError},
+    router::write_response,
+    service::{ServiceConfig, ServiceError},
+    ErrorType, IntoResponse, Read, Service, Write,
+};
+
+/// Size of the scratch buffer used to relay response bodies, see [`Buffers`](crate::Buffers).
+const BODY: usize = 1024;
+
+/// Error returned by [`render`] when the response does not fit into the buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferFull;
+
+impl embedded_io_async::Error for BufferFull {
+    fn kind(&self) -> embedded_io_async::ErrorKind {
+        embedded_io_async::ErrorKind::OutOfMemory
+    }
+}
+
+struct VecWriter<'v, const N: usize>(&'v mut heapless::Vec<u8, N>);
+
+impl<'v, const N: usize> ErrorType for VecWriter<'v, N> {
+    type Error = BufferFull;
+}
+
+impl<'v, const N: usize> Write for VecWriter<'v, N> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.0.extend_from_slice(buf).map_err(|()| BufferFull)?;
+        Ok(buf.len())
+    }
+}
+
+/// Renders `response` to the exact bytes sent to the client, status line,
+/// headers in the order they are written and body, e.g. for golden-file assertions.
+///
+/// The response is rendered as sent to a client keeping the connection alive.
+///
+/// ```
+/// use low_profile::{http::StatusCode, test_util::render};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let bytes = render::<128, _>((StatusCode::CREATED, "done")).await.unwrap();
+/// assert_eq!(
+///     &bytes[..],
+///     b"HTTP/1.1 201 Created\r\n\
+///       Content-Type: text/plain; charset=utf-8\r\n\
+///       Content-Length: 4\r\n\
+///       \r\n\
+///       done"
+/// );
+/// # }
+/// ```
+pub async fn render<const N: usize, T: IntoResponse>(
+    response: T,
+) -> Result<heapless::Vec<u8, N>, WriteError<BufferFull, <T::Body as ResponseBody>::Error>> {
+    let mut bytes = heapless::Vec::new();
+    write_response::<_, _, BODY>(
+        VecWriter(&mut bytes),
+        response.into_response(),
+        true,
+        &ServiceConfig::new(),
+    )
+    .await
+    .map_err(|err| match err {
+        ServiceError::Io(err) => WriteError::Io(err),
+        ServiceError::Body(err) => WriteError::Body(err),
+        // Writing a response never parses anything.
+        ServiceError::ProtocolError(_) => unreachable!(),
+    })?;
+    Ok(bytes)
+}
+
+/// Faults injected by a [`Flaky`] transport, every run with the same seed injects
+/// the same faults.
+#[derive(Debug, Clone, Copy)]
+pub struct Faults {
+    seed: u64,
+    max_chunk: usize,
+    max_delay: u32,
+    resets: u16,
+}
+
+impl Faults {
+    /// No faults besides the ones enabled with the builder methods.
+    pub const fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            max_chunk: usize::MAX,
+            max_delay: 0,
+            resets: 0,
+        }
+    }
+
+    /// Transfers between 1 and `max` bytes per read or write, limiting the throughput.
+    pub const fn max_chunk(mut self, max: usize) -> Self {
+        self.max_chunk = if max == 0 { 1 } else { max };
+        self
+    }
+
+    /// Delays reads and writes by yielding to the executor up to `polls` times.
+    pub const fn max_delay(mut self, polls: u32) -> Self {
+        self.max_delay = polls;
+        self
+    }
+
+    /// Resets the connection with a chance of `per_mille` / 1000 per read or write.
+    pub const fn resets(mut self, per_mille: u16) -> Self {
+        self.resets = per_mille;
+        self
+    }
+}
+
+/// Error returned by a [`Flaky`] transport.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlakyError {
+    /// An injected connection reset, all following operations fail as well.
+    Reset,
+    /// An error of the wrapped reader or writer.
+    Inner(ErrorKind),
+}
+
+impl embedded_io_async::Error for FlakyError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Reset => ErrorKind::ConnectionReset,
+            Self::Inner(kind) => *kind,
+        }
+    }
+}
+
+/// Reader or writer injecting partial transfers, delays and resets, see [`Faults`].
+pub struct Flaky<T> {
+    inner: T,
+    faults: Faults,
+    rng: u64,
+    reset: bool,
+}
+
+impl<T> Flaky<T> {
+    pub fn new(inner: T, faults: Faults) -> Self {
+        Self {
+            inner,
+            faults,
+            // Xorshift must not be seeded with zero.
+            rng: faults.seed | 1,
+            reset: false,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Returns a random number below `bound`, xorshift64*.
+    fn random(&mut self, bound: u64) -> u64 {
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        self.rng.wrapping_mul(0x2545_f491_4f6c_dd1d) % bound.max(1)
+    }
+
+    /// Injects a delay and possibly a reset, returns the amount of bytes to transfer.
+    async fn fault(&mut self, len: usize) -> Result<usize, FlakyError> {
+        let delay = self.random(u64::from(self.faults.max_delay) + 1);
+        for _ in 0..delay {
+            yield_now().await;
+        }
+        if self.reset || self.random(1000) < u64::from(self.faults.resets) {
+            self.reset = true;
+            return Err(FlakyError::Reset);
+        }
+        let max = len.min(self.faults.max_chunk).max(1);
+        Ok(1 + self.random(max as u64) as usize)
+    }
+}
+
+/// Returns pending once, letting other tasks run.
+fn yield_now() -> impl Future<Output = ()> {
+    let mut yielded = false;
+    poll_fn(move |cx| {
+        if yielded {
+            return Poll::Ready(());
+        }
+        yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    })
+}
+
+impl<T: ErrorType> ErrorType for Flaky<T> {
+    type Error = FlakyError;
+}
+
+impl<T: Read> Read for Flaky<T> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let len = self.fault(buf.len()).await?;
+        self.inner
+            .read(&mut buf[..len])
+            .await
+            .map_err(|err| FlakyError::Inner(embedded_io_async::Error::kind(&err)))
+    }
+}
+
+impl<T: Write> Write for Flaky<T> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let len = self.fault(buf.len()).await?;
+        self.inner
+            .write(&buf[..len])
+            .await
+            .map_err(|err| FlakyError::Inner(embedded_io_async::Error::kind(&err)))
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.fault(0).await?;
+        self.inner
+            .flush()
+            .await
+            .map_err(|err| FlakyError::Inner(embedded_io_async::Error::kind(&err)))
+    }
+}
+
+/// Outcome of a [`soak`] test.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SoakReport {
+    /// Runs whose output matched the output without faults.
+    pub passed: u32,
+    /// Runs hit by an injected reset, their output is not checked.
+    pub reset: u32,
+    /// Runs which failed otherwise or whose output differed.
+    pub failed: u32,
+}
+
+impl SoakReport {
+    /// Returns `true` if no run failed.
+    pub fn is_ok(&self) -> bool {
+        self.failed == 0
+    }
+}
+
+/// Serves `input`, e.g. several pipelined requests, `runs` times over [`Flaky`]
+/// transports, with the seed of `faults` incremented for every run.
+///
+/// The output of every run not hit by a reset must equal the output without
+/// faults, which must fit into `N` bytes.
+///
+/// ```
+/// use low_profile::{test_util::{soak, Faults}, Router};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let router = Router::new()
+///     .get("/", || async { "Hello" })
+///     .post("/echo", |body: heapless::String<16>| async move { body });
+///
+/// let input = b"GET / HTTP/1.1\r\n\r\n\
+///     POST /echo HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello\
+///     GET /missing HTTP/1.1\r\n\r\n";
+/// let faults = Faults::new(1).max_chunk(7).max_delay(3).resets(5);
+///
+/// let report = soak::<1024, _>(&router, input, faults, 1000).await;
+/// assert!(report.is_ok(), "{report:?}");
+/// # }
+/// ```
+pub async fn soak<const N: usize, S: Service>(
+    service: &S,
+    input: &[u8],
+    faults: Faults,
+    runs: u32,
+) -> SoakReport {
+    let mut report = SoakReport::default();
+
+    let mut expected = heapless::Vec::<u8, N>::new();
+    let clean = Faults::new(faults.seed);
+    if serve_flaky(service, input, clean, &mut expected)
+        .await
+        .0
+        .is_err()
+    {
+        report.failed = runs;
+        return report;
+    }
+
+    for run in 0..runs {
+        let faults = Faults {
+            seed: faults.seed.wrapping_add(u64::from(run)),
+            ..faults
+        };
+        let mut output = heapless::Vec::<u8, N>::new();
+        match serve_flaky(service, input, faults, &mut output).await {
+            // Requests cut by a reset may still be answered, e.g. with an error.
+            (_, true) => report.reset += 1,
+            (Ok(()), false) if output == expected => report.passed += 1,
+            _ => report.failed += 1,
+        }
+    }
+    report
+}
+
+async fn serve_flaky<const N: usize, S: Service>(
+    service: &S,
+    input: &[u8],
+    faults: Faults,
+    output: &mut heapless::Vec<u8, N>,
+) -> (Result<(), ServiceError<FlakyError, S::BodyError>>, bool) {
+    let mut reader = Flaky::new(Cursor::new(input), faults);
+    // Different faults for writing than for reading.
+    let mut writer = Flaky::new(
+        VecWriter(output),
+        Faults {
+            seed: !faults.seed,
+            ..faults
+        },
+    );
+    let result = service.serve(&mut reader, &mut writer).await;
+    (result, reader.reset || writer.reset)
+}
@@ -0,0 +1,292 @@
+//! Timezone free timestamps and their textual representations.
+//!
+//! A [`Timestamp`] counts seconds since the unix epoch in UTC, it formats and parses as
+//! HTTP-date (`Date`, `Last-Modified`, cookie `Expires`) using [`HttpDate`] and as
+//! RFC 3339 (JSON payloads) using [`Rfc3339`].
+//!
+//! ```
+//! use low_profile::time::{HttpDate, Rfc3339, Timestamp};
+//!
+//! let ts = Timestamp::from_unix(784111777);
+//! assert_eq!(ts.http_date().to_string(), "Sun, 06 Nov 1994 08:49:37 GMT");
+//! assert_eq!(ts.rfc3339().to_string(), "1994-11-06T08:49:37Z");
+//!
+//! let parsed: Rfc3339 = "1994-11-06T09:49:37+01:00".parse().unwrap();
+//! assert_eq!(parsed.0, ts);
+//! ```
+
+use core::{fmt, str::FromStr};
+
+const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Error returned when parsing a timestamp fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidTimestamp;
+
+impl fmt::Display for InvalidTimestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid timestamp")
+    }
+}
+
+/// Seconds since the unix epoch, `1970-01-01T00:00:00Z`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp(u64);
+
+impl Timestamp {
+    pub const UNIX_EPOCH: Self = Self(0);
+
+    pub const fn from_unix(secs: u64) -> Self {
+        Self(secs)
+    }
+
+    pub const fn unix(self) -> u64 {
+        self.0
+    }
+
+    pub const fn http_date(self) -> HttpDate {
+        HttpDate(self)
+    }
+
+    pub const fn rfc3339(self) -> Rfc3339 {
+        Rfc3339(self)
+    }
+
+    fn to_civil(self) -> Civil {
+        let days = (self.0 / 86400) as i64;
+        let secs = self.0 % 86400;
+        let (year, month, day) = civil_from_days(days);
+
+        Civil {
+            year,
+            month,
+            day,
+            weekday: ((days + 4) % 7) as usize,
+            hour: (secs / 3600) as u32,
+            minute: (secs / 60 % 60) as u32,
+            second: (secs % 60) as u32,
+        }
+    }
+
+    fn from_unix_signed(secs: i64) -> Result<Self, InvalidTimestamp> {
+        u64::try_from(secs).map(Self).map_err(|_| InvalidTimestamp)
+    }
+}
+
+/// Broken down UTC date and time.
+struct Civil {
+    year: i64,
+    month: u32,
+    day: u32,
+    /// Days since Sunday.
+    weekday: usize,
+    hour: u32,
+    minute: u32,
+    second: u32,
+}
+
+impl Civil {
+    /// Seconds since the unix epoch, negative before it.
+    fn unix_secs(&self) -> Result<i64, InvalidTimestamp> {
+        let days_in_month = match self.month {
+            2 if is_leap_year(self.year) => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            1..=12 => 31,
+            _ => return Err(InvalidTimestamp),
+        };
+        if self.day == 0
+            || self.day > days_in_month
+            || self.hour > 23
+            || self.minute > 59
+            // Leap seconds are folded into the following second.
+            || self.second > 60
+        {
+            return Err(InvalidTimestamp);
+        }
+
+        let days = days_from_civil(self.year, self.month, self.day);
+        Ok(days * 86400
+            + i64::from(self.hour) * 3600
+            + i64::from(self.minute) * 60
+            + i64::from(self.second))
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+// Date algorithms from http://howardhinnant.github.io/date_algorithms.html
+
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = i64::from((month + 9) % 12);
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Reads a fixed amount of ascii digits.
+fn digits(s: &[u8]) -> Result<u32, InvalidTimestamp> {
+    s.iter().try_fold(0, |acc, c| match c {
+        b'0'..=b'9' => Ok(acc * 10 + u32::from(c - b'0')),
+        _ => Err(InvalidTimestamp),
+    })
+}
+
+/// Timestamp formatted as IMF-fixdate, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`.
+///
+/// Parsing only accepts IMF-fixdate, the obsolete RFC 850 and asctime formats are rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HttpDate(pub Timestamp);
+
+impl fmt::Display for HttpDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let civil = self.0.to_civil();
+        write!(
+            f,
+            "{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT",
+            WEEKDAYS[civil.weekday],
+            civil.day,
+            MONTHS[civil.month as usize - 1],
+            civil.year,
+            civil.hour,
+            civil.minute,
+            civil.second
+        )
+    }
+}
+
+impl FromStr for HttpDate {
+    type Err = InvalidTimestamp;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Sun, 06 Nov 1994 08:49:37 GMT
+        let s = s.as_bytes();
+        if s.len() != 29
+            || &s[3..5] != b", "
+            || s[7] != b' '
+            || s[11] != b' '
+            || s[16] != b' '
+            || s[19] != b':'
+            || s[22] != b':'
+            || &s[25..] != b" GMT"
+        {
+            return Err(InvalidTimestamp);
+        }
+
+        let month = MONTHS
+            .iter()
+            .position(|month| month.as_bytes() == &s[8..11])
+            .ok_or(InvalidTimestamp)?;
+
+        let civil = Civil {
+            year: i64::from(digits(&s[12..16])?),
+            month: month as u32 + 1,
+            day: digits(&s[5..7])?,
+            weekday: 0,
+            hour: digits(&s[17..19])?,
+            minute: digits(&s[20..22])?,
+            second: digits(&s[23..25])?,
+        };
+        let timestamp = Timestamp::from_unix_signed(civil.unix_secs()?)?;
+
+        if WEEKDAYS[timestamp.to_civil().weekday].as_bytes() != &s[..3] {
+            return Err(InvalidTimestamp);
+        }
+        Ok(Self(timestamp))
+    }
+}
+
+/// Timestamp formatted as RFC 3339 in UTC, e.g. `1994-11-06T08:49:37Z`.
+///
+/// Parsing accepts any UTC offset and fractional seconds, which are truncated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Rfc3339(pub Timestamp);
+
+impl fmt::Display for Rfc3339 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let civil = self.0.to_civil();
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            civil.year, civil.month, civil.day, civil.hour, civil.minute, civil.second
+        )
+    }
+}
+
+impl FromStr for Rfc3339 {
+    type Err = InvalidTimestamp;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // 1994-11-06T08:49:37[.123](Z|+01:00)
+        let s = s.as_bytes();
+        if s.len() < 20
+            || s[4] != b'-'
+            || s[7] != b'-'
+            || !matches!(s[10], b'T' | b't' | b' ')
+            || s[13] != b':'
+            || s[16] != b':'
+        {
+            return Err(InvalidTimestamp);
+        }
+
+        let civil = Civil {
+            year: i64::from(digits(&s[0..4])?),
+            month: digits(&s[5..7])?,
+            day: digits(&s[8..10])?,
+            weekday: 0,
+            hour: digits(&s[11..13])?,
+            minute: digits(&s[14..16])?,
+            second: digits(&s[17..19])?,
+        };
+
+        let mut rest = &s[19..];
+        if let [b'.', fraction @ ..] = rest {
+            let len = fraction.iter().take_while(|c| c.is_ascii_digit()).count();
+            if len == 0 {
+                return Err(InvalidTimestamp);
+            }
+            rest = &fraction[len..];
+        }
+
+        let offset = match rest {
+            [b'Z' | b'z'] => 0,
+            [sign @ (b'+' | b'-'), hour @ .., b':', m1, m2] if hour.len() == 2 => {
+                let hours = digits(hour)?;
+                let minutes = digits(&[*m1, *m2])?;
+                if hours > 23 || minutes > 59 {
+                    return Err(InvalidTimestamp);
+                }
+                let offset = i64::from(hours * 3600 + minutes * 60);
+                if *sign == b'+' {
+                    offset
+                } else {
+                    -offset
+                }
+            }
+            _ => return Err(InvalidTimestamp),
+        };
+
+        Timestamp::from_unix_signed(civil.unix_secs()? - offset).map(Self)
+    }
+}
@@ -0,0 +1,136 @@
+//! Wire level dumps of the bytes received and sent, enabled by the `trace` feature.
+//!
+//! Wrapping the reader and writer of a connection with [`Traced`] passes a hex and
+//! ASCII dump of every read and write to a [`TraceSink`], e.g. RTT or a logger,
+//! to diagnose problems with clients without a network sniffer:
+//!
+//! ```no_run
+//! use embedded_io_adapters::tokio_1::FromTokio;
+//! use low_profile::{trace::Traced, Router, Service};
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() -> std::io::Result<()> {
+//! let router = Router::new().get("/", || async { "Hello World" });
+//! let sink = |line: core::fmt::Arguments<'_>| println!("{line}");
+//!
+//! let socket = tokio::net::TcpListener::bind("127.0.0.1:8000").await?;
+//! let (mut stream, _) = socket.accept().await?;
+//! let (reader, writer) = stream.split();
+//! let reader = Traced::new(FromTokio::new(reader), &sink);
+//! let writer = Traced::new(FromTokio::new(writer), &sink);
+//!
+//! // RX 00000000  47 45 54 20 2f 20 48 54 54 50 2f 31 2e 31 0d 0a  GET / HTTP/1.1..
+//! // ...
+//! // TX 00000000  48 54 54 50 2f 31 2e 31 20                       HTTP/1.1
+//! // ...
+//! let _ = router.serve(reader, writer).await;
+//! # Ok(())
+//! # }
+//! ```
+
+use core::fmt;
+
+use crate::{ErrorType, Read, Write};
+
+/// Bytes dumped per line.
+const LINE: usize = 16;
+
+/// Receives the lines of the dumps made by [`Traced`].
+///
+/// Implemented for closures taking the formatted line, lines have no line break.
+pub trait TraceSink {
+    fn line(&self, line: fmt::Arguments<'_>);
+}
+
+impl<F: Fn(fmt::Arguments<'_>)> TraceSink for F {
+    fn line(&self, line: fmt::Arguments<'_>) {
+        self(line)
+    }
+}
+
+/// Reader or writer dumping the bytes read or written to a [`TraceSink`].
+///
+/// Lines of received bytes start with `RX`, of sent bytes with `TX`, followed by
+/// the offset of the first byte within the bytes received or sent so far.
+/// Every read and write starts a new line.
+pub struct Traced<T, S> {
+    inner: T,
+    sink: S,
+    received: usize,
+    sent: usize,
+}
+
+impl<T, S: TraceSink> Traced<T, S> {
+    pub fn new(inner: T, sink: S) -> Self {
+        Self {
+            inner,
+            sink,
+            received: 0,
+            sent: 0,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: ErrorType, S> ErrorType for Traced<T, S> {
+    type Error = T::Error;
+}
+
+impl<T: Read, S: TraceSink> Read for Traced<T, S> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let read = self.inner.read(buf).await?;
+        dump(&self.sink, "RX", self.received, &buf[..read]);
+        self.received += read;
+        Ok(read)
+    }
+}
+
+impl<T: Write, S: TraceSink> Write for Traced<T, S> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        let written = self.inner.write(buf).await?;
+        dump(&self.sink, "TX", self.sent, &buf[..written]);
+        self.sent += written;
+        Ok(written)
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush().await
+    }
+}
+
+fn dump(sink: &impl TraceSink, direction: &str, offset: usize, bytes: &[u8]) {
+    for (i, bytes) in bytes.chunks(LINE).enumerate() {
+        sink.line(format_args!(
+            "{direction} {:08x}  {}",
+            offset + i * LINE,
+            HexLine(bytes)
+        ));
+    }
+}
+
+/// Formats up to [`LINE`] bytes as hex followed by their printable ASCII characters.
+struct HexLine<'a>(&'a [u8]);
+
+impl<'a> fmt::Display for HexLine<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{byte:02x} ")?;
+        }
+        for _ in self.0.len()..LINE {
+            f.write_str("   ")?;
+        }
+        f.write_str(" ")?;
+        for &byte in self.0 {
+            let c = if byte.is_ascii_graphic() || byte == b' ' {
+                byte as char
+            } else {
+                '.'
+            };
+            fmt::Write::write_char(f, c)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,231 @@
+//! Helpers to build URLs, e.g. for `Location` headers, and to decode their components.
+//!
+//! Values inserted into a URL must be percent-encoded according to where they are
+//! placed, a `/` is fine within a path but must be encoded within a path segment:
+//!
+//! ```
+//! use low_profile::url::{encode, join, COMPONENT, PATH_SEGMENT};
+//!
+//! let location = format!(
+//!     "/files/{}?owner={}",
+//!     encode("a/b c.txt", &PATH_SEGMENT),
+//!     encode("me & you", &COMPONENT),
+//! );
+//! assert_eq!(location, "/files/a%2Fb%20c.txt?owner=me%20%26%20you");
+//!
+//! let next = join::<64>("/api/v1/items", "../v2/items?page=2").unwrap();
+//! assert_eq!(next, "/api/v2/items?page=2");
+//! ```
+
+use core::fmt::{self, Write as _};
+
+/// Set of ASCII bytes which are percent-encoded, non-ASCII bytes are always encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AsciiSet {
+    mask: u128,
+}
+
+impl AsciiSet {
+    /// Only encodes non-ASCII bytes.
+    pub const EMPTY: Self = Self { mask: 0 };
+
+    /// Adds `byte` to the set, it has to be ASCII.
+    pub const fn add(self, byte: u8) -> Self {
+        assert!(byte.is_ascii());
+        Self {
+            mask: self.mask | 1 << byte,
+        }
+    }
+
+    /// Removes `byte` from the set.
+    pub const fn remove(self, byte: u8) -> Self {
+        assert!(byte.is_ascii());
+        Self {
+            mask: self.mask & !(1 << byte),
+        }
+    }
+
+    const fn add_all(mut self, bytes: &[u8]) -> Self {
+        let mut i = 0;
+        while i < bytes.len() {
+            self = self.add(bytes[i]);
+            i += 1;
+        }
+        self
+    }
+
+    /// Returns `true` if `byte` is encoded.
+    pub const fn contains(&self, byte: u8) -> bool {
+        !byte.is_ascii() || self.mask & (1 << byte) != 0
+    }
+}
+
+/// Control characters, space and `%`, which are never valid within a URL.
+pub const CONTROLS: AsciiSet = AsciiSet {
+    // 0x00..=0x1F and 0x7F.
+    mask: 0xFFFF_FFFF | 1 << 0x7F,
+}
+.add_all(b" %");
+
+/// Encodes everything which is not allowed within a path, keeping `/`.
+pub const PATH: AsciiSet = CONTROLS.add_all(b"\"#<>?`{}[]\\^|");
+
+/// Encodes everything which is not allowed within a single path segment, including `/`.
+pub const PATH_SEGMENT: AsciiSet = PATH.add(b'/');
+
+/// Encodes everything but unreserved characters, safe for query keys and values
+/// as well as any other component.
+pub const COMPONENT: AsciiSet = CONTROLS.add_all(b"!\"#$&'()*+,/:;<=>?@[\\]^`{|}");
+
+/// Percent-encodes `input` with the bytes in `set`, see [`encode`].
+#[derive(Debug, Clone, Copy)]
+pub struct PercentEncode<'a> {
+    input: &'a str,
+    set: &'a AsciiSet,
+}
+
+/// Percent-encodes the bytes of `input` contained in `set` when formatted.
+pub fn encode<'a>(input: &'a str, set: &'a AsciiSet) -> PercentEncode<'a> {
+    PercentEncode { input, set }
+}
+
+impl<'a> fmt::Display for PercentEncode<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut rest = self.input;
+        while !rest.is_empty() {
+            let plain = rest
+                .bytes()
+                .position(|byte| self.set.contains(byte))
+                .unwrap_or(rest.len());
+            f.write_str(&rest[..plain])?;
+
+            let encoded = rest[plain..]
+                .bytes()
+                .position(|byte| !self.set.contains(byte))
+                .map_or(rest.len(), |i| plain + i);
+            for byte in rest[plain..encoded].bytes() {
+                write!(f, "%{byte:02X}")?;
+            }
+            rest = &rest[encoded..];
+        }
+        Ok(())
+    }
+}
+
+/// Error returned when percent-decoding fails, see [`decode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// A `%` is not followed by two hex digits.
+    Escape,
+    /// The decoded bytes are not valid UTF-8.
+    Utf8,
+    /// The decoded input does not fit into the buffer.
+    Capacity,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Escape => "invalid percent escape",
+            Self::Utf8 => "invalid UTF-8",
+            Self::Capacity => "buffer too small",
+        })
+    }
+}
+
+/// Percent-decodes `input` into `buf`, returning the decoded part of `buf`.
+///
+/// The decoded string is never longer than `input`.
+///
+/// ```
+/// use low_profile::url::{decode, decode_form};
+///
+/// let mut buf = [0; 32];
+/// assert_eq!(decode("/files/my%20file.txt", &mut buf), Ok("/files/my file.txt"));
+/// assert_eq!(decode_form("Hello+World%21", &mut buf), Ok("Hello World!"));
+/// ```
+pub fn decode<'b>(input: &str, buf: &'b mut [u8]) -> Result<&'b str, DecodeError> {
+    decode_into(input, false, buf)
+}
+
+/// Percent-decodes `input` like [`decode`] and decodes `+` to a space, as used in
+/// query strings and `application/x-www-form-urlencoded` bodies.
+pub fn decode_form<'b>(input: &str, buf: &'b mut [u8]) -> Result<&'b str, DecodeError> {
+    decode_into(input, true, buf)
+}
+
+fn decode_into<'b>(input: &str, plus: bool, buf: &'b mut [u8]) -> Result<&'b str, DecodeError> {
+    let mut bytes = input.bytes();
+    let mut len = 0;
+    while let Some(byte) = bytes.next() {
+        let byte = match byte {
+            b'%' => {
+                let mut digit = || {
+                    bytes
+                        .next()
+                        .and_then(|c| (c as char).to_digit(16))
+                        .ok_or(DecodeError::Escape)
+                };
+                (digit()? << 4 | digit()?) as u8
+            }
+            b'+' if plus => b' ',
+            byte => byte,
+        };
+        *buf.get_mut(len).ok_or(DecodeError::Capacity)? = byte;
+        len += 1;
+    }
+    core::str::from_utf8(&buf[..len]).map_err(|_| DecodeError::Utf8)
+}
+
+/// Resolves the path `reference` relative to the path `base`, like a browser
+/// resolves a relative link, and removes `.` and `..` segments.
+///
+/// An absolute `reference` replaces the path of `base`, a query of `reference` is kept.
+/// Fails if the result is longer than `N` bytes.
+pub fn join<const N: usize>(
+    base: &str,
+    reference: &str,
+) -> Result<heapless::String<N>, fmt::Error> {
+    let (path, query) = match reference.find('?') {
+        Some(i) => reference.split_at(i),
+        None => (reference, ""),
+    };
+    let base = base.split(['?', '#']).next().unwrap_or_default();
+
+    let (dir, path) = match path {
+        "" => ("", base),
+        path if path.starts_with('/') => ("", path),
+        // Merge with the base path up to and including its last `/`.
+        path => (&base[..base.rfind('/').map_or(0, |i| i + 1)], path),
+    };
+
+    // `dir` is empty or ends with `/`, `path` replaces its empty last segment.
+    let mut dir = dir.split('/');
+    dir.next_back();
+    let mut segments = dir.chain(path.split('/')).peekable();
+    if segments.peek() == Some(&"") {
+        segments.next();
+    }
+
+    let mut out = heapless::String::new();
+    while let Some(segment) = segments.next() {
+        match segment {
+            "." => {}
+            ".." => out.truncate(out.rfind('/').unwrap_or(0)),
+            segment => {
+                write!(out, "/{segment}")?;
+                continue;
+            }
+        }
+        // A trailing dot segment refers to the directory.
+        if segments.peek().is_none() {
+            out.write_char('/')?;
+        }
+    }
+    if out.is_empty() {
+        out.write_char('/')?;
+    }
+
+    out.write_str(query)?;
+    Ok(out)
+}
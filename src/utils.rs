@@ -1,13 +1,14 @@
 use core::{
     fmt,
     fmt::Debug,
-    future::Future,
-    task::{Context, Poll},
+    future::{poll_fn, Future},
+    marker::PhantomData,
+    task::{Context, Poll, Waker},
 };
 
 use heapless::Vec;
 
-use crate::Write;
+use crate::{either::Either, Write};
 
 /// Adapter to use the `write!()` macro with the async `Write` trait.
 ///
@@ -120,6 +121,136 @@ impl<E: fmt::Debug> fmt::Display for WriteFmtError<E> {
     }
 }
 
+/// Size of the value returned by `f`, without calling `f`.
+pub(crate) fn return_size<A, B, F, T>(_f: &F) -> usize
+where
+    F: FnOnce(A, B) -> T,
+{
+    core::mem::size_of::<T>()
+}
+
+/// Compile time assertion that the value returned by `f` is at most `MAX` bytes.
+pub(crate) fn assert_return_size<A, B, F, T, const MAX: usize>(_f: &F)
+where
+    F: FnOnce(A, B) -> T,
+{
+    struct Assert<T, const MAX: usize>(PhantomData<T>);
+
+    impl<T, const MAX: usize> Assert<T, MAX> {
+        const OK: () = assert!(core::mem::size_of::<T>() <= MAX, "size limit exceeded");
+    }
+
+    #[allow(clippy::let_unit_value)]
+    let () = Assert::<T, MAX>::OK;
+}
+
+/// Returns `true` if the comma separated header value `list` contains `token`,
+/// ignoring case, e.g. `close` in `Connection: keep-alive, Close`.
+pub(crate) fn has_token(list: &str, token: &str) -> bool {
+    list.split(',')
+        .any(|item| item.trim().eq_ignore_ascii_case(token))
+}
+
+/// 64 bit FNV-1a hash, usable in constants.
+pub(crate) const fn fnv1a(data: &[u8]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    let mut i = 0;
+    while i < data.len() {
+        hash ^= data[i] as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+        i += 1;
+    }
+    hash
+}
+
+/// Formats a string as a quoted and escaped JSON string.
+pub(crate) struct JsonStr<'a>(pub &'a str);
+
+impl<'a> fmt::Display for JsonStr<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use fmt::Write as _;
+
+        f.write_char('"')?;
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                '\r' => f.write_str("\\r")?,
+                '\t' => f.write_str("\\t")?,
+                c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+                c => f.write_char(c)?,
+            }
+        }
+        f.write_char('"')
+    }
+}
+
+/// Formats a string with HTML special characters escaped.
+pub(crate) struct HtmlStr<'a>(pub &'a str);
+
+impl<'a> fmt::Display for HtmlStr<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use fmt::Write as _;
+
+        for c in self.0.chars() {
+            match c {
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '&' => f.write_str("&amp;")?,
+                '"' => f.write_str("&quot;")?,
+                '\'' => f.write_str("&#39;")?,
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Wakers of tasks waiting for the same event.
+///
+/// Keeps up to `N` distinct wakers, registering more wakes all registered tasks
+/// early, which then register again when polled.
+pub(crate) struct WakerSet<const N: usize>(Vec<Waker, N>);
+
+impl<const N: usize> WakerSet<N> {
+    pub const fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn register(&mut self, waker: &Waker) {
+        if self.0.iter().any(|registered| registered.will_wake(waker)) {
+            return;
+        }
+        if self.0.is_full() {
+            self.wake_all();
+        }
+        // Cannot fail, the set was emptied if full.
+        let _ = self.0.push(waker.clone());
+    }
+
+    pub fn wake_all(&mut self) {
+        core::mem::take(&mut self.0)
+            .into_iter()
+            .for_each(Waker::wake);
+    }
+}
+
+/// Polls both futures until one of them completes, dropping the other one.
+///
+/// `a` is polled first.
+pub(crate) async fn select<A: Future, B: Future>(a: A, b: B) -> Either<A::Output, B::Output> {
+    let mut a = core::pin::pin!(a);
+    let mut b = core::pin::pin!(b);
+    poll_fn(|cx| {
+        if let Poll::Ready(output) = a.as_mut().poll(cx) {
+            return Poll::Ready(Either::Left(output));
+        }
+        b.as_mut().poll(cx).map(Either::Right)
+    })
+    .await
+}
+
 /// Re-Implementation of [`futures::FuturesExt::now_or_never`].
 ///
 /// Evaluates and consumes the future, returning the resulting output
@@ -0,0 +1,181 @@
+//! Parsers for values commonly exchanged with devices.
+//!
+//! All types implement [`FromStr`] and [`Display`](fmt::Display), so they can be used
+//! wherever extractors parse strings, and round trip through responses.
+
+pub use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use core::{fmt, str::FromStr, time};
+
+/// Error returned when parsing a value fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidValue {
+    /// The input has the wrong length or amount of components.
+    Length,
+    /// The input contains an unexpected character.
+    Character,
+    /// The duration has no or an unknown unit.
+    Unit,
+    /// The value does not fit the target type.
+    Overflow,
+}
+
+impl fmt::Display for InvalidValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Length => "invalid length",
+            Self::Character => "invalid character",
+            Self::Unit => "invalid unit",
+            Self::Overflow => "value too large",
+        })
+    }
+}
+
+fn hex_digit(c: u8) -> Result<u8, InvalidValue> {
+    match c {
+        b'0'..=b'9' => Ok(c - b'0'),
+        b'a'..=b'f' => Ok(c - b'a' + 10),
+        b'A'..=b'F' => Ok(c - b'A' + 10),
+        _ => Err(InvalidValue::Character),
+    }
+}
+
+fn hex_byte(s: &[u8]) -> Result<u8, InvalidValue> {
+    match s {
+        [high, low] => Ok(hex_digit(*high)? << 4 | hex_digit(*low)?),
+        _ => Err(InvalidValue::Length),
+    }
+}
+
+/// Exactly `N` bytes, encoded as `2 * N` hex digits.
+///
+/// ```
+/// use low_profile::value::Hex;
+///
+/// let key: Hex<4> = "DEADbeef".parse().unwrap();
+/// assert_eq!(key.0, [0xde, 0xad, 0xbe, 0xef]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Hex<const N: usize>(pub [u8; N]);
+
+impl<const N: usize> FromStr for Hex<N> {
+    type Err = InvalidValue;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != 2 * N {
+            return Err(InvalidValue::Length);
+        }
+
+        let mut bytes = [0; N];
+        for (byte, digits) in bytes.iter_mut().zip(s.as_bytes().chunks_exact(2)) {
+            *byte = hex_byte(digits)?;
+        }
+        Ok(Self(bytes))
+    }
+}
+
+impl<const N: usize> fmt::Display for Hex<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+    }
+}
+
+/// An EUI-48 MAC address.
+///
+/// Parses six hex encoded bytes separated by either `:` or `-`,
+/// displays as lowercase and `:` separated.
+///
+/// ```
+/// use low_profile::value::MacAddr;
+///
+/// let mac: MacAddr = "02-00-5E-10-00-01".parse().unwrap();
+/// assert_eq!(mac.to_string(), "02:00:5e:10:00:01");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MacAddr(pub [u8; 6]);
+
+impl FromStr for MacAddr {
+    type Err = InvalidValue;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let separator = match s.as_bytes().get(2) {
+            Some(b':') => ':',
+            Some(b'-') => '-',
+            _ => return Err(InvalidValue::Character),
+        };
+
+        let mut bytes = [0; 6];
+        let mut parts = s.split(separator);
+        for byte in bytes.iter_mut() {
+            *byte = hex_byte(parts.next().ok_or(InvalidValue::Length)?.as_bytes())?;
+        }
+        if parts.next().is_some() {
+            return Err(InvalidValue::Length);
+        }
+        Ok(Self(bytes))
+    }
+}
+
+impl fmt::Display for MacAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [a, b, c, d, e, g] = self.0;
+        write!(f, "{a:02x}:{b:02x}:{c:02x}:{d:02x}:{e:02x}:{g:02x}")
+    }
+}
+
+/// A duration written as an integer followed by a unit.
+///
+/// Supported units are `ns`, `us`, `ms`, `s`, `m` and `h`.
+///
+/// ```
+/// use core::time;
+/// use low_profile::value::Duration;
+///
+/// let timeout: Duration = "250ms".parse().unwrap();
+/// assert_eq!(timeout.0, time::Duration::from_millis(250));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Duration(pub time::Duration);
+
+impl FromStr for Duration {
+    type Err = InvalidValue;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split = s
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or(InvalidValue::Unit)?;
+        let (value, unit) = s.split_at(split);
+
+        if value.is_empty() {
+            return Err(InvalidValue::Character);
+        }
+        let value: u64 = value.parse().map_err(|_| InvalidValue::Overflow)?;
+
+        let duration = match unit {
+            "ns" => time::Duration::from_nanos(value),
+            "us" => time::Duration::from_micros(value),
+            "ms" => time::Duration::from_millis(value),
+            "s" => time::Duration::from_secs(value),
+            "m" => time::Duration::from_secs(value.checked_mul(60).ok_or(InvalidValue::Overflow)?),
+            "h" => {
+                time::Duration::from_secs(value.checked_mul(60 * 60).ok_or(InvalidValue::Overflow)?)
+            }
+            _ => return Err(InvalidValue::Unit),
+        };
+        Ok(Self(duration))
+    }
+}
+
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let nanos = self.0.subsec_nanos();
+        if nanos == 0 {
+            write!(f, "{}s", self.0.as_secs())
+        } else if self.0.subsec_millis() * 1_000_000 == nanos {
+            write!(f, "{}ms", self.0.as_millis())
+        } else if self.0.subsec_micros() * 1_000 == nanos {
+            write!(f, "{}us", self.0.as_micros())
+        } else {
+            write!(f, "{}ns", self.0.as_nanos())
+        }
+    }
+}
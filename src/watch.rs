@@ -0,0 +1,230 @@
+//! Change notifications for state shared with streaming handlers.
+//!
+//! Long running responses, e.g. server sent events, borrow the state for the
+//! whole stream. Wrapping values with [`Watched`] lets them wait for changes instead
+//! of polling and always observe the value also visible to other handlers:
+//!
+//! ```
+//! use low_profile::{extract::State, watch::{Subscriber, Watched}, Router};
+//!
+//! struct App {
+//!     temperature: Watched<i32>,
+//! }
+//!
+//! // Long polling, answers with the next temperature reading.
+//! async fn next_temperature(State(app): State<&App>) -> heapless::String<16> {
+//!     let value = Subscriber::new(&app.temperature).next().await;
+//!     let mut body = heapless::String::new();
+//!     core::fmt::write(&mut body, format_args!("{value}")).unwrap();
+//!     body
+//! }
+//!
+//! let app = App { temperature: Watched::new(20) };
+//! let router = Router::new()
+//!     .get("/temperature/next", next_temperature)
+//!     .with_state(&app);
+//! ```
+
+use core::{
+    cell::RefCell,
+    future::{poll_fn, Future},
+    task::Poll,
+};
+
+use crate::utils::WakerSet;
+
+/// Maximum amount of waiting subscribers woken individually, more subscribers
+/// are still notified but are woken spuriously.
+const WAITERS: usize = 4;
+
+/// A value which can be waited on for changes.
+///
+/// Implemented by [`Watched`] and, with the `std` feature, by `SyncWatched`. Other
+/// synchronization primitives, e.g. the mutexes of `embassy-sync` for state shared
+/// with interrupts, can implement it to be used with [`Subscriber`].
+pub trait Watch {
+    type Value;
+
+    /// Returns the current value and its version, which changes with every update.
+    fn get(&self) -> (Self::Value, u64);
+
+    /// Waits until the version differs from `version`.
+    fn changed(&self, version: u64) -> impl Future<Output = ()>;
+}
+
+/// A value shared between handlers, notifying subscribers about changes.
+///
+/// Not thread safe, it is meant for state shared by tasks of a single executor.
+pub struct Watched<T> {
+    inner: RefCell<Inner<T>>,
+}
+
+struct Inner<T> {
+    value: T,
+    version: u64,
+    wakers: WakerSet<WAITERS>,
+}
+
+impl<T> Watched<T> {
+    pub const fn new(value: T) -> Self {
+        Self {
+            inner: RefCell::new(Inner {
+                value,
+                version: 0,
+                wakers: WakerSet::new(),
+            }),
+        }
+    }
+
+    /// Replaces the value and notifies subscribers.
+    pub fn set(&self, value: T) {
+        self.update(|current| *current = value);
+    }
+
+    /// Modifies the value in place and notifies subscribers.
+    pub fn update<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        let mut inner = self.inner.borrow_mut();
+        let result = f(&mut inner.value);
+        inner.version += 1;
+        inner.wakers.wake_all();
+        result
+    }
+
+    /// Calls `f` with a reference to the current value.
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.inner.borrow().value)
+    }
+}
+
+impl<T: Clone> Watch for Watched<T> {
+    type Value = T;
+
+    fn get(&self) -> (T, u64) {
+        let inner = self.inner.borrow();
+        (inner.value.clone(), inner.version)
+    }
+
+    fn changed(&self, version: u64) -> impl Future<Output = ()> {
+        poll_fn(move |cx| {
+            let mut inner = self.inner.borrow_mut();
+            if inner.version != version {
+                return Poll::Ready(());
+            }
+            inner.wakers.register(cx.waker());
+            Poll::Pending
+        })
+    }
+}
+
+impl<T: Default> Default for Watched<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+/// A value shared across threads, notifying subscribers about changes.
+///
+/// Like [`Watched`], but guarded by a mutex of the standard library, e.g. for state
+/// updated by a thread outside the executor serving the requests.
+///
+/// A panic within [`update`](Self::update) may leave the value partially modified,
+/// all further accesses panic instead of handing it out.
+#[cfg(feature = "std")]
+pub struct SyncWatched<T> {
+    inner: std::sync::Mutex<Inner<T>>,
+}
+
+#[cfg(feature = "std")]
+impl<T> SyncWatched<T> {
+    pub const fn new(value: T) -> Self {
+        Self {
+            inner: std::sync::Mutex::new(Inner {
+                value,
+                version: 0,
+                wakers: WakerSet::new(),
+            }),
+        }
+    }
+
+    /// Replaces the value and notifies subscribers.
+    pub fn set(&self, value: T) {
+        self.update(|current| *current = value);
+    }
+
+    /// Modifies the value in place and notifies subscribers.
+    pub fn update<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        let mut inner = self.lock();
+        let result = f(&mut inner.value);
+        inner.version += 1;
+        inner.wakers.wake_all();
+        result
+    }
+
+    /// Calls `f` with a reference to the current value.
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.lock().value)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner<T>> {
+        self.inner
+            .lock()
+            .expect("value poisoned by a panicking update")
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Clone> Watch for SyncWatched<T> {
+    type Value = T;
+
+    fn get(&self) -> (T, u64) {
+        let inner = self.lock();
+        (inner.value.clone(), inner.version)
+    }
+
+    fn changed(&self, version: u64) -> impl Future<Output = ()> {
+        poll_fn(move |cx| {
+            let mut inner = self.lock();
+            if inner.version != version {
+                return Poll::Ready(());
+            }
+            inner.wakers.register(cx.waker());
+            Poll::Pending
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Default> Default for SyncWatched<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+/// Receives the changes of a [`Watch`].
+///
+/// Intermediate values are skipped if the value changes faster than it is received.
+pub struct Subscriber<'w, W> {
+    watch: &'w W,
+    version: u64,
+}
+
+impl<'w, W: Watch> Subscriber<'w, W> {
+    /// Subscribes to changes after the current value.
+    pub fn new(watch: &'w W) -> Self {
+        let (_, version) = watch.get();
+        Self { watch, version }
+    }
+
+    /// Returns the current value without waiting, marking it as seen.
+    pub fn current(&mut self) -> W::Value {
+        let (value, version) = self.watch.get();
+        self.version = version;
+        value
+    }
+
+    /// Waits for a value not seen yet and returns it.
+    pub async fn next(&mut self) -> W::Value {
+        self.watch.changed(self.version).await;
+        self.current()
+    }
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use low_profile::{Router, ServiceConfig};
+
+fn router() -> Router<(), impl low_profile::Route<()>, (), impl Sized> {
+    Router::new()
+        .post("/upload", |body: heapless::Vec<u8, 64>| async move { body })
+        .with_config(ServiceConfig::new().max_content_length(8))
+}
+
+#[tokio::test]
+async fn content_length_over_limit() {
+    let output = common::serve(
+        &router(),
+        "POST /upload HTTP/1.1\r\nContent-Length: 9\r\n\r\n123456789",
+    )
+    .await;
+    assert!(
+        output.starts_with("HTTP/1.1 413 Payload Too Large\r\n"),
+        "{output}"
+    );
+}
+
+#[tokio::test]
+async fn chunked_within_limit() {
+    let output = common::serve(
+        &router(),
+        "POST /upload HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n4\r\n1234\r\n4\r\n5678\r\n0\r\n\r\n",
+    )
+    .await;
+    assert!(output.starts_with("HTTP/1.1 200 OK\r\n"), "{output}");
+    assert!(output.ends_with("\r\n\r\n12345678"), "{output}");
+}
+
+#[tokio::test]
+async fn chunked_over_limit() {
+    let output = common::serve(
+        &router(),
+        "POST /upload HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n4\r\n1234\r\n5\r\n56789\r\n0\r\n\r\n",
+    )
+    .await;
+    assert!(
+        output.starts_with("HTTP/1.1 413 Payload Too Large\r\n"),
+        "{output}"
+    );
+    assert!(output.contains("Connection: close\r\n"), "{output}");
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use low_profile::Router;
+
+fn router() -> Router<(), impl low_profile::Route<()>, (), impl Sized> {
+    Router::new().post("/echo", |body: heapless::Vec<u8, 64>| async move { body })
+}
+
+async fn assert_malformed(body: &str) {
+    let input = format!("POST /echo HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n{body}");
+    let output = common::serve(&router(), &input).await;
+    assert!(
+        output.starts_with("HTTP/1.1 400 Bad Request\r\n"),
+        "{output}"
+    );
+    assert!(output.contains("Connection: close\r\n"), "{output}");
+}
+
+#[tokio::test]
+async fn complete() {
+    let output = common::serve_chunked(
+        &router(),
+        "POST /echo HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n\
+         5;ext=1\r\nhello\r\n6\r\n world\r\n0\r\nTrailer: 1\r\n\r\n",
+        3,
+    )
+    .await;
+    assert!(output.starts_with("HTTP/1.1 200 OK\r\n"), "{output}");
+    assert!(output.ends_with("\r\n\r\nhello world"), "{output}");
+}
+
+#[tokio::test]
+async fn truncated_chunk() {
+    assert_malformed("5\r\nhel").await;
+}
+
+#[tokio::test]
+async fn missing_last_chunk() {
+    assert_malformed("5\r\nhello\r\n").await;
+    assert_malformed("5\r\nhello\r\n0\r\n").await;
+}
+
+#[tokio::test]
+async fn bad_size_line() {
+    assert_malformed("zz\r\nhello\r\n0\r\n\r\n").await;
+    assert_malformed(&format!("{}\r\n", "1".repeat(100))).await;
+    assert_malformed("fffffffffffffffffffff\r\nhello\r\n0\r\n\r\n").await;
+}
+
+#[tokio::test]
+async fn missing_crlf() {
+    assert_malformed("5\r\nhelloX\r\n0\r\n\r\n").await;
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use low_profile::{coalesce::coalesce, Router};
+
+async fn reading(calls: &'static AtomicUsize) -> heapless::String<8> {
+    let call = calls.fetch_add(1, Ordering::Relaxed);
+    // Gives the other request the chance to arrive while this one runs.
+    for _ in 0..4 {
+        tokio::task::yield_now().await;
+    }
+    heapless::String::try_from(if call == 0 { "first" } else { "second" }).unwrap()
+}
+
+async fn serve_both(calls: &'static AtomicUsize, first: &str, second: &str) -> (String, String) {
+    let router = Router::<(), _>::new().get("/sensor", coalesce(move || reading(calls)));
+    tokio::join!(
+        common::serve(&router, first),
+        common::serve(&router, second)
+    )
+}
+
+#[tokio::test]
+async fn identical_requests_share_response() {
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+    let request = "GET /sensor HTTP/1.1\r\nConnection: close\r\n\r\n";
+    let (first, second) = serve_both(&CALLS, request, request).await;
+    assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+    assert!(first.ends_with("\r\n\r\nfirst"), "{first}");
+    assert!(second.ends_with("\r\n\r\nfirst"), "{second}");
+}
+
+#[tokio::test]
+async fn requests_with_credentials_not_shared() {
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+    let (first, second) = serve_both(
+        &CALLS,
+        "GET /sensor HTTP/1.1\r\nAuthorization: Basic YWRtaW46YWRtaW4=\r\nConnection: close\r\n\r\n",
+        "GET /sensor HTTP/1.1\r\nConnection: close\r\n\r\n",
+    )
+    .await;
+    assert_eq!(CALLS.load(Ordering::Relaxed), 2);
+    assert!(first.ends_with("\r\n\r\nfirst"), "{first}");
+    assert!(second.ends_with("\r\n\r\nsecond"), "{second}");
+
+    let (_, second) = serve_both(
+        &CALLS,
+        "GET /sensor HTTP/1.1\r\nConnection: close\r\n\r\n",
+        "GET /sensor HTTP/1.1\r\nCookie: session=1\r\nConnection: close\r\n\r\n",
+    )
+    .await;
+    assert_eq!(CALLS.load(Ordering::Relaxed), 4);
+    assert!(second.ends_with("\r\n\r\nsecond"), "{second}");
+}
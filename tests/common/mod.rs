@@ -0,0 +1,72 @@
+//! In-memory connections for serving requests in tests.
+
+#![allow(dead_code)]
+
+use core::convert::Infallible;
+
+use low_profile::{ErrorType, Read, Service, Write};
+
+/// Client side of a connection, sending `data` in reads of at most `chunk` bytes.
+pub struct Input<'a> {
+    data: &'a [u8],
+    chunk: usize,
+}
+
+impl<'a> Input<'a> {
+    /// Sends `data` in reads as large as requested.
+    pub fn new(data: &'a str) -> Self {
+        Self {
+            data: data.as_bytes(),
+            chunk: usize::MAX,
+        }
+    }
+
+    /// Returns `true` once all data was read.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
+impl ErrorType for Input<'_> {
+    type Error = Infallible;
+}
+
+impl Read for Input<'_> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Infallible> {
+        let len = buf.len().min(self.data.len()).min(self.chunk);
+        buf[..len].copy_from_slice(&self.data[..len]);
+        self.data = &self.data[len..];
+        Ok(len)
+    }
+}
+
+/// Everything written to the client.
+pub struct Output(pub Vec<u8>);
+
+impl ErrorType for Output {
+    type Error = Infallible;
+}
+
+impl Write for Output {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Infallible> {
+        self.0.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+}
+
+/// Serves `input` on a single connection, returns the bytes sent to the client.
+pub async fn serve<S: Service>(service: &S, input: &str) -> String {
+    serve_chunked(service, input, usize::MAX).await
+}
+
+/// Like [`serve`], receiving the input in reads of at most `chunk` bytes.
+pub async fn serve_chunked<S: Service>(service: &S, input: &str, chunk: usize) -> String {
+    let mut output = Output(Vec::new());
+    let input = Input {
+        data: input.as_bytes(),
+        chunk,
+    };
+    // Failures show in the output, e.g. as a connection closed without response.
+    let _ = service.serve(input, &mut output).await;
+    String::from_utf8(output.0).unwrap()
+}
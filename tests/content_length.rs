@@ -0,0 +1,46 @@
+mod common;
+
+use low_profile::{response::StaticChunks, Response, Router};
+
+type Chunks = StaticChunks<core::array::IntoIter<&'static [u8], 2>>;
+
+async fn short() -> Response<Chunks> {
+    Response::new(StaticChunks::new([&b"ab"[..], b"c"].into_iter()).with_len(5))
+}
+
+async fn long() -> Response<Chunks> {
+    Response::new(StaticChunks::new([&b"abc"[..], b"defgh"].into_iter()).with_len(5))
+}
+
+fn router() -> Router<(), impl low_profile::Route<()>, (), impl Sized> {
+    Router::new()
+        .get("/", || async { "index" })
+        .get("/short", short)
+        .get("/long", long)
+}
+
+#[tokio::test]
+async fn body_shorter_than_declared() {
+    let output = common::serve(
+        &router(),
+        "GET /short HTTP/1.1\r\n\r\nGET / HTTP/1.1\r\n\r\n",
+    )
+    .await;
+    assert!(output.starts_with("HTTP/1.1 200 OK\r\n"), "{output}");
+    assert!(output.contains("Content-Length: 5\r\n"), "{output}");
+    assert!(output.ends_with("\r\n\r\nabc"), "{output}");
+    // The client still waits for the missing bytes, the connection is not reused.
+    assert_eq!(output.matches("HTTP/1.1").count(), 1, "{output}");
+}
+
+#[tokio::test]
+async fn body_longer_than_declared() {
+    let output = common::serve(
+        &router(),
+        "GET /long HTTP/1.1\r\n\r\nGET / HTTP/1.1\r\nConnection: close\r\n\r\n",
+    )
+    .await;
+    let (long, index) = output.split_at(output.rfind("HTTP/1.1").unwrap());
+    assert!(long.ends_with("Content-Length: 5\r\n\r\nabcde"), "{output}");
+    assert!(index.ends_with("\r\n\r\nindex"), "{output}");
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use low_profile::{response::ResponseHeaders, IntoResponse, Router};
+
+async fn cached() -> impl IntoResponse {
+    let mut headers = ResponseHeaders::<2, 64>::new();
+    headers.append("Cache-Control", "max-age=60").unwrap();
+    "cached"
+        .into_response()
+        .with_default_headers(&[("Cache-Control", "no-store"), ("X-Frame-Options", "DENY")])
+        .with_headers(headers)
+}
+
+fn router() -> Router<(), impl low_profile::Route<()>, (), impl Sized> {
+    Router::new()
+        .get("/", cached)
+        .get("/plain", || async { "plain" })
+        .default_headers(&[
+            ("Cache-Control", "no-cache"),
+            ("Content-Type", "application/json"),
+            ("Date", "Thu, 01 Jan 1970 00:00:00 GMT"),
+        ])
+}
+
+#[tokio::test]
+async fn nested_defaults_keep_written_headers() {
+    let output = common::serve(&router(), "GET / HTTP/1.1\r\nConnection: close\r\n\r\n").await;
+    assert_eq!(output.matches("Cache-Control").count(), 1, "{output}");
+    assert!(output.contains("Cache-Control: max-age=60\r\n"), "{output}");
+    assert!(output.contains("X-Frame-Options: DENY\r\n"), "{output}");
+}
+
+#[tokio::test]
+async fn managed_headers_skipped() {
+    let output = common::serve(
+        &router(),
+        "GET /plain HTTP/1.1\r\nConnection: close\r\n\r\n",
+    )
+    .await;
+    assert!(output.contains("Cache-Control: no-cache\r\n"), "{output}");
+    assert_eq!(output.matches("Content-Type").count(), 1, "{output}");
+    assert!(!output.contains("application/json"), "{output}");
+    assert!(!output.contains("Date"), "{output}");
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use low_profile::{digest, digest::Digested, Router, ServiceConfig};
+
+async fn content_digest(body: &'static str) -> String {
+    let router = Router::<(), _>::new().get("/", move || async move { Digested::new(body) });
+    let output = common::serve(&router, "GET / HTTP/1.1\r\nConnection: close\r\n\r\n").await;
+    let start = output.find("Content-Digest: ").expect(&output) + "Content-Digest: ".len();
+    output[start..start + output[start..].find("\r\n").unwrap()].to_string()
+}
+
+#[tokio::test]
+async fn sha256_known_answers() {
+    assert_eq!(
+        content_digest("").await,
+        "sha-256=:47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=:"
+    );
+    assert_eq!(
+        content_digest("abc").await,
+        "sha-256=:ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0=:"
+    );
+    assert_eq!(
+        content_digest("abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq").await,
+        "sha-256=:JI1qYdIGOLjlwCaTDD5gOaM85Flk/yFn9uzt1BnbBsE=:"
+    );
+}
+
+fn router() -> Router<(), impl low_profile::Route<()>, (), impl Sized> {
+    Router::new()
+        .put(
+            "/",
+            digest::verify(|body: heapless::Vec<u8, 16>| async move { body }),
+        )
+        .with_config(ServiceConfig::new().max_content_length(12))
+}
+
+#[tokio::test]
+async fn crc32c_known_answer() {
+    let output = common::serve(
+        &router(),
+        "PUT / HTTP/1.1\r\nContent-Digest: crc32c=:4waSgw==:\r\nContent-Length: 9\r\n\r\n\
+         123456789",
+    )
+    .await;
+    assert!(output.starts_with("HTTP/1.1 200 OK\r\n"), "{output}");
+    assert!(output.ends_with("\r\n\r\n123456789"), "{output}");
+
+    let output = common::serve(
+        &router(),
+        "PUT / HTTP/1.1\r\nContent-Digest: crc32c=:4waSgw==:\r\nContent-Length: 9\r\n\r\n\
+         123456780",
+    )
+    .await;
+    assert!(
+        output.starts_with("HTTP/1.1 400 Bad Request\r\n"),
+        "{output}"
+    );
+}
+
+#[tokio::test]
+async fn chunked_body() {
+    let output = common::serve(
+        &router(),
+        "PUT / HTTP/1.1\r\nContent-Digest: crc32c=:4waSgw==:\r\nTransfer-Encoding: chunked\r\n\r\n\
+         4\r\n1234\r\n5\r\n56789\r\n0\r\n\r\n",
+    )
+    .await;
+    assert!(output.starts_with("HTTP/1.1 200 OK\r\n"), "{output}");
+    assert!(output.ends_with("\r\n\r\n123456789"), "{output}");
+
+    let output = common::serve(
+        &router(),
+        "PUT / HTTP/1.1\r\nContent-Digest: crc32c=:4waSgw==:\r\nTransfer-Encoding: chunked\r\n\r\n\
+         9\r\n123456789\r\n9\r\n123456789\r\n0\r\n\r\n",
+    )
+    .await;
+    assert!(
+        output.starts_with("HTTP/1.1 413 Payload Too Large\r\n"),
+        "{output}"
+    );
+}
+
+#[tokio::test]
+async fn partial_response() {
+    let router = Router::<(), _>::new()
+        .get("/", || async { Digested::new("abc") })
+        .accept_ranges();
+    let output = common::serve(
+        &router,
+        "GET / HTTP/1.1\r\nRange: bytes=1-\r\nConnection: close\r\n\r\n",
+    )
+    .await;
+    assert!(
+        output.starts_with("HTTP/1.1 206 Partial Content\r\n"),
+        "{output}"
+    );
+    assert!(!output.contains("Content-Digest"), "{output}");
+    assert!(
+        output.contains("Repr-Digest: sha-256=:ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0=:\r\n"),
+        "{output}"
+    );
+    assert!(output.ends_with("\r\n\r\nbc"), "{output}");
+}
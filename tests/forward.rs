@@ -0,0 +1,92 @@
+mod common;
+
+use low_profile::{
+    extract::{Path, TypedHeader},
+    header::Host,
+    rewrite::Forward,
+    Either, Router,
+};
+
+async fn led(Path(id): Path<u8>) -> Either<&'static str, Forward> {
+    match id {
+        0..=3 => Either::Left("on"),
+        _ => Either::Right(Forward::to("/missing")),
+    }
+}
+
+async fn missing(host: Option<TypedHeader<Host>>) -> &'static str {
+    // The headers of the original request are kept.
+    match host {
+        Some(_) => "No such LED",
+        None => "Not found",
+    }
+}
+
+fn router() -> Router<(), impl low_profile::Route<()>, (), impl Sized> {
+    Router::new()
+        .get("/leds/:id", led)
+        .get("/missing", missing)
+        .get("/loop", || async { Forward::to("/loop") })
+        .forwarding()
+}
+
+#[tokio::test]
+async fn forwarded_to_route() {
+    let output = common::serve(
+        &router(),
+        "GET /leds/2 HTTP/1.1\r\n\r\nGET /leds/7 HTTP/1.1\r\nHost: device\r\nConnection: close\r\n\r\n",
+    )
+    .await;
+    let (first, second) = output.split_at(output.rfind("HTTP/1.1").unwrap());
+    assert!(first.ends_with("\r\n\r\non"), "{output}");
+    assert!(second.starts_with("HTTP/1.1 200 OK\r\n"), "{output}");
+    assert!(second.ends_with("\r\n\r\nNo such LED"), "{output}");
+}
+
+#[tokio::test]
+async fn forward_loop() {
+    let output = common::serve(&router(), "GET /loop HTTP/1.1\r\nConnection: close\r\n\r\n").await;
+    assert!(
+        output.starts_with("HTTP/1.1 500 Internal Server Error\r\n"),
+        "{output}"
+    );
+}
+
+#[tokio::test]
+async fn forward_without_forwarding() {
+    let router = Router::<(), _>::new().get("/", || async { Forward::to("/other") });
+    let output = common::serve(&router, "GET / HTTP/1.1\r\nConnection: close\r\n\r\n").await;
+    assert!(
+        output.starts_with("HTTP/1.1 500 Internal Server Error\r\n"),
+        "{output}"
+    );
+}
+
+#[tokio::test]
+async fn forward_through_wrapping_bodies() {
+    use low_profile::{
+        response::{AfterResponse, Chunked},
+        IntoResponse, Response,
+    };
+
+    async fn after() -> Response<AfterResponse<Forward, fn()>> {
+        Forward::to("/missing")
+            .into_response()
+            .after_response(|| {})
+    }
+    async fn chunked() -> Response<Chunked<Forward>> {
+        Response::new(Chunked(Forward::to("/missing")))
+    }
+    let router = Router::<(), _>::new()
+        .get("/missing", missing)
+        .get("/after", after)
+        .get("/chunked", chunked)
+        .forwarding();
+
+    for path in ["/after", "/chunked"] {
+        let input = format!("GET {path} HTTP/1.1\r\nConnection: close\r\n\r\n");
+        let output = common::serve(&router, &input).await;
+        assert!(output.starts_with("HTTP/1.1 200 OK\r\n"), "{output}");
+        assert!(output.ends_with("\r\n\r\nNot found"), "{output}");
+    }
+}
@@ -0,0 +1,218 @@
+mod common;
+
+use low_profile::Router;
+
+fn router() -> Router<(), impl low_profile::Route<()>, (), impl Sized> {
+    Router::new()
+        .get("/", || async { "index" })
+        .post("/echo", |body: heapless::Vec<u8, 64>| async move { body })
+}
+
+async fn assert_rejected(request: &str) {
+    // The smuggled request following the body must not be answered.
+    let input = format!("{request}GET / HTTP/1.1\r\n\r\n");
+    let output = common::serve(&router(), &input).await;
+    assert!(
+        output.starts_with("HTTP/1.1 400 Bad Request\r\n"),
+        "{output}"
+    );
+    assert!(output.contains("Connection: close\r\n"), "{output}");
+    assert_eq!(output.matches("HTTP/1.1").count(), 1, "{output}");
+}
+
+#[tokio::test]
+async fn invalid_content_length() {
+    assert_rejected("POST /echo HTTP/1.1\r\nContent-Length: 5x\r\n\r\nhello").await;
+    assert_rejected("POST /echo HTTP/1.1\r\nContent-Length: +5\r\n\r\nhello").await;
+    assert_rejected("POST /echo HTTP/1.1\r\nContent-Length: 99999999999999999999999\r\n\r\n").await;
+}
+
+#[tokio::test]
+async fn conflicting_content_lengths() {
+    assert_rejected("POST /echo HTTP/1.1\r\nContent-Length: 5\r\nContent-Length: 0\r\n\r\nhello")
+        .await;
+    assert_rejected("POST /echo HTTP/1.1\r\nContent-Length: 5, 0\r\n\r\nhello").await;
+}
+
+#[tokio::test]
+async fn content_length_with_transfer_encoding() {
+    assert_rejected(
+        "POST /echo HTTP/1.1\r\nContent-Length: 0\r\nTransfer-Encoding: chunked\r\n\r\n\
+         5\r\nhello\r\n0\r\n\r\n",
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn identical_content_lengths() {
+    let output = common::serve(
+        &router(),
+        "POST /echo HTTP/1.1\r\nContent-Length: 5\r\nContent-Length: 5, 5\r\n\r\nhello\
+         GET / HTTP/1.1\r\nConnection: close\r\n\r\n",
+    )
+    .await;
+    assert!(output.starts_with("HTTP/1.1 200 OK\r\n"), "{output}");
+    assert!(output.ends_with("\r\n\r\nindex"), "{output}");
+    assert_eq!(output.matches("HTTP/1.1 200 OK").count(), 2, "{output}");
+}
+
+#[tokio::test]
+async fn head_then_get() {
+    let output = common::serve(
+        &router(),
+        "HEAD / HTTP/1.1\r\n\r\nGET / HTTP/1.1\r\nConnection: close\r\n\r\n",
+    )
+    .await;
+    let (head, get) = output.split_at(output.rfind("HTTP/1.1").unwrap());
+    assert!(head.starts_with("HTTP/1.1 200 OK\r\n"), "{output}");
+    assert!(head.ends_with("Content-Length: 5\r\n\r\n"), "{output}");
+    assert!(get.starts_with("HTTP/1.1 200 OK\r\n"), "{output}");
+    assert!(get.ends_with("\r\n\r\nindex"), "{output}");
+}
+
+#[tokio::test]
+async fn chunked_response_to_http10() {
+    use low_profile::{response::StaticChunks, Response};
+
+    async fn chunks() -> Response<StaticChunks<core::array::IntoIter<&'static [u8], 2>>> {
+        Response::new(StaticChunks::new([&b"ab"[..], b"cd"].into_iter()))
+    }
+    let router = Router::<(), _>::new().get("/", chunks);
+
+    let output = common::serve(&router, "GET / HTTP/1.1\r\nConnection: close\r\n\r\n").await;
+    assert!(
+        output.contains("Transfer-Encoding: chunked\r\n"),
+        "{output}"
+    );
+    assert!(
+        output.ends_with("\r\n\r\n2\r\nab\r\n2\r\ncd\r\n0\r\n\r\n"),
+        "{output}"
+    );
+
+    let output = common::serve(&router, "GET / HTTP/1.0\r\n\r\n").await;
+    assert!(!output.contains("Transfer-Encoding"), "{output}");
+    assert!(
+        output.ends_with("Connection: close\r\n\r\nabcd"),
+        "{output}"
+    );
+}
+
+#[tokio::test]
+async fn static_chunks_not_copied() {
+    use low_profile::{response::StaticChunks, ErrorType, Response, Service, Write};
+
+    static PAGE: &[u8] = b"<html><body>static</body></html>";
+
+    /// Client accepting at most 5 bytes per write, counting the bytes passed from `PAGE`.
+    struct Client {
+        output: Vec<u8>,
+        from_page: usize,
+    }
+
+    impl ErrorType for Client {
+        type Error = core::convert::Infallible;
+    }
+
+    impl Write for Client {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            let buf = &buf[..buf.len().min(5)];
+            if PAGE.as_ptr_range().contains(&buf.as_ptr()) {
+                self.from_page += buf.len();
+            }
+            self.output.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+    }
+
+    async fn page() -> Response<StaticChunks<core::array::IntoIter<&'static [u8], 2>>> {
+        Response::new(StaticChunks::new([&PAGE[..12], &PAGE[12..]].into_iter()))
+    }
+    let router = Router::<(), _>::new().get("/", page);
+
+    let mut client = Client {
+        output: Vec::new(),
+        from_page: 0,
+    };
+    let input = common::Input::new("GET / HTTP/1.1\r\nConnection: close\r\n\r\n");
+    router.serve(input, &mut client).await.unwrap();
+    let output = String::from_utf8(client.output).unwrap();
+    assert!(
+        output.ends_with("static</body></html>\r\n0\r\n\r\n"),
+        "{output}"
+    );
+    assert_eq!(client.from_page, PAGE.len(), "{output}");
+}
+
+#[tokio::test]
+async fn head_allowed_for_get() {
+    let output = common::serve(&router(), "PUT / HTTP/1.1\r\nConnection: close\r\n\r\n").await;
+    assert!(
+        output.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"),
+        "{output}"
+    );
+    assert!(output.contains("Allow: GET, HEAD\r\n"), "{output}");
+}
+
+#[tokio::test]
+async fn max_requests_per_connection() {
+    use low_profile::ServiceConfig;
+
+    let router = router().with_config(ServiceConfig::new().max_requests_per_connection(2));
+    let output = common::serve(&router, &"GET / HTTP/1.1\r\n\r\n".repeat(3)).await;
+    assert_eq!(output.matches("HTTP/1.1 200 OK").count(), 2, "{output}");
+    let (first, second) = output.split_at(output.rfind("HTTP/1.1").unwrap());
+    assert!(!first.contains("Connection: close"), "{output}");
+    assert!(second.contains("Connection: close\r\n"), "{output}");
+}
+
+#[tokio::test]
+async fn handler_closes_connection() {
+    use low_profile::{IntoResponse, Response};
+
+    async fn reboot() -> Result<Response<&'static [u8]>, &'static str> {
+        Ok("rebooting".into_response().close_connection())
+    }
+    let router = router().get("/reboot", reboot);
+
+    let output = common::serve(
+        &router,
+        "GET / HTTP/1.1\r\n\r\nGET /reboot HTTP/1.1\r\n\r\nGET / HTTP/1.1\r\n\r\n",
+    )
+    .await;
+    let (first, second) = output.split_at(output.rfind("HTTP/1.1").unwrap());
+    assert!(first.ends_with("\r\n\r\nindex"), "{output}");
+    assert!(!first.contains("Connection: close"), "{output}");
+    assert!(second.contains("Connection: close\r\n"), "{output}");
+    assert!(second.ends_with("\r\n\r\nrebooting"), "{output}");
+}
+
+#[tokio::test]
+async fn head_too_large() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use low_profile::{CloseReason, ConnectionEvent};
+
+    static ERROR: AtomicBool = AtomicBool::new(false);
+    fn on_event(event: ConnectionEvent) {
+        if let ConnectionEvent::Closed(CloseReason::Error) = event {
+            ERROR.store(true, Ordering::Relaxed);
+        }
+    }
+
+    let router = router()
+        .with_config(low_profile::ServiceConfig::new().on_event(on_event))
+        .buffers::<64, 8, 64>();
+    let input = format!(
+        "GET / HTTP/1.1\r\n\r\nGET /{} HTTP/1.1\r\n\r\n",
+        "a".repeat(64)
+    );
+    let output = common::serve_chunked(&router, &input, 16).await;
+    let (first, second) = output.split_at(output.rfind("HTTP/1.1").unwrap());
+    assert!(first.ends_with("\r\n\r\nindex"), "{output}");
+    assert!(
+        second.starts_with("HTTP/1.1 431 Request Header Fields Too Large\r\n"),
+        "{output}"
+    );
+    assert!(second.contains("Connection: close\r\n"), "{output}");
+    assert!(ERROR.load(Ordering::Relaxed));
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use core::convert::Infallible;
+
+use low_profile::{
+    fs::{File, FileSystem, ServeDir},
+    ErrorType, Read, Router,
+};
+
+/// Serves every path, with the path as content.
+struct Echo;
+
+struct Content(heapless::Vec<u8, 128>);
+
+impl ErrorType for Content {
+    type Error = Infallible;
+}
+
+impl Read for Content {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Infallible> {
+        let len = buf.len().min(self.0.len());
+        buf[..len].copy_from_slice(&self.0[..len]);
+        self.0 = heapless::Vec::from_slice(&self.0[len..]).unwrap();
+        Ok(len)
+    }
+}
+
+impl File for Content {
+    fn size(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl FileSystem for Echo {
+    type File = Content;
+    type Error = Infallible;
+
+    async fn open(&self, path: &str) -> Result<Option<Content>, Infallible> {
+        Ok(Some(Content(
+            heapless::Vec::from_slice(path.as_bytes()).unwrap(),
+        )))
+    }
+}
+
+fn router() -> Router<(), impl low_profile::Route<()>, (), impl Sized> {
+    Router::new().get("/files/*path", ServeDir::new(Echo))
+}
+
+#[tokio::test]
+async fn serves_files() {
+    let output = common::serve(&router(), "GET /files/a/b.txt HTTP/1.1\r\n\r\n").await;
+    assert!(output.starts_with("HTTP/1.1 200 OK\r\n"), "{output}");
+    assert!(output.contains("Content-Type: text/plain"), "{output}");
+    assert!(output.ends_with("\r\n\r\na/b.txt"), "{output}");
+}
+
+#[tokio::test]
+async fn rejects_escaping_paths() {
+    for path in [
+        "/files/../secret.txt",
+        "/files/a/%2e%2e/%2E%2E/secret.txt",
+        "/files/secret.txt%00.png",
+        "/files/..%5Csecret.txt",
+        "/files/a%5Cb.txt",
+        "/files/a%0Ab.txt",
+    ] {
+        let output = common::serve(&router(), &format!("GET {path} HTTP/1.1\r\n\r\n")).await;
+        // Escaped control characters are already rejected when decoding the path.
+        assert!(
+            output.starts_with("HTTP/1.1 404 Not Found\r\n")
+                || output.starts_with("HTTP/1.1 400 Bad Request\r\n"),
+            "{path}: {output}"
+        );
+        assert!(!output.contains("secret"), "{path}: {output}");
+    }
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use low_profile::https::HttpsRedirect;
+
+#[tokio::test]
+async fn redirect() {
+    let output = common::serve(
+        &HttpsRedirect::new(),
+        "GET /a?b HTTP/1.1\r\nHost: example.com\r\n\r\n",
+    )
+    .await;
+    assert!(
+        output.starts_with("HTTP/1.1 301 Moved Permanently\r\n"),
+        "{output}"
+    );
+    assert!(
+        output.contains("Location: https://example.com/a?b\r\n"),
+        "{output}"
+    );
+}
+
+#[tokio::test]
+async fn head_too_large() {
+    let input = format!(
+        "GET /{} HTTP/1.1\r\nHost: example.com\r\n\r\n",
+        "a".repeat(64)
+    );
+    let output = common::serve(&HttpsRedirect::new().buffers::<64, 8>(), &input).await;
+    assert!(
+        output.starts_with("HTTP/1.1 400 Bad Request\r\n"),
+        "{output}"
+    );
+    assert!(!output.contains("Location"), "{output}");
+}
+
+#[tokio::test]
+async fn malformed_head() {
+    let output = common::serve(&HttpsRedirect::new(), "GET\0/ HTTP/1.1\r\n\r\n").await;
+    assert!(
+        output.starts_with("HTTP/1.1 400 Bad Request\r\n"),
+        "{output}"
+    );
+}
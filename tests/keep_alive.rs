@@ -0,0 +1,81 @@
+mod common;
+
+use low_profile::{Router, ServiceConfig};
+
+fn router() -> Router<(), impl low_profile::Route<()>, (), impl Sized> {
+    Router::new()
+        .get("/", || async { "index" })
+        .post("/ignore", || async { "ignored" })
+        .post("/echo", |body: heapless::Vec<u8, 64>| async move { body })
+}
+
+/// Splits the output into the responses, assuming no body contains `HTTP/1.1`.
+fn responses(output: &str) -> Vec<&str> {
+    output
+        .match_indices("HTTP/1.1 ")
+        .map(|(i, _)| i)
+        .chain([output.len()])
+        .collect::<Vec<_>>()
+        .windows(2)
+        .map(|range| &output[range[0]..range[1]])
+        .collect()
+}
+
+#[tokio::test]
+async fn pipelined_requests() {
+    let input = "GET / HTTP/1.1\r\n\r\n\
+                 POST /echo HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello\
+                 GET / HTTP/1.1\r\n\r\n";
+    for chunk in [1, 5, usize::MAX] {
+        let output = common::serve_chunked(&router(), input, chunk).await;
+        let responses = responses(&output);
+        assert_eq!(responses.len(), 3, "{output}");
+        assert!(responses[0].ends_with("\r\n\r\nindex"), "{output}");
+        assert!(responses[1].ends_with("\r\n\r\nhello"), "{output}");
+        assert!(responses[2].ends_with("\r\n\r\nindex"), "{output}");
+        assert!(!output.contains("Connection: close"), "{output}");
+    }
+}
+
+#[tokio::test]
+async fn unread_body_skipped() {
+    // Larger than the request buffer.
+    let body = "x".repeat(2000);
+    let input = format!(
+        "POST /ignore HTTP/1.1\r\nContent-Length: 2000\r\n\r\n{body}GET / HTTP/1.1\r\n\r\n"
+    );
+    for chunk in [7, usize::MAX] {
+        let output = common::serve_chunked(&router(), &input, chunk).await;
+        let responses = responses(&output);
+        assert_eq!(responses.len(), 2, "{output}");
+        assert!(responses[0].ends_with("\r\n\r\nignored"), "{output}");
+        assert!(responses[1].ends_with("\r\n\r\nindex"), "{output}");
+    }
+}
+
+#[tokio::test]
+async fn client_closes() {
+    let output = common::serve(
+        &router(),
+        "GET / HTTP/1.1\r\nConnection: close\r\n\r\nGET / HTTP/1.1\r\n\r\n",
+    )
+    .await;
+    let responses = responses(&output);
+    assert_eq!(responses.len(), 1, "{output}");
+    assert!(responses[0].contains("Connection: close\r\n"), "{output}");
+}
+
+#[tokio::test]
+async fn http10_closes() {
+    let output = common::serve(&router(), "GET / HTTP/1.0\r\n\r\nGET / HTTP/1.0\r\n\r\n").await;
+    assert_eq!(responses(&output).len(), 1, "{output}");
+    assert!(output.contains("Connection: close\r\n"), "{output}");
+}
+
+#[tokio::test]
+async fn keep_alive_disabled() {
+    let router = router().with_config(ServiceConfig::new().keep_alive(false));
+    let output = common::serve(&router, "GET / HTTP/1.1\r\n\r\nGET / HTTP/1.1\r\n\r\n").await;
+    assert_eq!(responses(&output).len(), 1, "{output}");
+    assert!(output.contains("Connection: close\r\n"), "{output}");
+}
@@ -0,0 +1,227 @@
+mod common;
+
+use low_profile::{
+    extract::State,
+    log::{LogBuffer, LogText},
+    Router,
+};
+
+struct App {
+    log: LogBuffer<64>,
+}
+
+async fn logs(State(app): State<&App>) -> LogText<'_, 64> {
+    app.log.text()
+}
+
+#[tokio::test]
+async fn text_keeps_connection() {
+    let app = App {
+        log: LogBuffer::new(),
+    };
+    app.log.push("booted");
+    app.log.push_fmt(format_args!("uptime {}s", 42));
+    let router = Router::new().get("/logs", logs).with_state(&app);
+
+    let output = common::serve(
+        &router,
+        "GET /logs HTTP/1.1\r\n\r\nGET /logs HTTP/1.1\r\nConnection: close\r\n\r\n",
+    )
+    .await;
+    let (first, second) = output.split_at(output.rfind("HTTP/1.1").unwrap());
+    assert!(first.contains("Content-Length: 18\r\n"), "{output}");
+    assert!(first.ends_with("\r\n\r\nbooted\nuptime 42s\n"), "{output}");
+    assert!(!first.contains("Connection: close"), "{output}");
+    assert!(second.ends_with("\r\n\r\nbooted\nuptime 42s\n"), "{output}");
+}
+
+#[tokio::test]
+async fn text_of_wrapped_buffer() {
+    let app = App {
+        log: LogBuffer::new(),
+    };
+    for i in 0..20 {
+        app.log.push_fmt(format_args!("line {i}"));
+    }
+    let router = Router::new().get("/logs", logs).with_state(&app);
+
+    let output = common::serve(&router, "GET /logs HTTP/1.1\r\n\r\n").await;
+    let body = &output[output.find("\r\n\r\n").unwrap() + 4..];
+    assert!(body.ends_with("line 19\n"), "{output}");
+    assert!(
+        output.contains(&format!("Content-Length: {}\r\n", body.len())),
+        "{output}"
+    );
+}
+
+mod tail {
+    use core::cell::RefCell;
+
+    use low_profile::{
+        extract::State,
+        log::{LagPolicy, LogBuffer, LogTail},
+        ErrorType, Read, Router, Service, Write,
+    };
+
+    #[derive(Debug)]
+    struct Closed;
+
+    impl embedded_io_async::Error for Closed {
+        fn kind(&self) -> embedded_io_async::ErrorKind {
+            embedded_io_async::ErrorKind::ConnectionReset
+        }
+    }
+
+    struct Request(&'static [u8]);
+
+    impl ErrorType for Request {
+        type Error = Closed;
+    }
+
+    impl Read for Request {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Closed> {
+            let len = buf.len().min(self.0.len());
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+
+    /// Client receiving the stream, `on_write` and `on_flush` act on the log.
+    struct Client<'a, F, G> {
+        output: &'a RefCell<String>,
+        on_write: F,
+        on_flush: G,
+    }
+
+    impl<F, G> ErrorType for Client<'_, F, G> {
+        type Error = Closed;
+    }
+
+    impl<F: FnMut(&str) -> bool, G: FnMut(&str) -> bool> Write for Client<'_, F, G> {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Closed> {
+            let mut output = self.output.borrow_mut();
+            output.push_str(core::str::from_utf8(buf).unwrap());
+            match (self.on_write)(&output) {
+                true => Ok(buf.len()),
+                false => Err(Closed),
+            }
+        }
+
+        async fn flush(&mut self) -> Result<(), Closed> {
+            match (self.on_flush)(&self.output.borrow()) {
+                true => Ok(()),
+                false => Err(Closed),
+            }
+        }
+    }
+
+    struct App {
+        log: LogBuffer<32>,
+        policy: LagPolicy,
+    }
+
+    async fn live(State(app): State<&App>) -> LogTail<'_, 32> {
+        app.log.tail().on_lag(app.policy)
+    }
+
+    fn events(output: &str) -> Vec<&str> {
+        output
+            .lines()
+            .filter_map(|line| line.strip_prefix("data: "))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn drop_newest() {
+        let app = App {
+            log: LogBuffer::new(),
+            policy: LagPolicy::DropNewest,
+        };
+        app.log.push("a1");
+        app.log.push("a2");
+        let router = Router::new().get("/", live).with_state(&app);
+
+        let output = RefCell::new(String::new());
+        let client = Client {
+            output: &output,
+            on_write: |output: &str| {
+                // The client is slow, lines are dropped before they are sent.
+                if output.ends_with("data: a1\n") {
+                    (0..20).for_each(|i| app.log.push_fmt(format_args!("b{i}")));
+                }
+                true
+            },
+            on_flush: |output: &str| {
+                if output.contains("c1") {
+                    return false;
+                }
+                app.log.push("c1");
+                true
+            },
+        };
+        let _ = router
+            .serve(Request(b"GET / HTTP/1.1\r\n\r\n"), client)
+            .await;
+        assert_eq!(events(&output.borrow()), ["a1", "a2", "c1"]);
+    }
+
+    #[tokio::test]
+    async fn drop_oldest() {
+        let app = App {
+            log: LogBuffer::new(),
+            policy: LagPolicy::DropOldest,
+        };
+        app.log.push("a1");
+        app.log.push("a2");
+        let router = Router::new().get("/", live).with_state(&app);
+
+        let output = RefCell::new(String::new());
+        let client = Client {
+            output: &output,
+            on_write: |output: &str| {
+                if output.ends_with("data: a1\n") {
+                    (0..20).for_each(|i| app.log.push_fmt(format_args!("b{i}")));
+                }
+                true
+            },
+            on_flush: |output: &str| !output.contains("b19"),
+        };
+        let _ = router
+            .serve(Request(b"GET / HTTP/1.1\r\n\r\n"), client)
+            .await;
+        let output = output.borrow();
+        let events = events(&output);
+        assert_eq!(events[..2], ["a1", "a2"]);
+        assert_eq!(events.last(), Some(&"b19"));
+        assert!(!events.contains(&"b0"), "{output}");
+    }
+
+    #[tokio::test]
+    async fn block() {
+        let app = App {
+            log: LogBuffer::new(),
+            policy: LagPolicy::Block,
+        };
+        let router = Router::new().get("/", live).with_state(&app);
+
+        let output = RefCell::new(String::new());
+        let client = Client {
+            output: &output,
+            on_write: |output: &str| !output.ends_with("data: line 19\n\n"),
+            on_flush: |_: &str| true,
+        };
+        let producer = async {
+            for i in 0..20 {
+                let line = format!("line {i}");
+                app.log.push_async(&line).await;
+            }
+        };
+        let _ = tokio::join!(
+            router.serve(Request(b"GET / HTTP/1.1\r\n\r\n"), client),
+            producer
+        );
+        let expected: Vec<_> = (0..20).map(|i| format!("line {i}")).collect();
+        assert_eq!(events(&output.borrow()), expected);
+    }
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use low_profile::{
+    extract::State,
+    mirror::{self, RequestLog},
+    Router,
+};
+
+struct App {
+    log: RequestLog<2, 256>,
+}
+
+fn recorded(log: &RequestLog<2, 256>) -> String {
+    String::from_utf8(log.render::<512>().to_vec()).unwrap()
+}
+
+async fn handler(State(_): State<&App>) -> &'static str {
+    "ok"
+}
+
+const REQUEST: &str = "GET /config?x=1 HTTP/1.1\r\n\
+    Authorization: Bearer secret\r\n\
+    cookie: session=secret\r\n\
+    Proxy-Authorization: Basic secret\r\n\
+    Accept: text/plain\r\n\
+    Connection: close\r\n\r\n";
+
+#[tokio::test]
+async fn redacts_credentials() {
+    let app = App {
+        log: RequestLog::new(),
+    };
+    let router = Router::new()
+        .get("/config", mirror::mirror(|app: &&App| &app.log, handler))
+        .with_state(&app);
+
+    let output = common::serve(&router, REQUEST).await;
+    assert!(output.ends_with("\r\n\r\nok"), "{output}");
+
+    let log = recorded(&app.log);
+    assert!(log.starts_with("GET /config?x=1\r\n"), "{log}");
+    assert!(log.contains("Authorization: [redacted]\r\n"), "{log}");
+    assert!(log.contains("cookie: [redacted]\r\n"), "{log}");
+    assert!(log.contains("Proxy-Authorization: [redacted]\r\n"), "{log}");
+    assert!(log.contains("Accept: text/plain\r\n"), "{log}");
+    assert!(!log.contains("secret"), "{log}");
+}
+
+#[tokio::test]
+async fn keeps_credentials() {
+    let app = App {
+        log: RequestLog::new().keep_credentials(),
+    };
+    let router = Router::new()
+        .get("/config", mirror::mirror(|app: &&App| &app.log, handler))
+        .with_state(&app);
+
+    common::serve(&router, REQUEST).await;
+
+    let log = recorded(&app.log);
+    assert!(log.contains("Authorization: Bearer secret\r\n"), "{log}");
+    assert!(log.contains("cookie: session=secret\r\n"), "{log}");
+}
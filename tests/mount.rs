@@ -0,0 +1,87 @@
+mod common;
+
+use low_profile::{mount::Mount, Router, Service};
+
+fn service() -> impl Service {
+    let dav = Router::<(), _>::new()
+        .get("/dav/*path", || async { "file" })
+        .post(
+            "/dav/upload",
+            |body: heapless::Vec<u8, 64>| async move { body },
+        );
+    let app = Router::<(), _>::new()
+        .get("/", || async { "index" })
+        .get("/dav/secret", || async { "secret" });
+    Mount::new("/dav", dav, app)
+}
+
+#[tokio::test]
+async fn serves_one_request_per_connection() {
+    let output = common::serve(
+        &service(),
+        "GET /dav/a HTTP/1.1\r\n\r\nGET / HTTP/1.1\r\n\r\n",
+    )
+    .await;
+    assert_eq!(output.matches("HTTP/1.1").count(), 1, "{output}");
+    assert!(output.contains("Connection: close\r\n"), "{output}");
+    assert!(output.ends_with("\r\n\r\nfile"), "{output}");
+
+    let output = common::serve(
+        &service(),
+        "GET / HTTP/1.1\r\n\r\nGET /dav/secret HTTP/1.1\r\n\r\n",
+    )
+    .await;
+    assert_eq!(output.matches("HTTP/1.1").count(), 1, "{output}");
+    assert!(output.contains("Connection: close\r\n"), "{output}");
+    assert!(output.ends_with("\r\n\r\nindex"), "{output}");
+}
+
+#[tokio::test]
+async fn pipelined_after_body() {
+    for chunk in [1, 7, usize::MAX] {
+        let output = common::serve_chunked(
+            &service(),
+            "POST /dav/upload HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello\
+             GET /dav/secret HTTP/1.1\r\n\r\n",
+            chunk,
+        )
+        .await;
+        assert_eq!(output.matches("HTTP/1.1").count(), 1, "{output}");
+        assert!(output.ends_with("\r\n\r\nhello"), "{output}");
+
+        let output = common::serve_chunked(
+            &service(),
+            "POST /dav/upload HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n\
+             2;x=y\r\nhe\r\n3\r\nllo\r\n0\r\nTrailer: 1\r\n\r\n\
+             GET /dav/secret HTTP/1.1\r\n\r\n",
+            chunk,
+        )
+        .await;
+        assert_eq!(output.matches("HTTP/1.1").count(), 1, "{output}");
+        assert!(output.ends_with("\r\n\r\nhello"), "{output}");
+    }
+}
+
+#[tokio::test]
+async fn connection_close_sent_once() {
+    let output = common::serve(
+        &service(),
+        "GET /dav/a HTTP/1.1\r\nConnection: close\r\n\r\n",
+    )
+    .await;
+    assert_eq!(output.matches("Connection: close").count(), 1, "{output}");
+}
+
+#[tokio::test]
+async fn head_too_large() {
+    let input = format!("GET /dav/{} HTTP/1.1\r\n\r\n", "a".repeat(64));
+    let output = common::serve(
+        &Mount::new("/dav", service(), service()).buffers::<64>(),
+        &input,
+    )
+    .await;
+    assert!(
+        output.starts_with("HTTP/1.1 431 Request Header Fields Too Large\r\n"),
+        "{output}"
+    );
+}
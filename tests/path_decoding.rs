@@ -0,0 +1,72 @@
+mod common;
+
+use low_profile::{extract::Path, Router};
+
+fn router() -> Router<(), impl low_profile::Route<()>, (), impl Sized> {
+    Router::new()
+        .get("/hello world", || async { "spaced" })
+        .get(
+            "/items/:id",
+            |Path(id): Path<heapless::String<32>>| async move { id },
+        )
+        .get(
+            "/files/*path",
+            |Path(path): Path<heapless::String<32>>| async move { path },
+        )
+}
+
+async fn get(path: &str) -> String {
+    common::serve(&router(), &format!("GET {path} HTTP/1.1\r\n\r\n")).await
+}
+
+async fn assert_rejected(path: &str) {
+    let output = get(path).await;
+    assert!(
+        output.starts_with("HTTP/1.1 400 Bad Request\r\n"),
+        "{path}: {output}"
+    );
+    assert!(output.contains("Connection: close\r\n"), "{path}: {output}");
+}
+
+#[tokio::test]
+async fn space() {
+    let output = get("/hello%20world").await;
+    assert!(output.ends_with("\r\n\r\nspaced"), "{output}");
+    let output = get("/items/a%20b?q=%20").await;
+    assert!(output.ends_with("\r\n\r\na b"), "{output}");
+}
+
+#[tokio::test]
+async fn utf8() {
+    let output = get("/items/%C3%A4").await;
+    assert!(output.ends_with("\r\n\r\n\u{e4}"), "{output}");
+}
+
+#[tokio::test]
+async fn encoded_slash_separates_segments() {
+    let output = get("/items/a%2Fb").await;
+    assert!(output.starts_with("HTTP/1.1 404 Not Found\r\n"), "{output}");
+    let output = get("/files/a%2Fb").await;
+    assert!(output.ends_with("\r\n\r\na/b"), "{output}");
+}
+
+#[tokio::test]
+async fn bad_escapes() {
+    assert_rejected("/items/%zz").await;
+    assert_rejected("/items/%4").await;
+    assert_rejected("/items/%4?q").await;
+    assert_rejected("/items/%").await;
+}
+
+#[tokio::test]
+async fn invalid_utf8() {
+    assert_rejected("/items/%ff").await;
+    assert_rejected("/items/%C3").await;
+}
+
+#[tokio::test]
+async fn control_characters() {
+    assert_rejected("/items/a%00.png").await;
+    assert_rejected("/items/a%0Ab").await;
+    assert_rejected("/items/a%7F").await;
+}
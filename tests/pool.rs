@@ -0,0 +1,55 @@
+mod common;
+
+use low_profile::{pool::BufferPool, Router};
+
+#[tokio::test]
+async fn exhausted_pool() {
+    static POOL: BufferPool<2, 2048> = BufferPool::new();
+
+    let router = Router::<(), _>::new().get("/", || async { "index" });
+    let serve = |input: &'static str| async {
+        let mut output = common::Output(Vec::new());
+        let input = common::Input::new(input);
+        router
+            .serve_pooled(input, &mut output, &POOL)
+            .await
+            .unwrap();
+        String::from_utf8(output.0).unwrap()
+    };
+
+    let output = serve("GET / HTTP/1.1\r\nConnection: close\r\n\r\n").await;
+    assert!(output.ends_with("\r\n\r\nindex"), "{output}");
+    assert_eq!(POOL.in_use(), 0);
+
+    let held = [POOL.take().unwrap(), POOL.take().unwrap()];
+    let output = serve("GET / HTTP/1.1\r\n\r\nGET / HTTP/1.1\r\n\r\n").await;
+    assert!(
+        output.starts_with("HTTP/1.1 503 Service Unavailable\r\n"),
+        "{output}"
+    );
+    assert!(output.contains("Connection: close\r\n"), "{output}");
+    assert_eq!(output.matches("HTTP/1.1").count(), 1, "{output}");
+    assert_eq!(POOL.exhausted(), 1);
+
+    drop(held);
+    assert_eq!(POOL.in_use(), 0);
+    assert_eq!(POOL.peak(), 2);
+    let output = serve("GET / HTTP/1.1\r\nConnection: close\r\n\r\n").await;
+    assert!(output.ends_with("\r\n\r\nindex"), "{output}");
+    assert_eq!(POOL.in_use(), 0);
+}
+
+#[tokio::test]
+async fn request_read_before_rejection() {
+    static POOL: BufferPool<1, 2048> = BufferPool::new();
+
+    let router = Router::<(), _>::new().get("/", || async { "index" });
+    let _held = POOL.take().unwrap();
+    let mut output = common::Output(Vec::new());
+    let mut input = common::Input::new("GET / HTTP/1.1\r\nHost: a\r\n\r\n");
+    router
+        .serve_pooled(&mut input, &mut output, &POOL)
+        .await
+        .unwrap();
+    assert!(input.is_empty());
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use core::convert::Infallible;
+
+use low_profile::{
+    response::{ResponseBody, ResponseWriter, WriteError},
+    Response, Router, Write,
+};
+
+async fn data() -> Response<&'static [u8]> {
+    Response::new(&b"0123456789"[..])
+}
+
+fn router() -> Router<(), impl low_profile::Route<()>, (), impl Sized> {
+    Router::new().get("/data", data).etag().accept_ranges()
+}
+
+async fn get(headers: &str) -> String {
+    let input = format!("GET /data HTTP/1.1\r\n{headers}Connection: close\r\n\r\n");
+    common::serve(&router(), &input).await
+}
+
+#[tokio::test]
+async fn complete() {
+    let output = get("").await;
+    assert!(output.starts_with("HTTP/1.1 200 OK\r\n"), "{output}");
+    assert!(output.contains("Accept-Ranges: bytes\r\n"), "{output}");
+    assert!(output.ends_with("\r\n\r\n0123456789"), "{output}");
+}
+
+#[tokio::test]
+async fn partial() {
+    let output = get("Range: bytes=2-4\r\n").await;
+    assert!(
+        output.starts_with("HTTP/1.1 206 Partial Content\r\n"),
+        "{output}"
+    );
+    assert!(
+        output.contains("Content-Range: bytes 2-4/10\r\n"),
+        "{output}"
+    );
+    assert!(output.contains("Content-Length: 3\r\n"), "{output}");
+    assert!(output.ends_with("\r\n\r\n234"), "{output}");
+
+    let output = get("Range: bytes=-3\r\n").await;
+    assert!(
+        output.contains("Content-Range: bytes 7-9/10\r\n"),
+        "{output}"
+    );
+    assert!(output.ends_with("\r\n\r\n789"), "{output}");
+}
+
+#[tokio::test]
+async fn partial_keeps_connection() {
+    let output = common::serve(
+        &router(),
+        "GET /data HTTP/1.1\r\nRange: bytes=8-\r\n\r\nGET /data HTTP/1.1\r\nConnection: close\r\n\r\n",
+    )
+    .await;
+    let (first, second) = output.split_at(output.rfind("HTTP/1.1").unwrap());
+    assert!(first.ends_with("\r\n\r\n89"), "{output}");
+    assert!(!first.contains("Connection: close"), "{output}");
+    assert!(second.ends_with("\r\n\r\n0123456789"), "{output}");
+}
+
+#[tokio::test]
+async fn unsatisfiable() {
+    let output = get("Range: bytes=20-\r\n").await;
+    assert!(
+        output.starts_with("HTTP/1.1 416 Range Not Satisfiable\r\n"),
+        "{output}"
+    );
+    assert!(output.contains("Content-Range: bytes */10\r\n"), "{output}");
+    assert!(
+        output.ends_with("Content-Length: 0\r\nConnection: close\r\n\r\n"),
+        "{output}"
+    );
+}
+
+#[tokio::test]
+async fn if_range() {
+    let output = get("").await;
+    let etag = output
+        .lines()
+        .find_map(|line| line.strip_prefix("ETag: "))
+        .unwrap();
+
+    let output = get(&format!("Range: bytes=0-1\r\nIf-Range: {etag}\r\n")).await;
+    assert!(
+        output.starts_with("HTTP/1.1 206 Partial Content\r\n"),
+        "{output}"
+    );
+    assert!(output.ends_with("\r\n\r\n01"), "{output}");
+
+    for if_range in ["\"other\"", "Wed, 21 Oct 2015 07:28:00 GMT"] {
+        let output = get(&format!("Range: bytes=0-1\r\nIf-Range: {if_range}\r\n")).await;
+        assert!(output.starts_with("HTTP/1.1 200 OK\r\n"), "{output}");
+        assert!(output.ends_with("\r\n\r\n0123456789"), "{output}");
+    }
+}
+
+/// Body claiming the largest possible length, writing nothing.
+struct Huge;
+
+impl ResponseBody for Huge {
+    type Error = Infallible;
+
+    fn content_length(&self) -> Option<usize> {
+        Some(usize::MAX)
+    }
+
+    async fn write_to<W: Write>(
+        self,
+        _writer: &mut ResponseWriter<'_, W>,
+        _buf: &mut [u8],
+    ) -> Result<(), WriteError<W::Error, Infallible>> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn longest_content_range() {
+    let router = Router::<(), _>::new()
+        .get("/", || async { Response::new(Huge) })
+        .accept_ranges();
+    let max = usize::MAX;
+    let input = format!(
+        "GET / HTTP/1.1\r\nRange: bytes={}-{}\r\n\r\n",
+        max - 5,
+        max - 1
+    );
+    let output = common::serve(&router, &input).await;
+    let content_range = format!("Content-Range: bytes {}-{}/{max}\r\n", max - 5, max - 1);
+    assert!(output.contains(&content_range), "{output}");
+}
@@ -0,0 +1,24 @@
+mod common;
+
+use low_profile::{response::Redirect, Router};
+
+#[tokio::test]
+async fn location_with_line_break() {
+    let router = Router::<(), _>::new().get("/", || async {
+        Redirect::found("/next\r\nSet-Cookie: session=stolen")
+    });
+    let output = common::serve(&router, "GET / HTTP/1.1\r\nConnection: close\r\n\r\n").await;
+    assert!(
+        output.starts_with("HTTP/1.1 500 Internal Server Error\r\n"),
+        "{output}"
+    );
+    assert!(!output.contains("Location"), "{output}");
+    assert!(!output.contains("Set-Cookie"), "{output}");
+}
+
+#[tokio::test]
+async fn location_with_control_character() {
+    let redirect = Redirect::temporary("/next\0");
+    assert_eq!(redirect.location(), None);
+    assert_eq!(Redirect::temporary("/next").location(), Some("/next"));
+}
@@ -0,0 +1,33 @@
+mod common;
+
+use low_profile::{rewrite::Rewrite, Router};
+
+fn router() -> Router<(), impl low_profile::Route<()>, (), impl Sized> {
+    Router::new()
+        .get("/", || async { "index" })
+        .get("/status", || async { "ok" })
+        .rewrite(Rewrite::prefix("/app", "/"))
+        .rewrite(Rewrite::prefix("/old", "/").redirect())
+}
+
+#[tokio::test]
+async fn prefix_to_root() {
+    let output = common::serve(
+        &router(),
+        "GET /app HTTP/1.1\r\n\r\nGET /app/status HTTP/1.1\r\nConnection: close\r\n\r\n",
+    )
+    .await;
+    let (first, second) = output.split_at(output.rfind("HTTP/1.1").unwrap());
+    assert!(first.ends_with("\r\n\r\nindex"), "{output}");
+    assert!(second.ends_with("\r\n\r\nok"), "{output}");
+}
+
+#[tokio::test]
+async fn prefix_redirect_to_root() {
+    let output = common::serve(&router(), "GET /old HTTP/1.1\r\nConnection: close\r\n\r\n").await;
+    assert!(
+        output.starts_with("HTTP/1.1 301 Moved Permanently\r\n"),
+        "{output}"
+    );
+    assert!(output.contains("Location: /\r\n"), "{output}");
+}
@@ -0,0 +1,37 @@
+use low_profile::{
+    extract::{FieldError, FieldErrors},
+    response::ResponseBody,
+    IntoResponse,
+};
+
+fn body(response: impl IntoResponse) -> String {
+    let response = response.into_response();
+    String::from_utf8(response.body().as_bytes().unwrap().to_vec()).unwrap()
+}
+
+#[test]
+fn field_error_escaped() {
+    assert_eq!(
+        body(FieldError::new("na\"me", "line\nbreak")),
+        r#"{"errors":[{"field":"na\"me","message":"line\nbreak"}]}"#
+    );
+}
+
+#[test]
+fn field_error_too_long() {
+    let message: &'static str = "\"".repeat(200).leak();
+    assert_eq!(body(FieldError::new("name", message)), r#"{"errors":[]}"#);
+}
+
+#[test]
+fn field_errors_too_long() {
+    let message: &'static str = "x".repeat(600).leak();
+    let mut errors = FieldErrors::<4>::new();
+    errors
+        .ensure(false, "first", message)
+        .ensure(false, "second", message);
+    let body = body(errors);
+    assert!(body.starts_with(r#"{"errors":[{"field":"first""#), "{body}");
+    assert!(body.ends_with(r#"}]}"#), "{body}");
+    assert!(!body.contains("second"), "{body}");
+}
@@ -0,0 +1,37 @@
+#![cfg(feature = "std")]
+
+use std::{sync::Arc, thread, time::Duration};
+
+use low_profile::watch::{Subscriber, SyncWatched};
+
+#[tokio::test]
+async fn update_from_other_thread() {
+    let temperature = Arc::new(SyncWatched::new(20));
+    let mut subscriber = Subscriber::new(&*temperature);
+
+    let sensor = {
+        let temperature = temperature.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            temperature.set(21);
+        })
+    };
+    assert_eq!(subscriber.next().await, 21);
+    assert_eq!(temperature.with(|value| *value), 21);
+    sensor.join().unwrap();
+}
+
+#[test]
+fn panicking_update_poisons() {
+    let pair = SyncWatched::new((1, 1));
+    let result = std::panic::catch_unwind(|| {
+        pair.update(|pair| {
+            pair.0 = 2;
+            panic!("update interrupted");
+        })
+    });
+    assert!(result.is_err());
+    // The half updated pair is never handed out.
+    let result = std::panic::catch_unwind(|| pair.with(|pair| *pair));
+    assert!(result.is_err());
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use core::convert::Infallible;
+
+use low_profile::{
+    response::ReadBody, ErrorType, Read, Response, Router, Service, ServiceConfig, Write,
+};
+
+/// Client recording the length of every write, a flush ends a group of writes.
+#[derive(Default)]
+struct Client {
+    output: Vec<u8>,
+    writes: Vec<Vec<usize>>,
+}
+
+impl ErrorType for Client {
+    type Error = Infallible;
+}
+
+impl Write for Client {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Infallible> {
+        self.output.extend_from_slice(buf);
+        match self.writes.last_mut() {
+            Some(writes) => writes.push(buf.len()),
+            None => self.writes.push(vec![buf.len()]),
+        }
+        Ok(buf.len())
+    }
+
+    async fn flush(&mut self) -> Result<(), Infallible> {
+        self.writes.push(Vec::new());
+        Ok(())
+    }
+}
+
+/// Body source returning at most 7 bytes per read.
+struct ShortReads(&'static [u8]);
+
+impl ErrorType for ShortReads {
+    type Error = Infallible;
+}
+
+impl Read for ShortReads {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Infallible> {
+        let len = buf.len().min(self.0.len()).min(7);
+        buf[..len].copy_from_slice(&self.0[..len]);
+        self.0 = &self.0[len..];
+        Ok(len)
+    }
+}
+
+const BODY: &str = "0123456789abcdefghijklmnopqrstuvwxyz0123456789abcdefghijklmnopqrstuvwxyz";
+
+async fn serve<S: Service>(service: &S) -> Client {
+    let mut client = Client::default();
+    let input = common::Input::new("GET / HTTP/1.1\r\nConnection: close\r\n\r\n");
+    service.serve(input, &mut client).await.unwrap();
+    client
+}
+
+fn assert_chunked(client: &Client, size: usize) {
+    let output = String::from_utf8_lossy(&client.output);
+    assert!(output.ends_with(BODY), "{output}");
+    for writes in &client.writes {
+        if let Some((_, whole)) = writes.split_last() {
+            assert!(
+                whole.iter().all(|len| len % size == 0),
+                "{:?}",
+                client.writes
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn slice_body() {
+    let router = Router::<(), _>::new()
+        .get("/", || async { BODY })
+        .with_config(ServiceConfig::new().write_chunk(16));
+
+    let client = serve(&router).await;
+    assert_chunked(&client, 16);
+}
+
+#[tokio::test]
+async fn short_reads() {
+    let router = Router::<(), _>::new()
+        .get("/", || async {
+            Response::new(ReadBody(ShortReads(BODY.as_bytes())))
+        })
+        .with_config(ServiceConfig::new().write_chunk(16));
+
+    let client = serve(&router).await;
+    assert_chunked(&client, 16);
+}
+
+#[tokio::test]
+#[should_panic(expected = "write chunk size")]
+async fn chunk_larger_than_buffer() {
+    let router = Router::<(), _>::new()
+        .get("/", || async { BODY })
+        .buffers::<256, 8, 32>()
+        .with_config(ServiceConfig::new().write_chunk(64));
+
+    serve(&router).await;
+}